@@ -1,34 +1,64 @@
-use std::time::{Duration, Instant};
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
 
 use device_query::Keycode;
 use eframe::{egui, epaint::FontId};
-use rand::{prelude::ThreadRng, rng};
+use image::RgbImage;
 use rdev::Button;
+use rusty_autoclicker::{
+    engine::{
+        ClickError, ClickJob, JobEvent, TargetPoint,
+        backend::InputError,
+        backend::{BackendKind, InputBackend, NullBackend},
+        clock::SystemClock,
+        defines::DEFAULT_MAX_FAILURES,
+        history::{ClickHistory, HistoryFormat, RecordingBackend},
+        pixel::{self, Color, PixelCondition},
+        runner::JobRunner,
+        screen::{self, Monitor, Region},
+        sequence::{Sequence, SequencePlayer},
+        stats::ClickStats,
+        template::ImageTarget,
+        window::{self, FocusGuard, WindowInfo, WindowMatch},
+    },
+    types::{AppMode, ClickButton, ClickPosition, ClickType, CoordMode, StopReason},
+};
+use tracing::{debug, error, info, level_filters::LevelFilter, warn};
 
+#[cfg(unix)]
+use crate::ipc::{IpcCommand, IpcEvent, IpcServer};
 use crate::{
     defines::*,
-    types::{AppMode, ClickButton, ClickPosition, ClickType},
+    fields::NumberField,
+    jobs::{JobSpec, JobState},
+    logging::LogBuffer,
+    profiles::{self, Profile, ProfileAction},
+    settings::{self, Settings},
+    utils::{coord_range, duration_of, format_coord},
 };
 
-/// We derive Deserialize/Serialize so we can persist app state on shutdown.
-#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
-#[cfg_attr(feature = "persistence", serde(default))] // if we add new fields, give them default values when deserializing old state
+/// The outcome of a background search of the target image: its center, if found
+pub type ImageSearch = Receiver<Result<(f64, f64), ClickError>>;
+
+/// User-facing settings are persisted through `settings::Settings`, see `save_settings_if_changed`.
 pub struct RustyAutoClickerApp {
-    // this how you opt-out of serialization of a member
-    #[cfg_attr(feature = "persistence", serde(skip))]
-    // Text input strings
-    pub hr_str: String,
-    pub min_str: String,
-    pub sec_str: String,
-    pub ms_str: String,
-    pub click_amount_str: String,
-    pub click_x_str: String,
-    pub click_y_str: String,
-    pub movement_sec_str: String,
-    pub movement_ms_str: String,
-
-    // Time
-    pub last_now: Instant,
+    // Number inputs
+    pub hr: NumberField<u64>,
+    pub min: NumberField<u64>,
+    pub sec: NumberField<f64>,
+    pub ms: NumberField<f64>,
+    pub click_amount: NumberField<u64>,
+    pub click_x: NumberField<f64>,
+    pub click_y: NumberField<f64>,
+    pub movement_sec: NumberField<f64>,
+    pub movement_ms: NumberField<f64>,
+
+    // Time, & when the first click of the main job is due
+    pub first_click_due: Instant,
     pub frame_start: Instant,
 
     // Counter
@@ -43,13 +73,37 @@ pub struct RustyAutoClickerApp {
     pub is_setting_coord: bool,
     pub is_setting_autoclick_key: bool,
     pub is_setting_set_coord_key: bool,
+    pub stop_reason: Option<StopReason>,
 
     // App mode
     pub app_mode: AppMode,
 
     // Window state
     pub hotkey_window_open: bool,
-    pub window_position: egui::Pos2,
+    pub profile_window_open: bool,
+    pub options_window_open: bool,
+    pub stats_window_open: bool,
+    pub log_panel_open: bool,
+    pub jobs_panel_open: bool,
+    pub timeline_window_open: bool,
+
+    // Profiles
+    pub profile_names: Vec<String>,
+    pub active_profile: Option<String>,
+    pub profile_action: ProfileAction,
+    pub profile_name_str: String,
+    pub profile_error: Option<String>,
+
+    // Settings as last saved to disk, and the last ones that could not be saved, not to retry
+    // saving them on every frame
+    pub saved_settings: Option<Settings>,
+    pub unsaved_settings: Option<Settings>,
+
+    // Control socket & state last reported to its clients
+    #[cfg(unix)]
+    pub ipc_server: Option<IpcServer>,
+    pub ipc_was_autoclicking: bool,
+    pub ipc_reported_clicks: u64,
 
     // Key states
     pub key_pressed_autoclick: bool,
@@ -61,26 +115,107 @@ pub struct RustyAutoClickerApp {
     pub click_btn: ClickButton,
     pub click_type: ClickType,
     pub click_position: ClickPosition,
-    // RNG
-    pub rng_thread: ThreadRng,
+    pub coord_mode: CoordMode,
+    pub window_match: WindowMatch,
+
+    // Points clicked in turn instead of the coordinates, & those captured on the overlay so far
+    pub points: Vec<TargetPoint>,
+    pub captured_points: Vec<TargetPoint>,
+    pub is_capturing_points: bool,
+
+    // Extra jobs running alongside the main one, & the job whose hotkey is being set
+    pub jobs: Vec<JobSpec>,
+    pub job_states: Vec<JobState>,
+    pub setting_job_key: Option<usize>,
+
+    // Timeline of actions, the sequence being played & the step being edited
+    pub sequence: Sequence,
+    pub sequence_player: Option<SequencePlayer>,
+    pub selected_step: Option<usize>,
+    /// Outcome of the last xdotool import or export
+    pub timeline_status: Option<String>,
+
+    // Monitors & windows of the display, refreshed when coordinates are set or converted
+    pub monitors: Vec<Monitor>,
+    pub windows: Vec<WindowInfo>,
+
+    // Image the coordinates are relative to, with its center when last searched
+    pub image_target: ImageTarget,
+    pub image_center: Option<(f64, f64)>,
+    /// The search running in the background, & whether to search again once it is over
+    pub image_search: Option<ImageSearch>,
+    pub image_search_again: bool,
+
+    // Only click while a window is focused, picked as the focused window when the countdown ends
+    pub focus_guard_enabled: bool,
+    pub focus_guard: FocusGuard,
+    pub focus_pick_deadline: Option<Instant>,
+
+    // Wait for, click while or stop when a pixel has a color, picked with the eyedropper
+    pub pixel_condition_enabled: bool,
+    pub pixel_condition: PixelCondition,
+    pub is_picking_pixel: bool,
+
+    // Point picked on the overlay: the cursor nudged with the arrow keys, & the pixels around it
+    pub picked_point: (i32, i32),
+    pub picker_cursor: (i32, i32),
+    pub picker_nudge: (i32, i32),
+    pub picker_click_held: bool,
+    pub magnifier: Option<(Region, RgbImage)>,
+
+    // Seed of the random humanlike timings
+    pub humanlike_seed: Option<u64>,
+
+    // Input backend, created when the first click is sent
+    pub backend_kind: BackendKind,
+    pub input_backend: Option<(BackendKind, Box<dyn InputBackend>)>,
+    pub input_error: Option<String>,
+    pub failure_counter: u64,
+    pub max_consecutive_failures: u32,
+
+    // Main job while autoclicking
+    pub main_runner: Option<JobRunner>,
+
+    // Dry run, sending the events nowhere and drawing them in an overlay instead
+    pub dry_run: bool,
+    pub dry_run_markers: VecDeque<((f64, f64), Instant)>,
+    pub dry_run_path: Vec<(f64, f64)>,
+
+    // Markers of the targets while autoclicking, & where the last clicks landed
+    pub show_targets: bool,
+    pub click_flashes: VecDeque<((f64, f64), Instant)>,
+
+    // Compact bar replacing the window while autoclicking, & the window to restore afterwards
+    pub compact_mode: bool,
+    pub is_compact: bool,
+    pub full_window: Option<(egui::Pos2, egui::Vec2)>,
+
+    // Timing statistics & injected events of the current session
+    pub click_stats: ClickStats,
+    pub click_history: ClickHistory,
+    pub history_export_status: Option<String>,
+
+    // Log panel
+    pub log_buffer: LogBuffer,
+    pub log_level: LevelFilter,
 }
 
 impl Default for RustyAutoClickerApp {
     fn default() -> Self {
         Self {
-            // Text input strings
-            hr_str: DEFAULT_HR_STR.to_owned(),
-            min_str: DEFAULT_MIN_STR.to_owned(),
-            sec_str: DEFAULT_SEC_STR.to_owned(),
-            ms_str: DEFAULT_MS_STR.to_owned(),
-            click_amount_str: DEFAULT_CLICK_AMOUNT_STR.to_owned(),
-            click_x_str: DEFAULT_CLICK_X_STR.to_owned(),
-            click_y_str: DEFAULT_CLICK_Y_STR.to_owned(),
-            movement_sec_str: DEFAULT_MOVEMENT_SEC_STR.to_owned(),
-            movement_ms_str: DEFAULT_MOVEMENT_MS_STR.to_owned(),
-
-            // Time
-            last_now: Instant::now(),
+            // Number inputs
+            hr: NumberField::new(DEFAULT_HR, HR_RANGE),
+            min: NumberField::new(DEFAULT_MIN, MIN_RANGE),
+            sec: NumberField::new(DEFAULT_SEC, SEC_RANGE),
+            ms: NumberField::new(DEFAULT_MS, MS_RANGE),
+            click_amount: NumberField::new(DEFAULT_CLICK_AMOUNT, CLICK_AMOUNT_RANGE),
+            click_x: NumberField::new(DEFAULT_CLICK_X, coord_range(CoordMode::Absolute)),
+            click_y: NumberField::new(DEFAULT_CLICK_Y, coord_range(CoordMode::Absolute)),
+            movement_sec: NumberField::new(DEFAULT_MOVEMENT_SEC, MOVEMENT_SEC_RANGE),
+            movement_ms: NumberField::new(DEFAULT_MOVEMENT_MS, MOVEMENT_MS_RANGE),
+
+            // Time, & when the first click of the main job is due
+            first_click_due: Instant::now(),
             frame_start: Instant::now(),
 
             // Counter
@@ -95,13 +230,36 @@ impl Default for RustyAutoClickerApp {
             is_setting_coord: false,
             is_setting_autoclick_key: false,
             is_setting_set_coord_key: false,
+            stop_reason: None,
 
             // App mode
             app_mode: AppMode::Bot,
 
             // Window state
             hotkey_window_open: false,
-            window_position: egui::Pos2 { x: 0f32, y: 0f32 },
+            profile_window_open: false,
+            options_window_open: false,
+            stats_window_open: false,
+            log_panel_open: false,
+            jobs_panel_open: false,
+            timeline_window_open: false,
+
+            // Profiles
+            profile_names: Vec::new(),
+            active_profile: None,
+            profile_action: ProfileAction::SaveAs,
+            profile_name_str: String::new(),
+            profile_error: None,
+
+            // Settings as last saved to disk
+            saved_settings: None,
+            unsaved_settings: None,
+
+            // Control socket & state last reported to its clients
+            #[cfg(unix)]
+            ipc_server: None,
+            ipc_was_autoclicking: false,
+            ipc_reported_clicks: 0u64,
 
             // Key states
             key_pressed_autoclick: false,
@@ -113,15 +271,77 @@ impl Default for RustyAutoClickerApp {
             click_btn: ClickButton::Mouse(Button::Left),
             click_type: ClickType::Single,
             click_position: ClickPosition::Mouse,
+            coord_mode: CoordMode::Absolute,
+            window_match: WindowMatch::default(),
+            points: Vec::new(),
+            captured_points: Vec::new(),
+            is_capturing_points: false,
+            jobs: Vec::new(),
+            job_states: Vec::new(),
+            setting_job_key: None,
+            sequence: Sequence::default(),
+            sequence_player: None,
+            selected_step: None,
+            timeline_status: None,
+
+            // Monitors & windows of the display, refreshed when coordinates are set or converted
+            monitors: Vec::new(),
+            windows: Vec::new(),
+            image_target: ImageTarget::default(),
+            image_center: None,
+            image_search: None,
+            image_search_again: false,
+            focus_guard_enabled: false,
+            focus_guard: FocusGuard::default(),
+            focus_pick_deadline: None,
+            pixel_condition_enabled: false,
+            pixel_condition: PixelCondition::default(),
+            is_picking_pixel: false,
+            picked_point: (0i32, 0i32),
+            picker_cursor: (0i32, 0i32),
+            picker_nudge: (0i32, 0i32),
+            picker_click_held: true,
+            magnifier: None,
 
-            // RNG
-            rng_thread: rng(),
+            // Seed of the random humanlike timings
+            humanlike_seed: None,
+
+            // Input backend, created when the first click is sent
+            backend_kind: BackendKind::default(),
+            input_backend: None,
+            input_error: None,
+            failure_counter: 0u64,
+            max_consecutive_failures: DEFAULT_MAX_FAILURES,
+
+            // Main job while autoclicking
+            main_runner: None,
+
+            // Dry run, sending the events nowhere and drawing them in an overlay instead
+            dry_run: false,
+            dry_run_markers: VecDeque::new(),
+            dry_run_path: Vec::new(),
+            show_targets: false,
+            click_flashes: VecDeque::new(),
+
+            // Compact bar replacing the window while autoclicking, & the window to restore afterwards
+            compact_mode: true,
+            is_compact: false,
+            full_window: None,
+
+            // Timing statistics & injected events of the current session
+            click_stats: ClickStats::default(),
+            click_history: ClickHistory::default(),
+            history_export_status: None,
+
+            // Log panel
+            log_buffer: LogBuffer::default(),
+            log_level: LevelFilter::INFO,
         }
     }
 }
 
 impl RustyAutoClickerApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, log_buffer: LogBuffer) -> Self {
         let ctx = &cc.egui_ctx;
 
         let mut style = (*ctx.style()).clone();
@@ -132,14 +352,63 @@ impl RustyAutoClickerApp {
         style.override_font_id = Some(font);
         ctx.set_style(style);
 
-        // Load previous app state (if any).
-        // Note that you must enable the `persistence` feature for this to work.
-        #[cfg(feature = "persistence")]
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        let mut app = Self {
+            profile_names: profiles::list(),
+            log_buffer,
+            ..Default::default()
+        };
+
+        // Load previous settings (if any)
+        match settings::load() {
+            Ok(Some(saved_settings)) => {
+                saved_settings.apply(&mut app);
+                app.saved_settings = Some(saved_settings);
+            }
+            Ok(None) => (),
+            Err(e) => warn!("Could not load settings: {e}"),
         }
 
-        Default::default()
+        // Needed to pick a monitor for percentage coordinates & show the picked windows
+        app.refresh_monitors();
+        if app.coord_mode == CoordMode::Window || app.focus_guard_enabled {
+            app.refresh_windows();
+        }
+
+        // Listen for control commands
+        #[cfg(unix)]
+        match IpcServer::start() {
+            Ok(ipc_server) => app.ipc_server = Some(ipc_server),
+            Err(e) => warn!("Could not start the control socket: {e}"),
+        }
+
+        app
+    }
+
+    /// Save the settings to disk if they changed since last saved
+    /// Transient states (setting coordinates or hotkeys) are not saved
+    pub fn save_settings_if_changed(&mut self) {
+        if self.is_setting_coord || self.is_setting_autoclick_key || self.is_setting_set_coord_key {
+            return;
+        }
+
+        let current_settings = Settings::from(&*self);
+        if self.saved_settings.as_ref() == Some(&current_settings)
+            || self.unsaved_settings.as_ref() == Some(&current_settings)
+        {
+            return;
+        }
+        match settings::save(&current_settings) {
+            Ok(()) => {
+                self.saved_settings = Some(current_settings);
+                self.unsaved_settings = None;
+            }
+            Err(e) => {
+                error!("Could not save settings: {e}");
+                self.input_error = Some(format!("Could not save the settings: {e}"));
+                // Retried once they change
+                self.unsaved_settings = Some(current_settings);
+            }
+        }
     }
 
     /// Enter the coordinate setting mode
@@ -148,26 +417,360 @@ impl RustyAutoClickerApp {
     ///
     /// * `ctx` - The ctx to manipulate
     pub fn enter_coordinate_setting(&mut self, ctx: &egui::Context) {
+        self.refresh_targets();
         self.is_setting_coord = true;
-        self.window_position =
-            ctx.input(|input_state| input_state.viewport().outer_rect.unwrap().min);
-        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(400f32, 30f32)));
-        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
+        self.picker_nudge = (0i32, 0i32);
+        // The click that opened the overlay does not pick a point
+        self.picker_click_held = true;
+        ctx.request_repaint();
     }
 
-    /// Make frame follow cursor with an offset
+    /// Enter the coordinate setting mode to capture the points of a target list
     ///
     /// # Arguments
     ///
-    /// * `ctx` - The ctx to set the window position on
-    pub fn follow_cursor(&mut self, ctx: &egui::Context) {
-        let offset = egui::Vec2 { x: 15f32, y: 15f32 };
-        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(
-            egui::pos2(
-                self.click_x_str.parse().unwrap(),
-                self.click_y_str.parse().unwrap(),
-            ) + offset,
-        ));
+    /// * `ctx` - The ctx to manipulate
+    pub fn enter_point_capture(&mut self, ctx: &egui::Context) {
+        self.is_capturing_points = true;
+        self.captured_points.clear();
+        self.enter_coordinate_setting(ctx);
+    }
+
+    /// Whether a key was pressed since the last frame
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys currently pressed
+    /// * `key` - The key to check
+    pub fn key_just_pressed(&self, keys: &[Keycode], key: Keycode) -> bool {
+        keys.contains(&key)
+            && !self
+                .keys_pressed
+                .as_ref()
+                .is_some_and(|keys_pressed| keys_pressed.contains(&key))
+    }
+
+    /// Whether the left mouse button was pressed since the last frame
+    ///
+    /// # Arguments
+    ///
+    /// * `pressed` - Whether the left mouse button is currently pressed
+    pub fn picker_clicked(&mut self, pressed: bool) -> bool {
+        let clicked = pressed && !self.picker_click_held;
+        self.picker_click_held = pressed;
+        clicked
+    }
+
+    /// Move the picked point to the cursor, or one pixel per arrow key pressed since last frame,
+    /// then capture the pixels around it for the magnifier
+    ///
+    /// # Arguments
+    ///
+    /// * `mouse_coord` - The mouse coordinates
+    /// * `keys` - The keys currently pressed
+    pub fn update_picked_point(&mut self, mouse_coord: (i32, i32), keys: &[Keycode]) -> (i32, i32) {
+        // Moving the mouse forgets the nudge
+        if mouse_coord != self.picker_cursor {
+            self.picker_cursor = mouse_coord;
+            self.picker_nudge = (0i32, 0i32);
+        }
+        for (key, (dx, dy)) in [
+            (Keycode::Left, (-1i32, 0i32)),
+            (Keycode::Right, (1i32, 0i32)),
+            (Keycode::Up, (0i32, -1i32)),
+            (Keycode::Down, (0i32, 1i32)),
+        ] {
+            if self.key_just_pressed(keys, key) {
+                self.picker_nudge.0 += dx;
+                self.picker_nudge.1 += dy;
+            }
+        }
+        self.picked_point = (
+            mouse_coord.0 + self.picker_nudge.0,
+            mouse_coord.1 + self.picker_nudge.1,
+        );
+
+        // Only capture the part of the magnifier that is on the screen
+        let around = Region {
+            x: self.picked_point.0 - MAGNIFIER_RADIUS,
+            y: self.picked_point.1 - MAGNIFIER_RADIUS,
+            width: (MAGNIFIER_RADIUS * 2 + 1) as u32,
+            height: (MAGNIFIER_RADIUS * 2 + 1) as u32,
+        };
+        self.magnifier = Monitor::bounds(&self.monitors)
+            .and_then(|screen| Region::from(screen).intersection(&around))
+            .and_then(|region| pixel::capture(region).ok().map(|image| (region, image)));
+        self.picked_point
+    }
+
+    /// The color of the picked point in the last capture of the magnifier
+    pub fn picked_color(&self) -> Option<Color> {
+        let (region, image) = self.magnifier.as_ref()?;
+        let x = u32::try_from(self.picked_point.0 - region.x).ok()?;
+        let y = u32::try_from(self.picked_point.1 - region.y).ok()?;
+        let [r, g, b] = image.get_pixel_checked(x, y)?.0;
+        Some(Color::new(r, g, b))
+    }
+
+    /// Read the monitors of the display again, keeping the previous ones if it fails
+    pub fn refresh_monitors(&mut self) {
+        match screen::monitors() {
+            Ok(monitors) => self.monitors = monitors,
+            Err(e) => warn!("Could not read the monitors: {e}"),
+        }
+    }
+
+    /// Read the top-level windows again, keeping the previous ones if it fails
+    pub fn refresh_windows(&mut self) {
+        match window::windows() {
+            Ok(windows) => self.windows = windows,
+            Err(e) => warn!("Could not read the windows: {e}"),
+        }
+    }
+
+    /// Read what the coordinates are relative to again: the monitors, & the window or image
+    pub fn refresh_targets(&mut self) {
+        self.refresh_monitors();
+        match self.coord_mode {
+            CoordMode::Window => self.refresh_windows(),
+            CoordMode::Image => self.refresh_image_center(),
+            _ => (),
+        }
+    }
+
+    /// Search the target image on the screen again in a background thread, so that the frames
+    /// are not held up by the search, see [`poll_image_search`](Self::poll_image_search)
+    /// Refreshing while a search runs searches once more after it, rather than searching twice
+    /// at once
+    pub fn refresh_image_center(&mut self) {
+        if self.image_search.is_some() {
+            self.image_search_again = true;
+            return;
+        }
+
+        let job = ClickJob::new()
+            .target((0.0, 0.0))
+            .image(self.image_target.clone());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            // The app may be gone by the time the search is over
+            let _ = sender.send(job.image_center(&NullBackend));
+        });
+        self.image_search = Some(receiver);
+    }
+
+    /// Take the center of the target image once the background search is over, forgetting it if
+    /// the image was not found, & hand it to the main job for its next click
+    /// Starts another search when the main job waits for one
+    pub fn poll_image_search(&mut self) {
+        if let Some(image_search) = &self.image_search {
+            let result = match image_search.try_recv() {
+                Err(TryRecvError::Empty) => return,
+                Ok(result) => result,
+                Err(TryRecvError::Disconnected) => Err(ClickError::target(InputError::new(
+                    "The image search stopped without a result",
+                ))),
+            };
+            self.image_search = None;
+            self.image_center = match &result {
+                Ok(center) => Some(*center),
+                Err(e) => {
+                    warn!("{e}");
+                    None
+                }
+            };
+            if let Some(runner) = self.main_runner.as_mut()
+                && runner.needs_image_center()
+            {
+                runner.set_image_center(result);
+            }
+        }
+
+        if std::mem::take(&mut self.image_search_again)
+            || self
+                .main_runner
+                .as_ref()
+                .is_some_and(JobRunner::needs_image_center)
+        {
+            self.refresh_image_center();
+        }
+    }
+
+    /// The target window among the windows last read
+    pub fn target_window(&self) -> Result<&WindowInfo, InputError> {
+        self.window_match
+            .find(&self.windows)?
+            .ok_or_else(|| InputError::new(format!("{} not found", self.window_match)))
+    }
+
+    /// Pick the focused window as the guarded window once the pick countdown is over
+    pub fn pick_focus_window_if_due(&mut self) {
+        if self
+            .focus_pick_deadline
+            .is_none_or(|deadline| Instant::now() < deadline)
+        {
+            return;
+        }
+        self.focus_pick_deadline = None;
+
+        self.refresh_windows();
+        match window::active_window() {
+            Ok(Some(id)) => {
+                let title = self
+                    .windows
+                    .iter()
+                    .find(|window| window.id == id)
+                    .map(|window| window.title.clone())
+                    .unwrap_or_default();
+                info!("Guarding the focus of window {id:#x} \"{title}\"");
+                self.focus_guard.window = WindowMatch::Id(id);
+            }
+            Ok(None) => warn!("No window was focused, try again"),
+            Err(e) => warn!("Could not read the focused window: {e}"),
+        }
+    }
+
+    /// Convert coordinates to pixels with the monitors & windows last read
+    ///
+    /// # Arguments
+    ///
+    /// * `coord_mode` - How the coordinates are expressed
+    /// * `coord` - The coordinates
+    pub fn coord_to_pixels(
+        &self,
+        coord_mode: CoordMode,
+        coord: (f64, f64),
+    ) -> Result<(f64, f64), InputError> {
+        match coord_mode {
+            CoordMode::Window => self.target_window()?.to_pixels(coord),
+            CoordMode::Image => {
+                let center = self.image_center()?;
+                Ok((center.0 + coord.0, center.1 + coord.1))
+            }
+            _ => screen::to_pixels(coord_mode, coord, &self.monitors),
+        }
+    }
+
+    /// Convert pixels to coordinates with the monitors & windows last read
+    ///
+    /// # Arguments
+    ///
+    /// * `coord_mode` - How the coordinates are expressed
+    /// * `pixels` - The point in pixels
+    pub fn pixels_to_coord(
+        &self,
+        coord_mode: CoordMode,
+        pixels: (f64, f64),
+    ) -> Result<(f64, f64), InputError> {
+        match coord_mode {
+            CoordMode::Window => Ok(self.target_window()?.from_pixels(pixels)),
+            CoordMode::Image => {
+                let center = self.image_center()?;
+                Ok((pixels.0 - center.0, pixels.1 - center.1))
+            }
+            _ => screen::from_pixels(coord_mode, pixels, &self.monitors),
+        }
+    }
+
+    /// The center of the target image when last searched
+    fn image_center(&self) -> Result<(f64, f64), InputError> {
+        self.image_center
+            .ok_or_else(|| InputError::new(format!("{} not found", self.image_target)))
+    }
+
+    /// Set the click coordinates from a point in pixels, converted to the coordinate mode
+    /// When picking a window by id, the window under the point becomes the target
+    /// Falls back to pixels if the point cannot be converted, e.g. on a missing monitor
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels` - The point in pixels
+    pub fn set_click_coord(&mut self, pixels: (f64, f64)) {
+        self.target_window_at(pixels);
+        let coord = match self.pixels_to_coord(self.coord_mode, pixels) {
+            Ok(coord) => coord,
+            Err(e) => {
+                warn!("Using pixel coordinates: {e}");
+                self.coord_mode = CoordMode::Absolute;
+                pixels
+            }
+        };
+        self.click_x
+            .set_text(format_coord(coord.0, self.coord_mode));
+        self.click_y
+            .set_text(format_coord(coord.1, self.coord_mode));
+    }
+
+    /// Make the window under a point the target, when picking a window by id
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels` - The point in pixels
+    fn target_window_at(&mut self, pixels: (f64, f64)) {
+        if self.coord_mode == CoordMode::Window
+            && matches!(self.window_match, WindowMatch::Id(_))
+            && let Some(window) = window::window_at(&self.windows, pixels)
+        {
+            self.window_match = WindowMatch::Id(window.id);
+        }
+    }
+
+    /// Add a point to the target list being captured, converted to the coordinate mode
+    /// The window under the first point becomes the target when picking a window by id
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels` - The point in pixels
+    pub fn capture_point(&mut self, pixels: (f64, f64)) {
+        if self.captured_points.is_empty() {
+            self.target_window_at(pixels);
+        }
+        match self.pixels_to_coord(self.coord_mode, pixels) {
+            Ok((x, y)) => {
+                let label = format!("Point {}", self.captured_points.len() + 1);
+                self.captured_points.push(TargetPoint::new(label, x, y));
+            }
+            Err(e) => warn!("Could not capture the point: {e}"),
+        }
+    }
+
+    /// Change how the click coordinates & points are expressed, converting the current ones
+    /// The numbers are kept, rounded to pixels if needed, if they cannot be converted
+    ///
+    /// # Arguments
+    ///
+    /// * `coord_mode` - The new coordinate mode
+    pub fn change_coord_mode(&mut self, coord_mode: CoordMode) {
+        self.refresh_monitors();
+        if CoordMode::Window == coord_mode || CoordMode::Window == self.coord_mode {
+            self.refresh_windows();
+        }
+        if CoordMode::Image == coord_mode || CoordMode::Image == self.coord_mode {
+            self.refresh_image_center();
+        }
+        let coord = (self.click_x.value(), self.click_y.value());
+        let coord = self
+            .coord_to_pixels(self.coord_mode, coord)
+            .and_then(|pixels| self.pixels_to_coord(coord_mode, pixels))
+            .unwrap_or(coord);
+        let points = self
+            .points
+            .iter()
+            .map(|point| {
+                let (x, y) = self
+                    .coord_to_pixels(self.coord_mode, (point.x, point.y))
+                    .and_then(|pixels| self.pixels_to_coord(coord_mode, pixels))
+                    .unwrap_or((point.x, point.y));
+                TargetPoint::new(point.label.clone(), x, y)
+            })
+            .collect();
+
+        self.points = points;
+        self.coord_mode = coord_mode;
+        self.click_x.set_range(coord_range(coord_mode));
+        self.click_y.set_range(coord_range(coord_mode));
+        self.click_x.set_text(format_coord(coord.0, coord_mode));
+        self.click_y.set_text(format_coord(coord.1, coord_mode));
     }
 
     /// Exit the coordinate setting mode
@@ -176,29 +779,578 @@ impl RustyAutoClickerApp {
     ///
     /// * `ctx` - The ctx to manipulate
     pub fn exit_coordinate_setting(&mut self, ctx: &egui::Context) {
-        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(true));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+
+        self.is_setting_coord = false;
+        self.magnifier = None;
+        if self.is_picking_pixel {
+            self.is_picking_pixel = false;
+            self.options_window_open = true;
+        } else if self.is_capturing_points {
+            // Finishing without any point keeps the previous target list
+            self.is_capturing_points = false;
+            if !self.captured_points.is_empty() {
+                self.points = std::mem::take(&mut self.captured_points);
+                self.click_position = ClickPosition::Coord;
+            }
+        } else {
+            self.click_position = ClickPosition::Coord;
+        }
+    }
+
+    /// Shrink the window to the compact bar when autoclicking starts & restore it when it stops
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The ctx to manipulate
+    pub fn update_compact_mode(&mut self, ctx: &egui::Context) {
+        let compact = self.compact_mode && self.is_autoclicking;
+        if compact && !self.is_compact {
+            self.enter_compact_mode(ctx);
+        } else if !compact && self.is_compact {
+            self.exit_compact_mode(ctx);
+        }
+    }
+
+    /// Replace the window with the compact bar, remembering where it was & its size
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The ctx to manipulate
+    fn enter_compact_mode(&mut self, ctx: &egui::Context) {
+        self.full_window = ctx.input(|i| {
+            let viewport = i.viewport();
+            Some((viewport.outer_rect?.min, viewport.inner_rect?.size()))
+        });
+
+        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
         ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(
-            WINDOW_WIDTH,
-            WINDOW_HEIGHT,
+            COMPACT_WINDOW_WIDTH,
+            COMPACT_WINDOW_HEIGHT,
         )));
-        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(self.window_position));
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+            egui::WindowLevel::AlwaysOnTop,
+        ));
+        self.is_compact = true;
+    }
 
-        self.is_setting_coord = false;
-        self.click_position = ClickPosition::Coord;
+    /// Restore the window replaced by the compact bar
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The ctx to manipulate
+    fn exit_compact_mode(&mut self, ctx: &egui::Context) {
+        let (position, size) = self.full_window.take().unzip();
+
+        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(true));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(
+            size.unwrap_or(egui::vec2(WINDOW_WIDTH, WINDOW_HEIGHT)),
+        ));
+        if let Some(position) = position {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+        }
+        self.is_compact = false;
+    }
+
+    /// Enter the coordinate setting mode to pick the pixel of the pixel condition
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The ctx to manipulate
+    pub fn enter_pixel_picking(&mut self, ctx: &egui::Context) {
+        self.is_picking_pixel = true;
+        self.options_window_open = false;
+        self.enter_coordinate_setting(ctx);
+    }
+
+    /// Set the pixel of the pixel condition to the point & its color in the magnifier
+    ///
+    /// # Arguments
+    ///
+    /// * `coord` - The point in pixels
+    pub fn pick_pixel(&mut self, (x, y): (i32, i32)) {
+        self.pixel_condition.x = x;
+        self.pixel_condition.y = y;
+        // Keep the last color when the capture fails, e.g. off screen
+        if let Some(color) = self.picked_color() {
+            self.pixel_condition.color = color;
+        }
+    }
+
+    /// The click interval typed in the hours, minutes, seconds & milliseconds inputs
+    pub fn interval(&self) -> Duration {
+        duration_of(
+            self.hr.value(),
+            self.min.value(),
+            self.sec.value(),
+            self.ms.value(),
+        )
+    }
+
+    /// The first invalid input the autoclicker depends on, with the reason, if any
+    pub fn invalid_input(&self) -> Option<String> {
+        let uses_coord = self.click_position == ClickPosition::Coord && self.points.is_empty();
+        [
+            ("Hours", self.hr.error()),
+            ("Minutes", self.min.error()),
+            ("Seconds", self.sec.error()),
+            ("Milliseconds", self.ms.error()),
+            ("Movement seconds", self.movement_sec.error()),
+            ("Movement milliseconds", self.movement_ms.error()),
+            ("Click amount", self.click_amount.error()),
+            ("X", self.click_x.error().filter(|_| uses_coord)),
+            ("Y", self.click_y.error().filter(|_| uses_coord)),
+        ]
+        .into_iter()
+        .find_map(|(name, error)| Some(format!("{name}: {}", error?)))
     }
 
     /// Start the autoclicking process
     ///
     /// # Arguments
     ///
-    /// * `negative_click_start_offset` - The offset to start the click counter at
-    pub fn start_autoclick(&mut self, negative_click_start_offset: u64) {
+    /// * `delay_first_click` - Whether the first click waits one interval, e.g. to not click on
+    ///   the START button
+    pub fn start_autoclick(&mut self, delay_first_click: bool) {
         self.click_counter = 0u64;
         self.is_autoclicking = !self.is_autoclicking;
-        self.rng_thread = rng();
+        self.first_click_due = Instant::now();
+        if delay_first_click {
+            self.first_click_due += self.interval();
+        }
+    }
+
+    /// The job typed in the main interface, with the options
+    pub fn main_job(&self) -> ClickJob {
+        ClickJob {
+            backend: self.backend_kind,
+            seed: self.humanlike_seed,
+            max_failures: self.max_consecutive_failures,
+            ..Profile::from(self).click_job()
+        }
+    }
+
+    /// Why the main job is paused or skipping its clicks, if it is
+    pub fn click_paused(&self) -> Option<&ClickError> {
+        self.main_runner.as_ref().and_then(|runner| runner.pause())
+    }
+
+    /// Send the click of the main job that is due, stopping autoclicking if it is over
+    ///
+    /// # Arguments
+    ///
+    /// * `mouse_coord` - The mouse coordinates before the click
+    pub fn step_main_job(&mut self, mouse_coord: (i32, i32)) {
+        debug!(
+            click_type = ?self.click_type,
+            click_btn = %self.click_btn,
+            "Click"
+        );
+
+        // Dry runs send the events nowhere
+        let mut null_backend = NullBackend;
+        let backend: &mut dyn InputBackend = if self.dry_run {
+            &mut null_backend
+        } else if self.ensure_input_backend()
+            && let Some((_, backend)) = self.input_backend.as_mut()
+        {
+            backend.as_mut()
+        } else {
+            self.is_autoclicking = false;
+            return;
+        };
+        let Some(runner) = self.main_runner.as_mut() else {
+            return;
+        };
+
+        let recorded_count = self.click_history.recorded_count();
+        let failure_counter = runner.failure_counter();
+        let mut clicked = false;
+        let mut recorder = RecordingBackend::new(backend, &mut self.click_history, self.app_mode);
+        let stop_reason = runner.step(&mut recorder, &mut SystemClock, &mut |event| {
+            clicked |= matches!(event, JobEvent::Clicked(_));
+        });
+
+        self.click_counter = runner.click_counter();
+        if runner.failure_counter() > failure_counter {
+            // Show the error, the runner stops the job if it keeps failing
+            self.failure_counter += 1u64;
+            self.input_error = runner.last_error().map(ToString::to_string);
+        }
+        if clicked {
+            self.click_stats.record(Instant::now());
+            if self.dry_run {
+                self.add_dry_run_marker(recorded_count, mouse_coord);
+            }
+            if self.show_targets {
+                self.add_click_flash(recorded_count, mouse_coord);
+            }
+        }
+        if let Some(stop_reason) = stop_reason {
+            if stop_reason == StopReason::Failed {
+                error!("Stopped autoclicking after consecutive failed clicks");
+            }
+            self.is_autoclicking = false;
+            self.stop_reason = Some(stop_reason);
+        }
+    }
+
+    /// Mark where the last click of a dry run happened and the path the mouse took to get there
+    ///
+    /// # Arguments
+    ///
+    /// * `recorded_count` - The amount of recorded events before the click
+    /// * `mouse_coord` - The mouse coordinates when the click started
+    pub fn add_dry_run_marker(&mut self, recorded_count: u64, mouse_coord: (i32, i32)) {
+        let start = (f64::from(mouse_coord.0), f64::from(mouse_coord.1));
+        let moves = self.click_moves(recorded_count);
+
+        let position = moves.last().copied().unwrap_or(start);
+        if moves.len() > 1 {
+            self.dry_run_path = std::iter::once(start).chain(moves).collect();
+        }
+
+        if self.dry_run_markers.len() >= DRY_RUN_MARKER_COUNT {
+            self.dry_run_markers.pop_front();
+        }
+        self.dry_run_markers.push_back((position, Instant::now()));
+    }
+
+    /// Flash where the last click landed on the target overlay
+    ///
+    /// # Arguments
+    ///
+    /// * `recorded_count` - The amount of recorded events before the click
+    /// * `mouse_coord` - The mouse coordinates when the click started
+    pub fn add_click_flash(&mut self, recorded_count: u64, mouse_coord: (i32, i32)) {
+        let position = self
+            .click_moves(recorded_count)
+            .last()
+            .copied()
+            .unwrap_or((f64::from(mouse_coord.0), f64::from(mouse_coord.1)));
+        self.click_flashes.push_back((position, Instant::now()));
+    }
+
+    /// The mouse movements recorded since a click started
+    ///
+    /// # Arguments
+    ///
+    /// * `recorded_count` - The amount of recorded events before the click
+    fn click_moves(&self, recorded_count: u64) -> Vec<(f64, f64)> {
+        let new_events = (self.click_history.recorded_count() - recorded_count) as usize;
+        self.click_history
+            .entries()
+            .iter()
+            .rev()
+            .take(new_events)
+            .rev()
+            .filter(|entry| entry.event == "move")
+            .filter_map(|entry| Some((entry.x?, entry.y?)))
+            .collect()
+    }
+
+    /// The targets of the clicks in pixels, with the labels of the points
+    /// Relative targets use the monitors, windows & image center as last read
+    pub fn target_markers(&self) -> Vec<(String, (f64, f64))> {
+        if self.click_position == ClickPosition::Mouse {
+            return Vec::new();
+        }
+        let targets = if self.points.is_empty() {
+            let coord = (self.click_x.value(), self.click_y.value());
+            vec![(String::new(), coord)]
+        } else {
+            self.points
+                .iter()
+                .map(|point| (point.label.clone(), (point.x, point.y)))
+                .collect()
+        };
+        targets
+            .into_iter()
+            .filter_map(|(label, coord)| {
+                let pixels = self.coord_to_pixels(self.coord_mode, coord);
+                pixels.ok().map(|pixels| (label, pixels))
+            })
+            .collect()
+    }
+
+    /// Create the selected input backend if it does not exist yet
+    /// Returns `false` and sets `input_error` if it cannot be created
+    pub fn ensure_input_backend(&mut self) -> bool {
+        if let Some((kind, _)) = &self.input_backend
+            && *kind == self.backend_kind
+        {
+            return true;
+        }
+
+        match self.backend_kind.create() {
+            Ok(backend) => {
+                info!("Using the {} input backend", self.backend_kind);
+                self.input_backend = Some((self.backend_kind, backend));
+                self.input_error = None;
+                true
+            }
+            Err(e) => {
+                error!("Could not create the {} backend: {e}", self.backend_kind);
+                self.input_backend = None;
+                self.input_error = Some(format!("{} backend: {e}", self.backend_kind));
+                false
+            }
+        }
+    }
+
+    /// Ask where to save the click history and export it
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The file format
+    pub fn export_history(&mut self, format: HistoryFormat) {
+        let path = native_dialog::DialogBuilder::file()
+            .set_filename(format!("click-history.{}", format.extension()))
+            .add_filter(format!("{format:?}"), [format.extension()])
+            .save_single_file()
+            .show();
+
+        self.history_export_status = match path {
+            Ok(Some(path)) => match self.click_history.export(&path, format) {
+                Ok(()) => {
+                    info!("Exported the click history to {}", path.display());
+                    Some(format!("Exported to {}", path.display()))
+                }
+                Err(e) => {
+                    error!("Could not export the click history: {e}");
+                    Some(format!("Could not export: {e}"))
+                }
+            },
+            Ok(None) => None,
+            Err(e) => Some(format!("Could not open the file dialog: {e}")),
+        };
+    }
+
+    /// Ask for the target image, in PNG
+    pub fn choose_target_image(&mut self) {
+        let path = native_dialog::DialogBuilder::file()
+            .add_filter("PNG", ["png"])
+            .open_single_file()
+            .show();
+
+        match path {
+            Ok(Some(path)) => {
+                self.image_target.path = path;
+                self.refresh_image_center();
+            }
+            Ok(None) => (),
+            Err(e) => error!("Could not open the file dialog: {e}"),
+        }
+    }
+
+    /// Load the named profile and make it the active one
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The profile name
+    pub fn load_profile(&mut self, name: &str) {
+        match profiles::load(name) {
+            Ok(profile) => {
+                profile.apply(self);
+                self.active_profile = Some(name.to_owned());
+                self.profile_error = None;
+            }
+            Err(e) => self.profile_error = Some(format!("Failed to load \"{name}\": {e}")),
+        }
+    }
+
+    /// Save the current settings under the given name and make it the active profile
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The profile name
+    pub fn save_profile(&mut self, name: &str) {
+        match profiles::save(name, &profiles::Profile::from(&*self)) {
+            Ok(()) => {
+                self.active_profile = Some(name.to_owned());
+                self.profile_error = None;
+                self.profile_names = profiles::list();
+            }
+            Err(e) => self.profile_error = Some(format!("Failed to save \"{name}\": {e}")),
+        }
+    }
+
+    /// Copy the saved active profile to a new name, leaving unsaved changes untouched
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the copy
+    pub fn duplicate_profile(&mut self, name: &str) {
+        let Some(active) = self.active_profile.clone() else {
+            return;
+        };
+        match profiles::load(&active).and_then(|profile| profiles::save(name, &profile)) {
+            Ok(()) => {
+                self.profile_error = None;
+                self.profile_names = profiles::list();
+            }
+            Err(e) => self.profile_error = Some(format!("Failed to duplicate \"{active}\": {e}")),
+        }
+    }
+
+    /// Delete the active profile, the current settings are kept
+    pub fn delete_active_profile(&mut self) {
+        let Some(active) = self.active_profile.take() else {
+            return;
+        };
+        match profiles::delete(&active) {
+            Ok(()) => self.profile_error = None,
+            Err(e) => self.profile_error = Some(format!("Failed to delete \"{active}\": {e}")),
+        }
+        self.profile_names = profiles::list();
+    }
+
+    /// Open the window asking for a profile name
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - What to do with the name once confirmed
+    pub fn open_profile_window(&mut self, action: ProfileAction) {
+        self.profile_action = action;
+        self.profile_name_str = match (action, &self.active_profile) {
+            (ProfileAction::Duplicate, Some(active)) => format!("{active} copy"),
+            _ => String::new(),
+        };
+        self.profile_window_open = true;
+    }
+
+    /// Apply the commands received on the control socket
+    #[cfg(unix)]
+    pub fn handle_ipc_commands(&mut self) {
+        while let Some((client_id, command)) =
+            self.ipc_server.as_ref().and_then(IpcServer::try_recv)
+        {
+            let reply = self.apply_ipc_command(command);
+            if let Some(ipc_server) = &self.ipc_server {
+                ipc_server.reply(client_id, &reply);
+            }
+        }
+    }
+
+    /// Apply a command received on the control socket, returning the reply
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command
+    #[cfg(unix)]
+    fn apply_ipc_command(&mut self, command: IpcCommand) -> IpcEvent {
+        let is_busy = self.is_setting_autoclick_key
+            || self.is_setting_coord
+            || self.is_setting_set_coord_key
+            || self.hotkey_window_open
+            || self.profile_window_open;
+
+        match command {
+            IpcCommand::Start if self.is_autoclicking => IpcEvent::Ok,
+            IpcCommand::Start if is_busy => IpcEvent::Error {
+                message: "The app is busy".to_owned(),
+            },
+            IpcCommand::Start => {
+                // Start autoclick, first click is instantaneous
+                Self::start_autoclick(self, false);
+                IpcEvent::Ok
+            }
+            IpcCommand::Stop => {
+                if self.is_autoclicking {
+                    self.is_autoclicking = false;
+                    self.stop_reason = Some(StopReason::Ipc);
+                }
+                IpcEvent::Ok
+            }
+            IpcCommand::Status => IpcEvent::Status {
+                is_autoclicking: self.is_autoclicking,
+                click_counter: self.click_counter,
+                interval_ms: self.interval().as_millis() as u64,
+                active_profile: self.active_profile.clone(),
+            },
+            IpcCommand::SetInterval { ms } => {
+                let previous = (
+                    self.hr.clone(),
+                    self.min.clone(),
+                    self.sec.clone(),
+                    self.ms.clone(),
+                );
+                self.hr.set(ms / 3600000);
+                self.min.set(ms / 60000 % 60);
+                self.sec.set((ms / 1000 % 60) as f64);
+                self.ms.set((ms % 1000) as f64);
+                // Keep the previous interval if the new one is out of range
+                match self.invalid_input() {
+                    Some(message) => {
+                        (self.hr, self.min, self.sec, self.ms) = previous;
+                        IpcEvent::Error { message }
+                    }
+                    None => IpcEvent::Ok,
+                }
+            }
+            IpcCommand::LoadProfile { .. } if self.is_autoclicking || is_busy => IpcEvent::Error {
+                message: "The app is busy".to_owned(),
+            },
+            IpcCommand::LoadProfile { name } => {
+                self.load_profile(&name);
+                match &self.profile_error {
+                    Some(message) => IpcEvent::Error {
+                        message: message.clone(),
+                    },
+                    None => IpcEvent::Ok,
+                }
+            }
+        }
+    }
+
+    /// Stream the clicks performed and start/stop changes since the last frame
+    #[cfg(unix)]
+    pub fn report_ipc_events(&mut self) {
+        let Some(ipc_server) = &self.ipc_server else {
+            return;
+        };
+
+        if self.is_autoclicking && !self.ipc_was_autoclicking {
+            ipc_server.broadcast(&IpcEvent::Started);
+            self.ipc_reported_clicks = 0u64;
+        }
+        if self.click_counter != self.ipc_reported_clicks {
+            ipc_server.broadcast(&IpcEvent::Clicks {
+                count: self.click_counter,
+            });
+            self.ipc_reported_clicks = self.click_counter;
+        }
+        if !self.is_autoclicking && self.ipc_was_autoclicking {
+            ipc_server.broadcast(&IpcEvent::Stopped {
+                reason: self.stop_reason.take().unwrap_or(StopReason::User),
+                count: self.click_counter,
+            });
+        }
+
+        self.ipc_was_autoclicking = self.is_autoclicking;
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipc_intervals_out_of_range_are_rejected() {
+        let mut app = RustyAutoClickerApp::default();
+        let reply = app.apply_ipc_command(IpcCommand::SetInterval { ms: 90_061_250 });
+        assert_eq!(reply, IpcEvent::Ok);
+        assert_eq!(app.interval(), Duration::from_millis(90_061_250));
+
+        let reply = app.apply_ipc_command(IpcCommand::SetInterval { ms: u64::MAX });
 
-        self.last_now = Instant::now()
-            .checked_sub(Duration::from_millis(negative_click_start_offset))
-            .unwrap();
+        assert_eq!(
+            reply,
+            IpcEvent::Error {
+                message: "Hours: Must be between 0 and 10000".to_owned()
+            }
+        );
+        assert_eq!(app.interval(), Duration::from_millis(90_061_250));
+        assert_eq!(app.invalid_input(), None);
     }
 }
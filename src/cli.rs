@@ -0,0 +1,953 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use rdev::{Button, Key};
+use rusty_autoclicker::{
+    engine::{
+        ClickJob, JobEvent, TargetPoint,
+        backend::{InputBackend, NullBackend},
+        clock::SystemClock,
+        history::{ClickHistory, HistoryFormat, RecordingBackend},
+        pixel::{PixelCondition, PixelTrigger},
+        screen::Region,
+        sequence::Sequence,
+        stats::ClickStats,
+        template::{ImageTarget, MissingAction},
+        window::{FocusAction, FocusGuard, WindowMatch},
+        xdotool,
+    },
+    types::{AppMode, ClickButton, ClickPosition, ClickType, CoordMode, StopReason},
+};
+use tracing::{error, info};
+
+use crate::{
+    ipc::{self, IpcCommand},
+    profiles::{self, Profile},
+    settings,
+};
+
+const USAGE: &str = "\
+Usage: rusty-autoclicker [COMMAND]
+
+Without a command the graphical interface is started.
+
+Commands:
+  run     Autoclick without the graphical interface, stop with Ctrl-C
+  ctl     Control the running graphical interface (Unix only)
+  xdotool Import or export xdotool scripts
+  help    Print this message
+
+Options for `run`:
+  --profile <NAME>         Start from a saved profile instead of the defaults
+  --interval <DURATION>    Time between clicks, e.g. 100ms, 1.5s, 2m, 1h [default: 100ms]
+  --button <BUTTON>        left, right or middle [default: left]
+  --key <KEY>              Press a keyboard key instead, e.g. Space, KeyA, F5
+  --type <TYPE>            single or double [default: single]
+  --amount <N>             Stop after N clicks, 0 = forever [default: 0]
+  --coords <X,Y>           Click at the coordinates instead of the mouse position, in pixels or in
+                           percent of the screen, e.g. 50%,50%
+  --point <X,Y>            Click several points in turn instead, one option per point, like --coords
+  --monitor <N>            Make percentage coordinates relative to monitor N, 0 being the primary
+  --window-title <REGEX>   Make the coordinates relative to the topmost window with a matching title
+  --window-class <REGEX>   Same, matching the WM_CLASS instance or class of the window (X11 only)
+  --window-id <ID>         Same, for the window with the given X11 id, e.g. 0x3a00007
+  --image <PATH>           Click on the center of a PNG image found on the screen, --coords being an
+                           offset from it in pixels (X11 only)
+  --confidence <PERCENT>   Lowest match score to consider the image found [default: 90]
+  --region <WxH+X+Y>       Search the image there only, e.g. 800x600+0+0
+  --image-missing <ACTION> pause or stop while the image is not found [default: pause]
+  --focus-title <REGEX>    Only click while a window with a matching title is focused (X11 only)
+  --focus-class <REGEX>    Same, matching the WM_CLASS instance or class of the window
+  --focus-id <ID>          Same, for the window with the given X11 id
+  --unfocused <ACTION>     skip or pause the clicks while the window is not focused [default: skip]
+  --wait-pixel <X,Y=COLOR> Start clicking once the pixel has the color, e.g. 10,20=#ff0000 (X11 only)
+  --while-pixel <X,Y=COLOR>
+                           Only click while the pixel has the color, skipping the other clicks
+  --stop-pixel <X,Y=COLOR> Stop as soon as the pixel no longer has the color
+  --pixel-tolerance <N>    Largest difference per color channel that still matches [default: 0]
+  --mode <MODE>            bot or humanlike [default: bot]
+  --movement-delay <DURATION>
+                           Delay between mouse movements in humanlike mode [default: 20ms]
+  --backend <BACKEND>      rdev, xtest or uinput (Linux only) [default: from the settings]
+  --seed <N>               Seed of the humanlike timings for reproducible runs [default: from the settings]
+  --max-failures <N>       Stop after N consecutive failed clicks, 0 = never [default: from the settings]
+  --history <PATH>         Export the sent events when the run finishes, to .csv or .jsonl
+  --dry-run                Send the events nowhere, e.g. with --history to preview a run
+  -q, --quiet              Do not print progress
+
+Subcommands of `ctl`, each printing the JSON answer:
+  start                    Start autoclicking
+  stop                     Stop autoclicking
+  status                   Print the state of the running instance
+  set-interval <DURATION>  Change the click interval
+  load-profile <NAME>      Load a saved profile
+  watch                    Print the streamed events until the instance exits
+
+Subcommands of `xdotool`:
+  import <SCRIPT> --profile <NAME>
+                           Replace the timeline of a profile with an xdotool script, creating the
+                           profile if needed
+  export [OPTIONS]         Print the job described by the options of `run` as an xdotool script
+  export --timeline --profile <NAME>
+                           Print the timeline of a profile as an xdotool script
+
+Exit status: 0 when all clicks were sent, 130 when stopped with Ctrl-C, 2 on invalid arguments,
+3 when the target image was not found with --image-missing stop, 1 on other errors, including too
+many failed clicks.";
+
+/// Exit status when the run was interrupted by Ctrl-C
+const EXIT_INTERRUPTED: u8 = 130;
+/// Exit status on invalid arguments
+const EXIT_USAGE: u8 = 2;
+/// Exit status when the target image was not found and the job stopped
+const EXIT_IMAGE_MISSING: u8 = 3;
+
+/// Options of the `run` command
+#[derive(PartialEq, Clone, Debug)]
+pub struct RunOptions {
+    pub job: ClickJob,
+    /// Where to export the click history
+    pub history_path: Option<PathBuf>,
+    pub dry_run: bool,
+    pub quiet: bool,
+}
+
+/// Command given on the command line
+#[derive(PartialEq, Clone, Debug)]
+pub enum Command {
+    Run(Box<RunOptions>),
+    /// Send a command to the running instance, or watch its events if `None`
+    Control(Option<IpcCommand>),
+    Xdotool(XdotoolCommand),
+    Help,
+}
+
+/// Subcommand of the `xdotool` command
+#[derive(PartialEq, Clone, Debug)]
+pub enum XdotoolCommand {
+    /// Replace the timeline of a profile with a script
+    Import { script: PathBuf, profile: String },
+    /// Print a job as a script
+    ExportJob(Box<ClickJob>),
+    /// Print the timeline of a profile as a script
+    ExportTimeline(Sequence),
+}
+
+/// Run the command line interface if a command was given
+/// Returns `None` when the GUI should be started instead
+///
+/// # Arguments
+///
+/// * `args` - The command line arguments, without the program name
+pub fn main(args: &[String]) -> Option<ExitCode> {
+    match parse_args(args) {
+        Ok(None) => None,
+        Ok(Some(Command::Help)) => {
+            println!("{USAGE}");
+            Some(ExitCode::SUCCESS)
+        }
+        Ok(Some(Command::Run(options))) => Some(run(&options)),
+        Ok(Some(Command::Control(command))) => Some(control(command.as_ref())),
+        Ok(Some(Command::Xdotool(command))) => Some(xdotool(command)),
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            Some(ExitCode::from(EXIT_USAGE))
+        }
+    }
+}
+
+/// Parse the command line arguments
+///
+/// # Arguments
+///
+/// * `args` - The command line arguments, without the program name
+pub fn parse_args(args: &[String]) -> Result<Option<Command>, String> {
+    let Some((command, options)) = args.split_first() else {
+        return Ok(None);
+    };
+
+    match command.as_str() {
+        "run" => parse_run_options(options).map(|options| Some(Command::Run(Box::new(options)))),
+        "ctl" => parse_control_command(options).map(|command| Some(Command::Control(command))),
+        "xdotool" => parse_xdotool_command(options).map(|command| Some(Command::Xdotool(command))),
+        "help" | "-h" | "--help" => Ok(Some(Command::Help)),
+        other => Err(format!("unknown command \"{other}\"")),
+    }
+}
+
+/// Parse the options of the `run` command
+///
+/// # Arguments
+///
+/// * `args` - The arguments following `run`
+fn parse_run_options(args: &[String]) -> Result<RunOptions, String> {
+    // The profile is the base the other options are applied on, so it is looked up first
+    let profile = match args.iter().position(|arg| arg == "--profile") {
+        Some(index) => {
+            let name = args.get(index + 1).ok_or("missing value for --profile")?;
+            let profile = profiles::load(name)
+                .map_err(|e| format!("could not load profile \"{name}\": {e}"))?;
+            // Refuse the numbers the app would not click with, e.g. edited by hand
+            if let Some(e) = profile.invalid_input() {
+                return Err(format!("invalid profile \"{name}\": {e}"));
+            }
+            profile
+        }
+        None => Profile::default(),
+    };
+    let settings = settings::load().ok().flatten().unwrap_or_default();
+    let mut options = RunOptions {
+        job: profile
+            .click_job()
+            .backend(settings.input_backend)
+            .max_failures(settings.max_consecutive_failures)
+            .seed(settings.humanlike_seed),
+        history_path: None,
+        dry_run: false,
+        quiet: false,
+    };
+
+    let mut monitor: Option<usize> = None;
+    let mut window: Option<WindowMatch> = None;
+    let mut image: Option<ImageTarget> = None;
+    let mut confidence: Option<f32> = None;
+    let mut region: Option<Region> = None;
+    let mut image_missing: Option<MissingAction> = None;
+    let mut focus_window: Option<WindowMatch> = None;
+    let mut focus_action: Option<FocusAction> = None;
+    let mut pixel: Option<PixelCondition> = None;
+    let mut pixel_tolerance: Option<u8> = None;
+    let mut coords_given = false;
+    let mut points: Vec<((f64, f64), CoordMode)> = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "-q" || arg == "--quiet" {
+            options.quiet = true;
+            continue;
+        }
+        if arg == "--dry-run" {
+            options.dry_run = true;
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--profile" => (),
+            "--interval" => options.job.interval = parse_duration(value)?,
+            "--button" => {
+                options.job.click_info.click_btn = ClickButton::Mouse(parse_button(value)?)
+            }
+            "--key" => options.job.click_info.click_btn = ClickButton::Key(parse_key(value)?),
+            "--type" => {
+                options.job.click_info.click_type = match value.to_lowercase().as_str() {
+                    "single" => ClickType::Single,
+                    "double" => ClickType::Double,
+                    _ => return Err(format!("invalid click type \"{value}\"")),
+                }
+            }
+            "--amount" => {
+                options.job.click_amount = value
+                    .parse()
+                    .map_err(|_| format!("invalid amount \"{value}\""))?
+            }
+            "--coords" => {
+                let (click_coord, coord_mode) = parse_coords(value)?;
+                options.job.click_info.click_coord = click_coord;
+                options.job.click_info.coord_mode = coord_mode;
+                options.job.click_info.click_position = ClickPosition::Coord;
+                // Replace the points of the profile
+                options.job.points.clear();
+                coords_given = true;
+            }
+            "--point" => points.push(parse_coords(value)?),
+            "--window-title" => window = Some(WindowMatch::Title(value.clone())),
+            "--window-class" => window = Some(WindowMatch::Class(value.clone())),
+            "--window-id" => window = Some(WindowMatch::Id(parse_window_id(value)?)),
+            "--image" => {
+                image = Some(ImageTarget {
+                    path: PathBuf::from(value),
+                    ..ImageTarget::default()
+                })
+            }
+            "--confidence" => {
+                let percent: f32 = value
+                    .trim_end_matches('%')
+                    .parse()
+                    .map_err(|_| format!("invalid confidence \"{value}\""))?;
+                if !(0.0..=100.0).contains(&percent) {
+                    return Err(format!("invalid confidence \"{value}\", use 0 to 100"));
+                }
+                confidence = Some(percent / 100.0);
+            }
+            "--region" => region = Some(value.parse()?),
+            "--image-missing" => {
+                image_missing = Some(match value.to_lowercase().as_str() {
+                    "pause" => MissingAction::Pause,
+                    "stop" => MissingAction::Stop,
+                    _ => return Err(format!("invalid image missing action \"{value}\"")),
+                })
+            }
+            "--focus-title" => focus_window = Some(WindowMatch::Title(value.clone())),
+            "--focus-class" => focus_window = Some(WindowMatch::Class(value.clone())),
+            "--focus-id" => focus_window = Some(WindowMatch::Id(parse_window_id(value)?)),
+            "--wait-pixel" => pixel = Some(parse_pixel(value, PixelTrigger::WaitUntil)?),
+            "--while-pixel" => pixel = Some(parse_pixel(value, PixelTrigger::While)?),
+            "--stop-pixel" => pixel = Some(parse_pixel(value, PixelTrigger::StopOnChange)?),
+            "--pixel-tolerance" => {
+                pixel_tolerance = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid tolerance \"{value}\", use 0 to 255"))?,
+                )
+            }
+            "--unfocused" => {
+                focus_action = Some(match value.to_lowercase().as_str() {
+                    "skip" => FocusAction::Skip,
+                    "pause" => FocusAction::Pause,
+                    _ => return Err(format!("invalid unfocused action \"{value}\"")),
+                })
+            }
+            "--monitor" => {
+                monitor = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid monitor \"{value}\""))?,
+                )
+            }
+            "--mode" => {
+                options.job.app_mode = match value.to_lowercase().as_str() {
+                    "bot" => AppMode::Bot,
+                    "humanlike" => AppMode::Humanlike,
+                    _ => return Err(format!("invalid mode \"{value}\"")),
+                }
+            }
+            "--movement-delay" => options.job.movement_delay = parse_duration(value)?,
+            "--backend" => {
+                options.job.backend = toml::Value::from(value.as_str())
+                    .try_into()
+                    .map_err(|_| format!("invalid backend \"{value}\""))?
+            }
+            "--seed" => {
+                options.job.seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid seed \"{value}\""))?,
+                )
+            }
+            "--max-failures" => {
+                options.job.max_failures = value
+                    .parse()
+                    .map_err(|_| format!("invalid amount of failures \"{value}\""))?
+            }
+            "--history" => {
+                let path = PathBuf::from(value);
+                if HistoryFormat::from_path(&path).is_none() {
+                    return Err(format!(
+                        "unknown history format \"{value}\", use a .csv or .jsonl file"
+                    ));
+                }
+                options.history_path = Some(path);
+            }
+            _ => return Err(format!("unknown option \"{arg}\"")),
+        }
+    }
+
+    // Applied first as the other options check the unit of the coordinates
+    if let Some((_, coord_mode)) = points.first() {
+        if points.iter().any(|(_, other)| other != coord_mode) {
+            return Err("--point needs the same unit for every point".to_owned());
+        }
+        if coords_given {
+            return Err("--coords and --point cannot be combined".to_owned());
+        }
+        options.job.click_info.coord_mode = *coord_mode;
+        options.job = options.job.points(
+            points
+                .iter()
+                .enumerate()
+                .map(|(index, ((x, y), _))| {
+                    TargetPoint::new(format!("Point {}", index + 1), *x, *y)
+                })
+                .collect(),
+        );
+    }
+
+    // Applied last as they depend on the coordinates, whatever the order of the options
+    if let Some(index) = monitor {
+        if options.job.click_info.coord_mode == CoordMode::Absolute {
+            return Err("--monitor needs percentage coordinates, e.g. --coords 50%,50%".to_owned());
+        }
+        options.job.click_info.coord_mode = CoordMode::MonitorPercent(index);
+    }
+    if image.is_some() && window.is_some() {
+        return Err("--image and --window-* cannot be combined".to_owned());
+    }
+    if let Some(window) = window {
+        if options.job.click_info.click_position == ClickPosition::Mouse {
+            return Err("--window-* needs --coords, relative to the window".to_owned());
+        }
+        if !matches!(
+            options.job.click_info.coord_mode,
+            CoordMode::Absolute | CoordMode::Window
+        ) {
+            return Err("--window-* needs pixel coordinates, e.g. --coords 20,40".to_owned());
+        }
+        options.job = options.job.window(window);
+    }
+    if let Some(image) = image {
+        if options.job.click_info.click_position == ClickPosition::Mouse {
+            // Click on the center of the image
+            options.job.click_info.click_coord = (0.0, 0.0);
+            options.job.click_info.click_position = ClickPosition::Coord;
+        } else if !matches!(
+            options.job.click_info.coord_mode,
+            CoordMode::Absolute | CoordMode::Image
+        ) {
+            return Err("--image needs a pixel offset, e.g. --coords 20,-10".to_owned());
+        }
+        options.job = options.job.image(image);
+    }
+    if confidence.is_some() || region.is_some() || image_missing.is_some() {
+        let Some(image) = &mut options.job.image else {
+            return Err("--confidence, --region & --image-missing need --image".to_owned());
+        };
+        image.confidence = confidence.unwrap_or(image.confidence);
+        image.region = region.or(image.region);
+        image.when_missing = image_missing.unwrap_or(image.when_missing);
+    }
+    // Override the focus guard of the profile, if any
+    if let Some(window) = focus_window {
+        options.job = options.job.focus_guard(FocusGuard {
+            window,
+            action: focus_action.unwrap_or_default(),
+        });
+    } else if let Some(action) = focus_action {
+        match &mut options.job.focus_guard {
+            Some(focus_guard) => focus_guard.action = action,
+            None => return Err("--unfocused needs a --focus-* window".to_owned()),
+        }
+    }
+    // Override the pixel condition of the profile, if any
+    if let Some(pixel) = pixel {
+        options.job = options.job.pixel_condition(pixel);
+    }
+    if let Some(tolerance) = pixel_tolerance {
+        match &mut options.job.pixel_condition {
+            Some(pixel) => pixel.tolerance = tolerance,
+            None => return Err("--pixel-tolerance needs a --*-pixel condition".to_owned()),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Parse the subcommand of the `ctl` command
+///
+/// # Arguments
+///
+/// * `args` - The arguments following `ctl`
+fn parse_control_command(args: &[String]) -> Result<Option<IpcCommand>, String> {
+    let value = |index: usize| {
+        args.get(index)
+            .ok_or_else(|| format!("missing value for {}", args[0]))
+    };
+
+    let (command, expected_len) = match args.first().map(String::as_str) {
+        Some("start") => (Some(IpcCommand::Start), 1),
+        Some("stop") => (Some(IpcCommand::Stop), 1),
+        Some("status") => (Some(IpcCommand::Status), 1),
+        Some("set-interval") => (
+            Some(IpcCommand::SetInterval {
+                ms: parse_duration(value(1)?)?.as_millis() as u64,
+            }),
+            2,
+        ),
+        Some("load-profile") => (
+            Some(IpcCommand::LoadProfile {
+                name: value(1)?.clone(),
+            }),
+            2,
+        ),
+        Some("watch") => (None, 1),
+        Some(other) => return Err(format!("unknown ctl command \"{other}\"")),
+        None => return Err("missing ctl command".to_owned()),
+    };
+
+    if args.len() > expected_len {
+        return Err(format!("unexpected argument \"{}\"", args[expected_len]));
+    }
+    Ok(command)
+}
+
+/// Parse the subcommand of the `xdotool` command
+///
+/// # Arguments
+///
+/// * `args` - The arguments following `xdotool`
+fn parse_xdotool_command(args: &[String]) -> Result<XdotoolCommand, String> {
+    let profile = |args: &[String]| match args {
+        [option, name] if option == "--profile" => Ok(name.clone()),
+        [] => Err("missing --profile".to_owned()),
+        [arg, ..] => Err(format!("unexpected argument \"{arg}\"")),
+    };
+
+    match args.first().map(String::as_str) {
+        Some("import") => {
+            let script = args.get(1).ok_or("missing xdotool script")?;
+            Ok(XdotoolCommand::Import {
+                script: PathBuf::from(script),
+                profile: profile(&args[2..])?,
+            })
+        }
+        Some("export") if args.get(1).is_some_and(|arg| arg == "--timeline") => {
+            let name = profile(&args[2..])?;
+            let profile = profiles::load(&name)
+                .map_err(|e| format!("could not load profile \"{name}\": {e}"))?;
+            Ok(XdotoolCommand::ExportTimeline(profile.sequence))
+        }
+        Some("export") => parse_run_options(&args[1..])
+            .map(|options| XdotoolCommand::ExportJob(Box::new(options.job))),
+        Some(other) => Err(format!("unknown xdotool command \"{other}\"")),
+        None => Err("missing xdotool command".to_owned()),
+    }
+}
+
+/// Parse a duration such as `100ms`, `1.5s`, `2m` or `1h`, plain numbers are milliseconds
+///
+/// # Arguments
+///
+/// * `string` - The string to parse
+pub fn parse_duration(string: &str) -> Result<Duration, String> {
+    let string = string.trim();
+    let split_at = string
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(string.len());
+    let (number, unit) = string.split_at(split_at);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration \"{string}\""))?;
+    let seconds_per_unit = match unit {
+        "" | "ms" => 0.001,
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(format!("invalid duration unit \"{unit}\"")),
+    };
+
+    Duration::try_from_secs_f64(number * seconds_per_unit)
+        .map_err(|_| format!("invalid duration \"{string}\""))
+}
+
+/// Parse an X11 window id, in hexadecimal with a `0x` prefix or in decimal
+///
+/// # Arguments
+///
+/// * `string` - The string to parse
+fn parse_window_id(string: &str) -> Result<u32, String> {
+    match string.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => string.parse(),
+    }
+    .map_err(|_| format!("invalid window id \"{string}\""))
+}
+
+/// Parse a pixel condition, e.g. `10,20=#ff0000`
+///
+/// # Arguments
+///
+/// * `string` - The string to parse
+/// * `trigger` - How the condition affects the clicks
+fn parse_pixel(string: &str, trigger: PixelTrigger) -> Result<PixelCondition, String> {
+    let invalid = || format!("invalid pixel \"{string}\", use X,Y=#rrggbb");
+    let (coords, color) = string.split_once('=').ok_or_else(invalid)?;
+    let (x, y) = coords.split_once(',').ok_or_else(invalid)?;
+    Ok(PixelCondition {
+        x: x.trim().parse().map_err(|_| invalid())?,
+        y: y.trim().parse().map_err(|_| invalid())?,
+        color: color.trim().parse()?,
+        tolerance: 0,
+        trigger,
+    })
+}
+
+/// Parse a mouse button name
+///
+/// # Arguments
+///
+/// * `string` - The string to parse
+fn parse_button(string: &str) -> Result<Button, String> {
+    match string.to_lowercase().as_str() {
+        "left" => Ok(Button::Left),
+        "right" => Ok(Button::Right),
+        "middle" => Ok(Button::Middle),
+        _ => Err(format!("invalid button \"{string}\"")),
+    }
+}
+
+/// Parse a keyboard key by its `rdev::Key` name
+///
+/// # Arguments
+///
+/// * `string` - The string to parse
+fn parse_key(string: &str) -> Result<Key, String> {
+    toml::Value::from(string)
+        .try_into()
+        .map_err(|_| format!("invalid key \"{string}\""))
+}
+
+/// Parse coordinates formatted as `X,Y` in pixels or `X%,Y%` in percent of the screen
+///
+/// # Arguments
+///
+/// * `string` - The string to parse
+fn parse_coords(string: &str) -> Result<((f64, f64), CoordMode), String> {
+    let invalid = || format!("invalid coordinates \"{string}\"");
+    let (x, y) = string.split_once(',').ok_or_else(invalid)?;
+    let (x, y) = (x.trim(), y.trim());
+
+    match (x.strip_suffix('%'), y.strip_suffix('%')) {
+        (Some(x), Some(y)) => {
+            let parse_percent = |string: &str| {
+                string
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|percent| (0.0..=100.0).contains(percent))
+                    .ok_or_else(invalid)
+            };
+            Ok((
+                (parse_percent(x)?, parse_percent(y)?),
+                CoordMode::ScreenPercent,
+            ))
+        }
+        (None, None) => {
+            let x: i64 = x.parse().map_err(|_| invalid())?;
+            let y: i64 = y.parse().map_err(|_| invalid())?;
+            Ok(((x as f64, y as f64), CoordMode::Absolute))
+        }
+        _ => Err(format!(
+            "invalid coordinates \"{string}\", use pixels or percentages for both"
+        )),
+    }
+}
+
+/// Autoclick until the click amount is reached or Ctrl-C is pressed
+///
+/// # Arguments
+///
+/// * `options` - The run options
+pub fn run(options: &RunOptions) -> ExitCode {
+    let stop_requested = Arc::new(AtomicBool::new(false));
+    let handler_stop_requested = stop_requested.clone();
+    if let Err(e) = ctrlc::set_handler(move || handler_stop_requested.store(true, Ordering::SeqCst))
+    {
+        eprintln!("Could not set the Ctrl-C handler: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    // Do not start clicking off screen, e.g. on a monitor that was unplugged
+    if let Err(e) = options.job.check_target() {
+        error!("Invalid click coordinates: {e}");
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    info!(job = ?options.job, "Running headless");
+    let mut stats = ClickStats::default();
+    stats.start(options.job.interval, Instant::now());
+    let backend = if options.dry_run {
+        Ok(Box::new(NullBackend) as Box<dyn InputBackend>)
+    } else {
+        options.job.backend.create()
+    };
+    let mut backend = match backend {
+        Ok(backend) => backend,
+        Err(e) => {
+            error!("Could not create the {} backend: {e}", options.job.backend);
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut history = ClickHistory::default();
+    let mut recorder = RecordingBackend::new(backend.as_mut(), &mut history, options.job.app_mode);
+    let mut is_skipping = false;
+    let report =
+        options.job.run_on(
+            &mut recorder,
+            &mut SystemClock,
+            &stop_requested,
+            |event| match event {
+                JobEvent::Clicked(click_counter) => {
+                    stats.record(Instant::now());
+                    if !options.quiet {
+                        print_progress(click_counter, options.job.click_amount);
+                    }
+                }
+                JobEvent::Paused(message) => eprintln!("\nPaused: {message}"),
+                JobEvent::Skipped(message) => {
+                    // Only print the first of a run of skipped clicks
+                    if !is_skipping {
+                        is_skipping = true;
+                        eprintln!("\nSkipping clicks: {message}");
+                    }
+                }
+                JobEvent::Resumed => {
+                    is_skipping = false;
+                    eprintln!("Resumed");
+                }
+            },
+        );
+    info!(
+        clicks = report.click_counter,
+        failures = report.failure_counter,
+        skipped = report.skipped_counter,
+        reason = ?report.stop_reason,
+        elapsed = ?report.elapsed,
+        "Headless run stopped"
+    );
+
+    if !options.quiet {
+        println!();
+        println!(
+            "{} after {} click(s) in {:.1?}",
+            match report.stop_reason {
+                StopReason::Completed => "Done",
+                StopReason::Failed => "Failed",
+                StopReason::PixelChanged => "Pixel changed",
+                StopReason::ImageMissing => "Image not found",
+                StopReason::User | StopReason::Ipc => "Stopped",
+            },
+            report.click_counter,
+            report.elapsed
+        );
+        if report.skipped_counter > 0 {
+            println!("{} click(s) skipped", report.skipped_counter);
+        }
+        print_stats(&stats);
+    }
+    if let Some(e) = &report.last_error {
+        eprintln!(
+            "error: {} click(s) failed, last error: {e}",
+            report.failure_counter
+        );
+    }
+
+    if let Some(path) = &options.history_path {
+        // Validated when parsing the options
+        let format = HistoryFormat::from_path(path).unwrap_or(HistoryFormat::Csv);
+        if let Err(e) = history.export(path, format) {
+            eprintln!(
+                "error: could not export the history to {}: {e}",
+                path.display()
+            );
+            return ExitCode::FAILURE;
+        }
+        info!("Exported the click history to {}", path.display());
+    }
+
+    match report.stop_reason {
+        StopReason::Completed | StopReason::PixelChanged => ExitCode::SUCCESS,
+        StopReason::Failed => ExitCode::FAILURE,
+        StopReason::ImageMissing => {
+            if let Some(image_target) = &options.job.image {
+                eprintln!("error: {image_target} not found on the screen");
+            }
+            ExitCode::from(EXIT_IMAGE_MISSING)
+        }
+        StopReason::User | StopReason::Ipc => ExitCode::from(EXIT_INTERRUPTED),
+    }
+}
+
+/// Send a command to the running instance over the control socket
+///
+/// # Arguments
+///
+/// * `command` - The command to send, `None` to watch the events
+#[cfg(unix)]
+fn control(command: Option<&IpcCommand>) -> ExitCode {
+    match ipc::run_client(command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Send a command to the running instance over the control socket
+///
+/// # Arguments
+///
+/// * `command` - The command to send, `None` to watch the events
+#[cfg(not(unix))]
+fn control(_command: Option<&IpcCommand>) -> ExitCode {
+    eprintln!("error: the control socket is only available on Unix");
+    ExitCode::FAILURE
+}
+
+/// Import or export an xdotool script
+///
+/// # Arguments
+///
+/// * `command` - What to import or export
+fn xdotool(command: XdotoolCommand) -> ExitCode {
+    let result = match command {
+        XdotoolCommand::Import { script, profile } => import_script(&script, &profile),
+        XdotoolCommand::ExportJob(job) => job
+            .to_sequence()
+            .and_then(|sequence| xdotool::write_script(&sequence))
+            .map(|script| print!("{script}"))
+            .map_err(|e| e.to_string()),
+        XdotoolCommand::ExportTimeline(sequence) => xdotool::write_script(&sequence)
+            .map(|script| print!("{script}"))
+            .map_err(|e| e.to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Replace the timeline of a profile with an xdotool script, creating the profile if needed
+///
+/// # Arguments
+///
+/// * `path` - The script
+/// * `name` - The name of the profile
+fn import_script(path: &Path, name: &str) -> Result<(), String> {
+    let script =
+        fs::read_to_string(path).map_err(|e| format!("could not read {}: {e}", path.display()))?;
+    let sequence =
+        xdotool::parse_script(&script).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut profile = match profiles::load(name) {
+        Ok(profile) => profile,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Profile::default(),
+        Err(e) => return Err(format!("could not load profile \"{name}\": {e}")),
+    };
+
+    let step_count = sequence.steps.len();
+    profile.sequence = sequence;
+    profiles::save(name, &profile)
+        .map_err(|e| format!("could not save profile \"{name}\": {e}"))?;
+    info!(
+        steps = step_count,
+        "Imported {} into {name}",
+        path.display()
+    );
+    println!("Imported {step_count} steps into the timeline of \"{name}\"");
+    Ok(())
+}
+
+/// Print the measured rate and the deviation from the target interval
+///
+/// # Arguments
+///
+/// * `stats` - The statistics of the run
+fn print_stats(stats: &ClickStats) {
+    let summary = stats.summary(Instant::now());
+    println!(
+        "{:.2} clicks/sec, deviation from {:?}: mean {:.1?}, p50 {:.1?}, p95 {:.1?}, p99 {:.1?}",
+        summary.clicks_per_second,
+        stats.target_interval,
+        summary.mean_deviation,
+        summary.p50_deviation,
+        summary.p95_deviation,
+        summary.p99_deviation
+    );
+}
+
+/// Print the click counter over the previous one
+///
+/// # Arguments
+///
+/// * `click_counter` - The amount of clicks sent
+/// * `click_amount` - The total amount of clicks, 0 = forever
+fn print_progress(click_counter: u64, click_amount: u64) {
+    if click_amount == 0 {
+        print!("\rClicks: {click_counter}");
+    } else {
+        print!("\rClicks: {click_counter}/{click_amount}");
+    }
+    let _ = io::stdout().flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_are_parsed_in_every_unit() {
+        assert_eq!(parse_duration("250"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration(" 100ms "), Ok(Duration::from_millis(100)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn empty_durations_are_rejected() {
+        assert_eq!(parse_duration(""), Err("invalid duration \"\"".to_owned()));
+        assert_eq!(
+            parse_duration("  "),
+            Err("invalid duration \"\"".to_owned())
+        );
+    }
+
+    #[test]
+    fn durations_without_a_number_are_rejected() {
+        assert_eq!(
+            parse_duration("s"),
+            Err("invalid duration \"s\"".to_owned())
+        );
+        assert_eq!(
+            parse_duration("-1s"),
+            Err("invalid duration \"-1s\"".to_owned())
+        );
+        assert_eq!(
+            parse_duration("1..5s"),
+            Err("invalid duration \"1..5s\"".to_owned())
+        );
+    }
+
+    #[test]
+    fn unknown_units_are_rejected() {
+        assert_eq!(
+            parse_duration("10 s"),
+            Err("invalid duration unit \" s\"".to_owned())
+        );
+        assert_eq!(
+            parse_duration("1d"),
+            Err("invalid duration unit \"d\"".to_owned())
+        );
+        // No exponents, they could only be too long
+        assert_eq!(
+            parse_duration("1e30"),
+            Err("invalid duration unit \"e30\"".to_owned())
+        );
+    }
+
+    #[test]
+    fn durations_too_long_are_rejected() {
+        let huge = format!("{}h", u64::MAX);
+        assert_eq!(
+            parse_duration(&huge),
+            Err(format!("invalid duration \"{huge}\""))
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::{ops::RangeInclusive, time::Duration};
+
 use device_query::Keycode;
 use eframe::egui::FontFamily;
 
@@ -10,31 +12,80 @@ pub const FONT_FAMILY: FontFamily = FontFamily::Monospace;
 
 // dimensions of main window
 pub const WINDOW_WIDTH: f32 = 550.0;
-pub const WINDOW_HEIGHT: f32 = 341.0;
-
-// ranges for click durations
-pub const DURATION_CLICK_MIN: u64 = 20;
-pub const DURATION_CLICK_MAX: u64 = 40;
-pub const DURATION_DOUBLE_CLICK_MIN: u64 = 30;
-pub const DURATION_DOUBLE_CLICK_MAX: u64 = 60;
+pub const WINDOW_HEIGHT: f32 = 390.0;
 
-// step widths for human-like mouse movement
-pub const MOUSE_STEP_POS_X: f64 = 10.0;
-pub const MOUSE_STEP_NEG_X: f64 = -10.0;
-pub const MOUSE_STEP_POS_Y: f64 = 10.0;
-pub const MOUSE_STEP_NEG_Y: f64 = -10.0;
+// dimensions of the compact bar shown while autoclicking
+pub const COMPACT_WINDOW_WIDTH: f32 = 380.0;
+pub const COMPACT_WINDOW_HEIGHT: f32 = 32.0;
 
 // Default input values
-pub const DEFAULT_HR_STR: &str = "0";
-pub const DEFAULT_MIN_STR: &str = "0";
-pub const DEFAULT_SEC_STR: &str = "0";
-pub const DEFAULT_MS_STR: &str = "100";
-pub const DEFAULT_CLICK_AMOUNT_STR: &str = "0";
-pub const DEFAULT_CLICK_X_STR: &str = "0";
-pub const DEFAULT_CLICK_Y_STR: &str = "0";
-pub const DEFAULT_MOVEMENT_SEC_STR: &str = "0";
-pub const DEFAULT_MOVEMENT_MS_STR: &str = "20";
+pub const DEFAULT_HR: u64 = 0;
+pub const DEFAULT_MIN: u64 = 0;
+pub const DEFAULT_SEC: f64 = 0.0;
+pub const DEFAULT_MS: f64 = 100.0;
+pub const DEFAULT_CLICK_AMOUNT: u64 = 0;
+pub const DEFAULT_CLICK_X: f64 = 0.0;
+pub const DEFAULT_CLICK_Y: f64 = 0.0;
+pub const DEFAULT_MOVEMENT_SEC: f64 = 0.0;
+pub const DEFAULT_MOVEMENT_MS: f64 = 20.0;
+
+// Input ranges, coordinates in pixels may be negative on monitors left of or above the primary one
+pub const HR_RANGE: RangeInclusive<u64> = 0..=10_000;
+pub const MIN_RANGE: RangeInclusive<u64> = 0..=100_000;
+pub const SEC_RANGE: RangeInclusive<f64> = 0.0..=1_000_000.0;
+pub const MS_RANGE: RangeInclusive<f64> = 0.0..=100_000_000.0;
+pub const MOVEMENT_SEC_RANGE: RangeInclusive<f64> = 0.0..=3_600.0;
+pub const MOVEMENT_MS_RANGE: RangeInclusive<f64> = 0.0..=3_600_000.0;
+pub const CLICK_AMOUNT_RANGE: RangeInclusive<u64> = 0..=1_000_000_000_000;
+pub const PIXEL_COORD_RANGE: RangeInclusive<f64> = -100_000.0..=100_000.0;
+pub const PERCENT_COORD_RANGE: RangeInclusive<f64> = 0.0..=100.0;
 
 // Hotkeys
 pub const HOTKEY_AUTOCLICK: Option<Keycode> = Some(Keycode::F6);
 pub const HOTKEY_SET_COORD: Option<Keycode> = Some(Keycode::Escape);
+
+// Settings & profiles
+pub const CONFIG_DIR_NAME: &str = "rusty-autoclicker";
+pub const SETTINGS_FILE_NAME: &str = "settings.toml";
+pub const VERSION_KEY: &str = "version";
+pub const PROFILES_DIR_NAME: &str = "profiles";
+pub const PROFILE_FILE_EXTENSION: &str = "toml";
+pub const PROFILE_NAME_MAX_LENGTH: usize = 32;
+
+// Control socket, lines queued for a client that does not read them & how long a write may block
+pub const IPC_SOCKET_NAME: &str = "rusty-autoclicker.sock";
+pub const IPC_CLIENT_BACKLOG: usize = 256;
+pub const IPC_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Dry run overlay
+pub const DRY_RUN_MARKER_COUNT: usize = 20;
+pub const DRY_RUN_MARKER_LIFETIME: Duration = Duration::from_secs(2);
+
+// Extra jobs, interval of a new job & input ranges, coordinates in whole pixels
+pub const DEFAULT_JOB_INTERVAL_MS: u64 = 1000;
+pub const JOB_INTERVAL_MS_RANGE: RangeInclusive<u64> = 1..=86_400_000;
+pub const JOB_COORD_RANGE: RangeInclusive<i64> = -100_000..=100_000;
+
+// Timeline, duration of a new step, width of a second & narrowest block in points
+pub const DEFAULT_STEP_MS: u64 = 100;
+pub const TIMELINE_PIXELS_PER_SECOND: f32 = 120.0;
+pub const TIMELINE_MIN_BLOCK_WIDTH: f32 = 36.0;
+pub const TIMELINE_HEIGHT: f32 = 44.0;
+
+// Target overlay, how long a click flashes
+pub const CLICK_FLASH_LIFETIME: Duration = Duration::from_millis(400);
+
+// Coordinate picker, pixels shown on each side of the picked point & points per pixel
+pub const MAGNIFIER_RADIUS: i32 = 7;
+pub const MAGNIFIER_ZOOM: f32 = 9.0;
+
+// Focus guard, time to focus the window to guard after clicking "Pick"
+pub const FOCUS_PICK_DELAY: Duration = Duration::from_secs(3);
+
+// Logging
+pub const LOGS_DIR_NAME: &str = "logs";
+pub const LOG_FILE_PREFIX: &str = "rusty-autoclicker";
+pub const LOG_FILE_SUFFIX: &str = "log";
+pub const LOG_MAX_FILES: usize = 7;
+pub const LOG_BUFFER_CAPACITY: usize = 1000;
+pub const DEFAULT_LOG_FILTER: &str = "warn,rusty_autoclicker=debug";
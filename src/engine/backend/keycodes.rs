@@ -0,0 +1,137 @@
+use rdev::Key;
+
+/// Offset between the X11 and the Linux input (evdev) keycodes
+const X11_KEYCODE_OFFSET: u8 = 8;
+
+/// X11 keycode of a key on a standard PC keyboard, `Key::Unknown` holds a raw X11 keycode
+///
+/// # Arguments
+///
+/// * `key` - The key to look up
+pub fn x11_keycode(key: Key) -> Option<u8> {
+    let keycode = match key {
+        Key::Alt => 64,
+        Key::AltGr => 108,
+        Key::Backspace => 22,
+        Key::CapsLock => 66,
+        Key::ControlLeft => 37,
+        Key::ControlRight => 105,
+        Key::Delete => 119,
+        Key::DownArrow => 116,
+        Key::End => 115,
+        Key::Escape => 9,
+        Key::F1 => 67,
+        Key::F10 => 76,
+        Key::F11 => 95,
+        Key::F12 => 96,
+        Key::F2 => 68,
+        Key::F3 => 69,
+        Key::F4 => 70,
+        Key::F5 => 71,
+        Key::F6 => 72,
+        Key::F7 => 73,
+        Key::F8 => 74,
+        Key::F9 => 75,
+        Key::Home => 110,
+        Key::LeftArrow => 113,
+        Key::MetaLeft => 133,
+        Key::MetaRight => 134,
+        Key::PageDown => 117,
+        Key::PageUp => 112,
+        Key::Return => 36,
+        Key::RightArrow => 114,
+        Key::ShiftLeft => 50,
+        Key::ShiftRight => 62,
+        Key::Space => 65,
+        Key::Tab => 23,
+        Key::UpArrow => 111,
+        Key::PrintScreen => 107,
+        Key::ScrollLock => 78,
+        Key::Pause => 127,
+        Key::NumLock => 77,
+        Key::BackQuote => 49,
+        Key::Num1 => 10,
+        Key::Num2 => 11,
+        Key::Num3 => 12,
+        Key::Num4 => 13,
+        Key::Num5 => 14,
+        Key::Num6 => 15,
+        Key::Num7 => 16,
+        Key::Num8 => 17,
+        Key::Num9 => 18,
+        Key::Num0 => 19,
+        Key::Minus => 20,
+        Key::Equal => 21,
+        Key::KeyQ => 24,
+        Key::KeyW => 25,
+        Key::KeyE => 26,
+        Key::KeyR => 27,
+        Key::KeyT => 28,
+        Key::KeyY => 29,
+        Key::KeyU => 30,
+        Key::KeyI => 31,
+        Key::KeyO => 32,
+        Key::KeyP => 33,
+        Key::LeftBracket => 34,
+        Key::RightBracket => 35,
+        Key::KeyA => 38,
+        Key::KeyS => 39,
+        Key::KeyD => 40,
+        Key::KeyF => 41,
+        Key::KeyG => 42,
+        Key::KeyH => 43,
+        Key::KeyJ => 44,
+        Key::KeyK => 45,
+        Key::KeyL => 46,
+        Key::SemiColon => 47,
+        Key::Quote => 48,
+        Key::BackSlash => 51,
+        Key::IntlBackslash => 94,
+        Key::KeyZ => 52,
+        Key::KeyX => 53,
+        Key::KeyC => 54,
+        Key::KeyV => 55,
+        Key::KeyB => 56,
+        Key::KeyN => 57,
+        Key::KeyM => 58,
+        Key::Comma => 59,
+        Key::Dot => 60,
+        Key::Slash => 61,
+        Key::Insert => 118,
+        Key::KpReturn => 104,
+        Key::KpMinus => 82,
+        Key::KpPlus => 86,
+        Key::KpMultiply => 63,
+        Key::KpDivide => 106,
+        Key::Kp0 => 90,
+        Key::Kp1 => 87,
+        Key::Kp2 => 88,
+        Key::Kp3 => 89,
+        Key::Kp4 => 83,
+        Key::Kp5 => 84,
+        Key::Kp6 => 85,
+        Key::Kp7 => 79,
+        Key::Kp8 => 80,
+        Key::Kp9 => 81,
+        Key::KpDelete => 91,
+        Key::Unknown(keycode) => return u8::try_from(keycode).ok(),
+        Key::Function => return None,
+    };
+    Some(keycode)
+}
+
+/// Linux input (evdev) keycode of a key, as used by uinput
+///
+/// # Arguments
+///
+/// * `key` - The key to look up
+pub fn evdev_keycode(key: Key) -> Option<u16> {
+    x11_keycode(key)
+        .and_then(|keycode| keycode.checked_sub(X11_KEYCODE_OFFSET))
+        .map(u16::from)
+}
+
+/// Every evdev keycode reachable from an X11 keycode, declared as keys of the uinput device
+pub fn evdev_keycodes() -> impl Iterator<Item = u16> {
+    1..=u16::from(u8::MAX - X11_KEYCODE_OFFSET)
+}
@@ -0,0 +1,304 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use image::RgbImage;
+use rdev::{Button, EventType, Key};
+
+use super::{InputBackend, InputError};
+use crate::engine::{
+    clock::VirtualClock,
+    pixel::Color,
+    screen::{Monitor, Region},
+    window::WindowInfo,
+};
+
+/// Backend recording the events instead of sending them, for tests
+///
+/// Clones share the recorded events, so keep one to inspect them after handing the backend over.
+#[derive(Clone, Debug, Default)]
+pub struct MockBackend {
+    events: Arc<Mutex<Vec<(Duration, EventType)>>>,
+    clock: Option<VirtualClock>,
+    mouse_position: (i32, i32),
+    monitors: Option<Vec<Monitor>>,
+    windows: Option<Arc<Mutex<Vec<WindowInfo>>>>,
+    active_window: Option<Arc<Mutex<u32>>>,
+    pixel_color: Option<Arc<Mutex<Color>>>,
+    screen: Option<Arc<Mutex<RgbImage>>>,
+    failing_key: Option<Key>,
+    failing_release: bool,
+    failed_events: Arc<Mutex<Vec<EventType>>>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Timestamp the events with the elapsed time of the clock
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock the engine runs on
+    pub fn with_clock(mut self, clock: VirtualClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Report a fixed mouse position instead of the real one
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The mouse X coordinate
+    /// * `y` - The mouse Y coordinate
+    pub fn with_mouse_position(mut self, x: i32, y: i32) -> Self {
+        self.mouse_position = (x, y);
+        self
+    }
+
+    /// Report fixed monitors instead of the ones of the real display
+    ///
+    /// # Arguments
+    ///
+    /// * `monitors` - The monitors, the primary one first
+    pub fn with_monitors(mut self, monitors: Vec<Monitor>) -> Self {
+        self.monitors = Some(monitors);
+        self
+    }
+
+    /// Report fixed top-level windows instead of the ones of the real display
+    /// Clones share the windows, so they can be changed with [`set_windows`](Self::set_windows)
+    /// while a job runs
+    ///
+    /// # Arguments
+    ///
+    /// * `windows` - The windows, from bottom to top
+    pub fn with_windows(mut self, windows: Vec<WindowInfo>) -> Self {
+        self.windows = Some(Arc::new(Mutex::new(windows)));
+        self
+    }
+
+    /// Replace the windows set with [`with_windows`](Self::with_windows)
+    ///
+    /// # Arguments
+    ///
+    /// * `windows` - The windows, from bottom to top
+    pub fn set_windows(&self, windows: Vec<WindowInfo>) {
+        if let Some(shared) = &self.windows {
+            *shared.lock().unwrap() = windows;
+        }
+    }
+
+    /// Report a fixed focused window instead of the one of the real display
+    /// Clones share it, so it can be changed with [`set_active_window`](Self::set_active_window)
+    /// while a job runs
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the focused window, 0 for none
+    pub fn with_active_window(mut self, id: u32) -> Self {
+        self.active_window = Some(Arc::new(Mutex::new(id)));
+        self
+    }
+
+    /// Report the same color for every screen pixel instead of capturing the real display
+    /// Clones share it, so it can be changed with [`set_pixel_color`](Self::set_pixel_color)
+    /// while a job runs
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color of the pixels
+    pub fn with_pixel_color(mut self, color: Color) -> Self {
+        self.pixel_color = Some(Arc::new(Mutex::new(color)));
+        self
+    }
+
+    /// Replace the color set with [`with_pixel_color`](Self::with_pixel_color)
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color of the pixels
+    pub fn set_pixel_color(&self, color: Color) {
+        if let Some(shared) = &self.pixel_color {
+            *shared.lock().unwrap() = color;
+        }
+    }
+
+    /// Capture regions of the image instead of the real display
+    /// Without [`with_monitors`](Self::with_monitors), the image is also the only monitor
+    /// Clones share the image, so it can be changed with [`set_screen`](Self::set_screen) while a
+    /// job runs
+    ///
+    /// # Arguments
+    ///
+    /// * `screen` - The content of the screen
+    pub fn with_screen(mut self, screen: RgbImage) -> Self {
+        self.screen = Some(Arc::new(Mutex::new(screen)));
+        self
+    }
+
+    /// Replace the image set with [`with_screen`](Self::with_screen)
+    ///
+    /// # Arguments
+    ///
+    /// * `screen` - The content of the screen
+    pub fn set_screen(&self, screen: RgbImage) {
+        if let Some(shared) = &self.screen {
+            *shared.lock().unwrap() = screen;
+        }
+    }
+
+    /// Replace the focused window set with [`with_active_window`](Self::with_active_window)
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the focused window, 0 for none
+    pub fn set_active_window(&self, id: u32) {
+        if let Some(shared) = &self.active_window {
+            *shared.lock().unwrap() = id;
+        }
+    }
+
+    /// Fail to press a key, e.g. to check what is released after a failed key press
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key that cannot be pressed
+    pub fn with_failing_key(mut self, key: Key) -> Self {
+        self.failing_key = Some(key);
+        self
+    }
+
+    /// Fail to release any button or key, e.g. to check that a failed click is still released
+    pub fn with_failing_release(mut self) -> Self {
+        self.failing_release = true;
+        self
+    }
+
+    /// The events that could not be sent so far, oldest first
+    pub fn failed_events(&self) -> Vec<EventType> {
+        self.failed_events.lock().unwrap().clone()
+    }
+
+    /// The events recorded so far, oldest first
+    pub fn events(&self) -> Vec<EventType> {
+        self.timed_events()
+            .into_iter()
+            .map(|(_, event_type)| event_type)
+            .collect()
+    }
+
+    /// The events recorded so far with the elapsed time of the clock when they were sent
+    pub fn timed_events(&self) -> Vec<(Duration, EventType)> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Forget the recorded events
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    fn record(&self, event_type: EventType) -> Result<(), InputError> {
+        let elapsed = self
+            .clock
+            .as_ref()
+            .map(VirtualClock::elapsed)
+            .unwrap_or_default();
+        self.events.lock().unwrap().push((elapsed, event_type));
+        Ok(())
+    }
+
+    fn fail(&self, event_type: EventType, message: String) -> Result<(), InputError> {
+        self.failed_events.lock().unwrap().push(event_type);
+        Err(InputError::new(message))
+    }
+}
+
+impl InputBackend for MockBackend {
+    fn mouse_position(&self) -> Option<(i32, i32)> {
+        Some(self.mouse_position)
+    }
+
+    fn monitors(&self) -> Option<Vec<Monitor>> {
+        self.monitors.clone().or_else(|| {
+            let screen = self.screen.as_ref()?.lock().unwrap();
+            Some(vec![Monitor {
+                x: 0,
+                y: 0,
+                width: screen.width(),
+                height: screen.height(),
+            }])
+        })
+    }
+
+    fn windows(&self) -> Option<Vec<WindowInfo>> {
+        self.windows
+            .as_ref()
+            .map(|windows| windows.lock().unwrap().clone())
+    }
+
+    fn active_window(&self) -> Option<u32> {
+        self.active_window.as_ref().map(|id| *id.lock().unwrap())
+    }
+
+    fn pixel(&self, _x: i32, _y: i32) -> Option<Color> {
+        self.pixel_color
+            .as_ref()
+            .map(|color| *color.lock().unwrap())
+    }
+
+    fn capture(&self, region: Region) -> Option<RgbImage> {
+        let screen = self.screen.as_ref()?.lock().unwrap();
+        Some(
+            image::imageops::crop_imm(
+                &*screen,
+                region.x.max(0) as u32,
+                region.y.max(0) as u32,
+                region.width,
+                region.height,
+            )
+            .to_image(),
+        )
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) -> Result<(), InputError> {
+        self.record(EventType::MouseMove { x, y })
+    }
+
+    fn press(&mut self, button: Button) -> Result<(), InputError> {
+        self.record(EventType::ButtonPress(button))
+    }
+
+    fn release(&mut self, button: Button) -> Result<(), InputError> {
+        if self.failing_release {
+            return self.fail(
+                EventType::ButtonRelease(button),
+                format!("Could not release {button:?}"),
+            );
+        }
+        self.record(EventType::ButtonRelease(button))
+    }
+
+    fn wheel(&mut self, delta_x: i64, delta_y: i64) -> Result<(), InputError> {
+        self.record(EventType::Wheel { delta_x, delta_y })
+    }
+
+    fn key_press(&mut self, key: Key) -> Result<(), InputError> {
+        if self.failing_key == Some(key) {
+            return self.fail(EventType::KeyPress(key), format!("Could not press {key:?}"));
+        }
+        self.record(EventType::KeyPress(key))
+    }
+
+    fn key_release(&mut self, key: Key) -> Result<(), InputError> {
+        if self.failing_release {
+            return self.fail(
+                EventType::KeyRelease(key),
+                format!("Could not release {key:?}"),
+            );
+        }
+        self.record(EventType::KeyRelease(key))
+    }
+}
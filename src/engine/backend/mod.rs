@@ -0,0 +1,171 @@
+//! Input backends sending the simulated events to the OS
+//!
+//! Pick one at runtime with [`BackendKind::create`], use [`MockBackend`] to observe the events or
+//! [`NullBackend`] to discard them.
+
+use std::fmt;
+
+use image::RgbImage;
+use rdev::{Button, EventType, Key};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    pixel::Color,
+    screen::{Monitor, Region},
+    window::WindowInfo,
+};
+
+#[cfg(target_os = "linux")]
+mod keycodes;
+mod mock;
+mod null;
+mod rdev_backend;
+#[cfg(target_os = "linux")]
+mod uinput;
+#[cfg(target_os = "linux")]
+mod xtest;
+
+pub use mock::MockBackend;
+pub use null::NullBackend;
+pub use rdev_backend::RdevBackend;
+#[cfg(target_os = "linux")]
+pub use uinput::UinputBackend;
+#[cfg(target_os = "linux")]
+pub use xtest::XTestBackend;
+
+/// Error returned by an input backend
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct InputError {
+    pub message: String,
+}
+
+impl InputError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Something able to inject mouse & keyboard events
+pub trait InputBackend: Send {
+    /// Move the mouse to absolute screen coordinates
+    fn move_to(&mut self, x: f64, y: f64) -> Result<(), InputError>;
+
+    /// Press a mouse button
+    fn press(&mut self, button: Button) -> Result<(), InputError>;
+
+    /// Release a mouse button
+    fn release(&mut self, button: Button) -> Result<(), InputError>;
+
+    /// Scroll by the given amount of wheel steps, positive is right/up
+    fn wheel(&mut self, delta_x: i64, delta_y: i64) -> Result<(), InputError>;
+
+    /// Press a keyboard key
+    fn key_press(&mut self, key: Key) -> Result<(), InputError>;
+
+    /// Release a keyboard key
+    fn key_release(&mut self, key: Key) -> Result<(), InputError>;
+
+    /// The mouse position known to the backend, `None` to read it from the OS
+    fn mouse_position(&self) -> Option<(i32, i32)> {
+        None
+    }
+
+    /// The monitors known to the backend, `None` to read them from the display
+    fn monitors(&self) -> Option<Vec<Monitor>> {
+        None
+    }
+
+    /// The top-level windows known to the backend from bottom to top, `None` to read them from
+    /// the display
+    fn windows(&self) -> Option<Vec<WindowInfo>> {
+        None
+    }
+
+    /// The id of the focused top-level window known to the backend, `Some(0)` if no window is
+    /// focused, `None` to read it from the display
+    fn active_window(&self) -> Option<u32> {
+        None
+    }
+
+    /// The color of a screen pixel known to the backend, `None` to capture it from the display
+    fn pixel(&self, _x: i32, _y: i32) -> Option<Color> {
+        None
+    }
+
+    /// A capture of a region of the screen known to the backend, `None` to capture the display
+    fn capture(&self, _region: Region) -> Option<RgbImage> {
+        None
+    }
+
+    /// Send an `rdev` event by dispatching it to the matching method
+    fn send(&mut self, event_type: &EventType) -> Result<(), InputError> {
+        match *event_type {
+            EventType::MouseMove { x, y } => self.move_to(x, y),
+            EventType::ButtonPress(button) => self.press(button),
+            EventType::ButtonRelease(button) => self.release(button),
+            EventType::Wheel { delta_x, delta_y } => self.wheel(delta_x, delta_y),
+            EventType::KeyPress(key) => self.key_press(key),
+            EventType::KeyRelease(key) => self.key_release(key),
+        }
+    }
+}
+
+/// The backends selectable in the settings and on the command line
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    /// Cross-platform, through the `rdev` crate
+    #[default]
+    Rdev,
+    /// X11 XTest extension (Linux)
+    #[serde(rename = "xtest")]
+    XTest,
+    /// Virtual `/dev/uinput` device, also works on Wayland (Linux)
+    Uinput,
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendKind::Rdev => write!(f, "rdev"),
+            BackendKind::XTest => write!(f, "XTest (X11)"),
+            BackendKind::Uinput => write!(f, "uinput"),
+        }
+    }
+}
+
+impl BackendKind {
+    /// The backends available on this platform
+    pub fn available() -> &'static [BackendKind] {
+        if cfg!(target_os = "linux") {
+            &[BackendKind::Rdev, BackendKind::XTest, BackendKind::Uinput]
+        } else {
+            &[BackendKind::Rdev]
+        }
+    }
+
+    /// Create the backend, connecting to the display server or device it needs
+    pub fn create(self) -> Result<Box<dyn InputBackend>, InputError> {
+        match self {
+            BackendKind::Rdev => Ok(Box::new(RdevBackend)),
+            #[cfg(target_os = "linux")]
+            BackendKind::XTest => Ok(Box::new(XTestBackend::new()?)),
+            #[cfg(target_os = "linux")]
+            BackendKind::Uinput => Ok(Box::new(UinputBackend::new()?)),
+            #[cfg(not(target_os = "linux"))]
+            BackendKind::XTest | BackendKind::Uinput => Err(InputError::new(format!(
+                "The {self} backend is only available on Linux"
+            ))),
+        }
+    }
+}
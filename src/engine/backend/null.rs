@@ -0,0 +1,33 @@
+use rdev::{Button, Key};
+
+use super::{InputBackend, InputError};
+
+/// Backend discarding every event, for dry runs
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NullBackend;
+
+impl InputBackend for NullBackend {
+    fn move_to(&mut self, _x: f64, _y: f64) -> Result<(), InputError> {
+        Ok(())
+    }
+
+    fn press(&mut self, _button: Button) -> Result<(), InputError> {
+        Ok(())
+    }
+
+    fn release(&mut self, _button: Button) -> Result<(), InputError> {
+        Ok(())
+    }
+
+    fn wheel(&mut self, _delta_x: i64, _delta_y: i64) -> Result<(), InputError> {
+        Ok(())
+    }
+
+    fn key_press(&mut self, _key: Key) -> Result<(), InputError> {
+        Ok(())
+    }
+
+    fn key_release(&mut self, _key: Key) -> Result<(), InputError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use std::{env, thread, time::Duration};
+
+use rdev::{Button, EventType, Key, simulate};
+
+use super::{InputBackend, InputError};
+
+/// Backend sending the events with `rdev::simulate`
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct RdevBackend;
+
+impl RdevBackend {
+    /// Simulate the event
+    ///
+    /// # Arguments
+    ///
+    /// * `event_type` - The event type to simulate
+    fn simulate(&self, event_type: &EventType) -> Result<(), InputError> {
+        let result =
+            simulate(event_type).map_err(|_| InputError::new("rdev could not simulate the event"));
+
+        // Let the OS catchup (at least MacOS)
+        if env::consts::OS == "macos" {
+            thread::sleep(Duration::from_millis(20u64));
+        }
+
+        result
+    }
+}
+
+impl InputBackend for RdevBackend {
+    fn move_to(&mut self, x: f64, y: f64) -> Result<(), InputError> {
+        self.simulate(&EventType::MouseMove { x, y })
+    }
+
+    fn press(&mut self, button: Button) -> Result<(), InputError> {
+        self.simulate(&EventType::ButtonPress(button))
+    }
+
+    fn release(&mut self, button: Button) -> Result<(), InputError> {
+        self.simulate(&EventType::ButtonRelease(button))
+    }
+
+    fn wheel(&mut self, delta_x: i64, delta_y: i64) -> Result<(), InputError> {
+        self.simulate(&EventType::Wheel { delta_x, delta_y })
+    }
+
+    fn key_press(&mut self, key: Key) -> Result<(), InputError> {
+        self.simulate(&EventType::KeyPress(key))
+    }
+
+    fn key_release(&mut self, key: Key) -> Result<(), InputError> {
+        self.simulate(&EventType::KeyRelease(key))
+    }
+}
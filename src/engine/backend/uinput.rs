@@ -0,0 +1,218 @@
+use evdev::{
+    AbsInfo, AbsoluteAxisCode, AttributeSet, EventType, InputEvent, KeyCode, RelativeAxisCode,
+    UinputAbsSetup, uinput::VirtualDevice,
+};
+use rdev::{Button, Key};
+
+use super::{
+    InputBackend, InputError,
+    keycodes::{evdev_keycode, evdev_keycodes},
+};
+use crate::engine::{
+    defines::UINPUT_SCREEN_ENV,
+    screen::{self, Monitor, Region},
+};
+
+const DEVICE_NAME: &str = "Rusty AutoClicker virtual input";
+
+// Key event values
+const KEY_RELEASED: i32 = 0;
+const KEY_PRESSED: i32 = 1;
+
+/// Backend sending the events through a virtual `/dev/uinput` device
+///
+/// Works wherever the kernel input stack is used (X11, Wayland, console), but needs write access
+/// to `/dev/uinput`, e.g. through the `input` group or a udev rule.
+/// The mouse is an absolute pointer spanning the whole virtual screen, like a drawing tablet.
+/// The screen is the one set in `RUSTY_AUTOCLICKER_SCREEN`, or else the monitors of the display.
+pub struct UinputBackend {
+    device: VirtualDevice,
+    monitors: Vec<Monitor>,
+    /// The virtual screen the pointer axes span
+    screen: Monitor,
+}
+
+impl UinputBackend {
+    /// Create the virtual device, spanning the configured screen or the monitors of the display
+    pub fn new() -> Result<Self, InputError> {
+        let monitors = match std::env::var(UINPUT_SCREEN_ENV) {
+            Ok(screen) => vec![parse_screen(&screen)?],
+            Err(_) => screen::monitors().map_err(|e| {
+                InputError::new(format!(
+                    "Unknown screen size ({}), set it in {UINPUT_SCREEN_ENV}, e.g. 1920x1080",
+                    e.message
+                ))
+            })?,
+        };
+        Self::with_monitors(monitors)
+    }
+
+    /// Create the virtual device, spanning the given monitors
+    ///
+    /// # Arguments
+    ///
+    /// * `monitors` - The monitors, the primary one first
+    pub fn with_monitors(monitors: Vec<Monitor>) -> Result<Self, InputError> {
+        let screen = Monitor::bounds(&monitors)
+            .filter(|screen| screen.width > 0 && screen.height > 0)
+            .ok_or_else(|| {
+                InputError::new(format!(
+                    "Unknown screen size, set it in {UINPUT_SCREEN_ENV}, e.g. 1920x1080"
+                ))
+            })?;
+
+        let mut keys = AttributeSet::<KeyCode>::new();
+        for keycode in evdev_keycodes() {
+            keys.insert(KeyCode(keycode));
+        }
+        keys.insert(KeyCode::BTN_LEFT);
+        keys.insert(KeyCode::BTN_RIGHT);
+        keys.insert(KeyCode::BTN_MIDDLE);
+
+        let mut wheels = AttributeSet::<RelativeAxisCode>::new();
+        wheels.insert(RelativeAxisCode::REL_WHEEL);
+        wheels.insert(RelativeAxisCode::REL_HWHEEL);
+
+        let axis = |code, size: u32| {
+            UinputAbsSetup::new(
+                code,
+                AbsInfo::new(0, 0, size.saturating_sub(1) as i32, 0, 0, 0),
+            )
+        };
+
+        let device = VirtualDevice::builder()
+            .and_then(|builder| {
+                builder
+                    .name(DEVICE_NAME)
+                    .with_keys(&keys)?
+                    .with_relative_axes(&wheels)?
+                    .with_absolute_axis(&axis(AbsoluteAxisCode::ABS_X, screen.width))?
+                    .with_absolute_axis(&axis(AbsoluteAxisCode::ABS_Y, screen.height))?
+                    .build()
+            })
+            .map_err(|e| InputError::new(format!("Could not create the uinput device: {e}")))?;
+
+        Ok(Self {
+            device,
+            monitors,
+            screen,
+        })
+    }
+
+    /// Emit a batch of events, followed by a synchronization
+    ///
+    /// # Arguments
+    ///
+    /// * `events` - The events to emit
+    fn emit(&mut self, events: &[InputEvent]) -> Result<(), InputError> {
+        self.device
+            .emit(events)
+            .map_err(|e| InputError::new(format!("Could not write to uinput: {e}")))
+    }
+
+    /// Emit a key or button event
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The evdev key or button code
+    /// * `value` - Pressed or released
+    fn emit_key(&mut self, code: KeyCode, value: i32) -> Result<(), InputError> {
+        self.emit(&[InputEvent::new(EventType::KEY.0, code.0, value)])
+    }
+}
+
+impl InputBackend for UinputBackend {
+    /// The monitors the pointer spans, so that relative coordinates match the axes
+    fn monitors(&self) -> Option<Vec<Monitor>> {
+        Some(self.monitors.clone())
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) -> Result<(), InputError> {
+        // The axes start at the top-left corner of the virtual screen
+        let x = x as i32 - self.screen.x;
+        let y = y as i32 - self.screen.y;
+        self.emit(&[
+            InputEvent::new(EventType::ABSOLUTE.0, AbsoluteAxisCode::ABS_X.0, x),
+            InputEvent::new(EventType::ABSOLUTE.0, AbsoluteAxisCode::ABS_Y.0, y),
+        ])
+    }
+
+    fn press(&mut self, button: Button) -> Result<(), InputError> {
+        self.emit_key(button_code(button)?, KEY_PRESSED)
+    }
+
+    fn release(&mut self, button: Button) -> Result<(), InputError> {
+        self.emit_key(button_code(button)?, KEY_RELEASED)
+    }
+
+    fn wheel(&mut self, delta_x: i64, delta_y: i64) -> Result<(), InputError> {
+        self.emit(&[
+            InputEvent::new(
+                EventType::RELATIVE.0,
+                RelativeAxisCode::REL_HWHEEL.0,
+                delta_x as i32,
+            ),
+            InputEvent::new(
+                EventType::RELATIVE.0,
+                RelativeAxisCode::REL_WHEEL.0,
+                delta_y as i32,
+            ),
+        ])
+    }
+
+    fn key_press(&mut self, key: Key) -> Result<(), InputError> {
+        self.emit_key(key_code(key)?, KEY_PRESSED)
+    }
+
+    fn key_release(&mut self, key: Key) -> Result<(), InputError> {
+        self.emit_key(key_code(key)?, KEY_RELEASED)
+    }
+}
+
+/// Parse a configured screen, `WIDTHxHEIGHT` or `WIDTHxHEIGHT+X+Y`
+///
+/// # Arguments
+///
+/// * `string` - The screen
+fn parse_screen(string: &str) -> Result<Monitor, InputError> {
+    let string = string.trim();
+    let region = if string.contains(['+', '-']) {
+        string.parse::<Region>()
+    } else {
+        format!("{string}+0+0").parse::<Region>()
+    }
+    .map_err(|e| InputError::new(format!("{UINPUT_SCREEN_ENV}: {e}")))?;
+    Ok(Monitor {
+        x: region.x,
+        y: region.y,
+        width: region.width,
+        height: region.height,
+    })
+}
+
+/// evdev code of a mouse button
+///
+/// # Arguments
+///
+/// * `button` - The mouse button
+fn button_code(button: Button) -> Result<KeyCode, InputError> {
+    match button {
+        Button::Left => Ok(KeyCode::BTN_LEFT),
+        Button::Right => Ok(KeyCode::BTN_RIGHT),
+        Button::Middle => Ok(KeyCode::BTN_MIDDLE),
+        Button::Unknown(_) => Err(InputError::new(format!(
+            "{button:?} is not supported by uinput"
+        ))),
+    }
+}
+
+/// evdev code of a key
+///
+/// # Arguments
+///
+/// * `key` - The key
+fn key_code(key: Key) -> Result<KeyCode, InputError> {
+    evdev_keycode(key)
+        .map(KeyCode)
+        .ok_or_else(|| InputError::new(format!("No evdev keycode for {key:?}")))
+}
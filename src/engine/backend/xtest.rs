@@ -0,0 +1,156 @@
+use rdev::{Button, Key};
+use x11rb::{
+    CURRENT_TIME,
+    connection::Connection,
+    protocol::{
+        xproto::{
+            BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+            MOTION_NOTIFY_EVENT, Window,
+        },
+        xtest::ConnectionExt,
+    },
+    rust_connection::RustConnection,
+};
+
+use super::{InputBackend, InputError, keycodes::x11_keycode};
+
+// X11 pointer buttons used for scrolling
+const BUTTON_WHEEL_UP: u8 = 4;
+const BUTTON_WHEEL_DOWN: u8 = 5;
+const BUTTON_WHEEL_LEFT: u8 = 6;
+const BUTTON_WHEEL_RIGHT: u8 = 7;
+
+/// Backend sending the events through the X11 XTest extension
+pub struct XTestBackend {
+    connection: RustConnection,
+    root: Window,
+}
+
+impl XTestBackend {
+    /// Connect to the X server named by `$DISPLAY`
+    pub fn new() -> Result<Self, InputError> {
+        let (connection, screen_num) = x11rb::connect(None)
+            .map_err(|e| InputError::new(format!("Could not connect to the X server: {e}")))?;
+        connection
+            .xtest_get_version(2, 2)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(|e| InputError::new(format!("The XTest extension is not available: {e}")))?;
+        let root = connection.setup().roots[screen_num].root;
+
+        Ok(Self { connection, root })
+    }
+
+    /// Send a fake input event and flush it to the server
+    ///
+    /// # Arguments
+    ///
+    /// * `event_type` - The X11 event type
+    /// * `detail` - The button or keycode, 0 for motion
+    /// * `x` - The x coordinate for motion
+    /// * `y` - The y coordinate for motion
+    fn fake_input(&self, event_type: u8, detail: u8, x: i16, y: i16) -> Result<(), InputError> {
+        self.connection
+            .xtest_fake_input(event_type, detail, CURRENT_TIME, self.root, x, y, 0)
+            .map_err(x11_error)?
+            .check()
+            .map_err(x11_error)?;
+        self.connection.flush().map_err(x11_error)
+    }
+
+    /// Press & release a pointer button `count` times
+    ///
+    /// # Arguments
+    ///
+    /// * `button` - The X11 pointer button
+    /// * `count` - How many times to click it
+    fn click_button(&self, button: u8, count: u64) -> Result<(), InputError> {
+        for _ in 0..count {
+            self.fake_input(BUTTON_PRESS_EVENT, button, 0, 0)?;
+            self.fake_input(BUTTON_RELEASE_EVENT, button, 0, 0)?;
+        }
+        Ok(())
+    }
+}
+
+impl InputBackend for XTestBackend {
+    fn move_to(&mut self, x: f64, y: f64) -> Result<(), InputError> {
+        self.fake_input(MOTION_NOTIFY_EVENT, 0, x11_coord(x)?, x11_coord(y)?)
+    }
+
+    fn press(&mut self, button: Button) -> Result<(), InputError> {
+        self.fake_input(BUTTON_PRESS_EVENT, x11_button(button), 0, 0)
+    }
+
+    fn release(&mut self, button: Button) -> Result<(), InputError> {
+        self.fake_input(BUTTON_RELEASE_EVENT, x11_button(button), 0, 0)
+    }
+
+    fn wheel(&mut self, delta_x: i64, delta_y: i64) -> Result<(), InputError> {
+        let button_x = if delta_x > 0 {
+            BUTTON_WHEEL_RIGHT
+        } else {
+            BUTTON_WHEEL_LEFT
+        };
+        let button_y = if delta_y > 0 {
+            BUTTON_WHEEL_UP
+        } else {
+            BUTTON_WHEEL_DOWN
+        };
+        self.click_button(button_x, delta_x.unsigned_abs())?;
+        self.click_button(button_y, delta_y.unsigned_abs())
+    }
+
+    fn key_press(&mut self, key: Key) -> Result<(), InputError> {
+        self.fake_input(KEY_PRESS_EVENT, keycode(key)?, 0, 0)
+    }
+
+    fn key_release(&mut self, key: Key) -> Result<(), InputError> {
+        self.fake_input(KEY_RELEASE_EVENT, keycode(key)?, 0, 0)
+    }
+}
+
+/// X11 pointer button number of a mouse button
+///
+/// # Arguments
+///
+/// * `button` - The mouse button
+fn x11_button(button: Button) -> u8 {
+    match button {
+        Button::Left => 1,
+        Button::Middle => 2,
+        Button::Right => 3,
+        Button::Unknown(button) => button,
+    }
+}
+
+/// X11 coordinate of a pixel, or an error if it does not fit the 16 bits of the protocol
+///
+/// # Arguments
+///
+/// * `value` - The coordinate in pixels
+fn x11_coord(value: f64) -> Result<i16, InputError> {
+    value
+        .is_finite()
+        .then_some(value as i64)
+        .and_then(|pixel| i16::try_from(pixel).ok())
+        .ok_or_else(|| InputError::new(format!("The coordinate {value} is out of the X11 screen")))
+}
+
+/// X11 keycode of a key, or an error if it has none
+///
+/// # Arguments
+///
+/// * `key` - The key
+fn keycode(key: Key) -> Result<u8, InputError> {
+    x11_keycode(key).ok_or_else(|| InputError::new(format!("No X11 keycode for {key:?}")))
+}
+
+/// Wrap an X11 error into an `InputError`
+///
+/// # Arguments
+///
+/// * `e` - The X11 error
+fn x11_error(e: impl std::fmt::Display) -> InputError {
+    InputError::new(format!("X11 error: {e}"))
+}
@@ -0,0 +1,78 @@
+//! Time source of the engine, replaceable by a virtual clock to test timings without waiting
+
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Where the engine gets the current time from and how it waits
+pub trait Clock: Send {
+    /// The current time
+    fn now(&self) -> Instant;
+
+    /// Wait for the given duration
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// The real time, sleeping the current thread
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// A clock that only advances when slept on, returning immediately
+///
+/// Clones share the elapsed time, so keep one to read it after handing the clock over.
+#[derive(Clone, Debug)]
+pub struct VirtualClock {
+    start: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::default(),
+        }
+    }
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time slept since the clock was created
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+
+    /// Move the time forward without sleeping
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - How far to move
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.advance(duration);
+    }
+}
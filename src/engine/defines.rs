@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+// ranges for click durations
+pub const DURATION_CLICK_MIN: u64 = 20;
+pub const DURATION_CLICK_MAX: u64 = 40;
+pub const DURATION_DOUBLE_CLICK_MIN: u64 = 30;
+pub const DURATION_DOUBLE_CLICK_MAX: u64 = 60;
+
+// step widths for human-like mouse movement
+pub const MOUSE_STEP_POS_X: f64 = 10.0;
+pub const MOUSE_STEP_NEG_X: f64 = -10.0;
+pub const MOUSE_STEP_POS_Y: f64 = 10.0;
+pub const MOUSE_STEP_NEG_Y: f64 = -10.0;
+
+// Job defaults
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+pub const DEFAULT_MOVEMENT_DELAY: Duration = Duration::from_millis(20);
+pub const DEFAULT_MAX_FAILURES: u32 = 5;
+
+// Amount of recent click intervals kept for the statistics
+pub const STATS_WINDOW: usize = 1000;
+
+// Amount of injected events kept in the history
+pub const HISTORY_CAPACITY: usize = 100_000;
+
+// Most clicks a job exported as a sequence is unrolled to, when they do not make whole rounds
+pub const MAX_UNROLLED_CLICKS: u64 = 100_000;
+
+// Longest sleep of a running job, so that stop requests are handled quickly
+pub const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How often a paused job looks for its missing target window again
+pub const WINDOW_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often a job waiting for a pixel color captures the pixel again
+pub const PIXEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Image targets
+/// Lowest match score of a target image, from 0 to 1
+pub const DEFAULT_IMAGE_CONFIDENCE: f32 = 0.9;
+/// Smallest side of a target image downscaled more than twice, searched before the full-size one
+pub const TEMPLATE_COARSE_MIN_SIZE: usize = 8;
+/// Best spots of the downscaled search refined at full size
+pub const TEMPLATE_CANDIDATES: usize = 16;
+
+/// Environment variable setting the screen the uinput pointer spans, `WIDTHxHEIGHT[+X+Y]`, for
+/// displays whose monitors cannot be read, e.g. Wayland without XWayland
+pub const UINPUT_SCREEN_ENV: &str = "RUSTY_AUTOCLICKER_SCREEN";
@@ -0,0 +1,400 @@
+//! Bounded history of the injected events, exportable to CSV or JSON Lines for audits
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use image::RgbImage;
+use rdev::{Button, EventType, Key};
+use serde::{
+    Deserialize, Serialize,
+    de::{DeserializeOwned, IntoDeserializer, value::StrDeserializer},
+};
+
+use super::{
+    backend::{InputBackend, InputError},
+    defines::HISTORY_CAPACITY,
+    pixel::Color,
+    screen::{Monitor, Region},
+    sequence::{Action, Sequence, Step},
+    window::WindowInfo,
+};
+use crate::types::{AppMode, ClickType};
+
+/// An event sent by an input backend
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the event was sent, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// `move`, `press`, `release`, `wheel`, `key_press` or `key_release`
+    pub event: String,
+    pub button: Option<String>,
+    pub key: Option<String>,
+    /// Where the mouse was moved to, or the last known position for the other events
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub delta_x: Option<i64>,
+    pub delta_y: Option<i64>,
+    pub mode: AppMode,
+}
+
+/// File formats the history can be exported to
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum HistoryFormat {
+    Csv,
+    JsonLines,
+}
+
+impl HistoryFormat {
+    /// Guess the format from the file extension, `.csv`, `.jsonl` or `.json`
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to export to
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "csv" => Some(HistoryFormat::Csv),
+            "jsonl" | "json" => Some(HistoryFormat::JsonLines),
+            _ => None,
+        }
+    }
+
+    /// The usual file extension
+    pub fn extension(self) -> &'static str {
+        match self {
+            HistoryFormat::Csv => "csv",
+            HistoryFormat::JsonLines => "jsonl",
+        }
+    }
+}
+
+/// The last `HISTORY_CAPACITY` events sent, oldest first
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct ClickHistory {
+    entries: VecDeque<HistoryEntry>,
+    /// Events recorded since the last clear, including the dropped ones
+    recorded_count: u64,
+    /// Last position the mouse was moved to
+    position: Option<(f64, f64)>,
+}
+
+impl ClickHistory {
+    /// The recorded events, oldest first
+    pub fn entries(&self) -> &VecDeque<HistoryEntry> {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Events recorded since the last clear, including the ones dropped when full
+    pub fn recorded_count(&self) -> u64 {
+        self.recorded_count
+    }
+
+    /// Remove all events
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recorded_count = 0;
+        self.position = None;
+    }
+
+    /// Record an event sent now, dropping the oldest one if full
+    ///
+    /// # Arguments
+    ///
+    /// * `event_type` - The event that was sent
+    /// * `mode` - The app mode it was sent in
+    pub fn record(&mut self, event_type: &EventType, mode: AppMode) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+
+        let mut entry = HistoryEntry {
+            timestamp_ms,
+            event: String::new(),
+            button: None,
+            key: None,
+            x: None,
+            y: None,
+            delta_x: None,
+            delta_y: None,
+            mode,
+        };
+        let button_name = |button: Button| Some(format!("{button:?}"));
+        let key_name = |key: Key| Some(format!("{key:?}"));
+        match *event_type {
+            EventType::MouseMove { x, y } => {
+                entry.event = "move".to_owned();
+                self.position = Some((x, y));
+            }
+            EventType::ButtonPress(button) => {
+                entry.event = "press".to_owned();
+                entry.button = button_name(button);
+            }
+            EventType::ButtonRelease(button) => {
+                entry.event = "release".to_owned();
+                entry.button = button_name(button);
+            }
+            EventType::Wheel { delta_x, delta_y } => {
+                entry.event = "wheel".to_owned();
+                entry.delta_x = Some(delta_x);
+                entry.delta_y = Some(delta_y);
+            }
+            EventType::KeyPress(key) => {
+                entry.event = "key_press".to_owned();
+                entry.key = key_name(key);
+            }
+            EventType::KeyRelease(key) => {
+                entry.event = "key_release".to_owned();
+                entry.key = key_name(key);
+            }
+        }
+        entry.x = self.position.map(|(x, _)| x);
+        entry.y = self.position.map(|(_, y)| y);
+
+        if self.entries.len() >= HISTORY_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        self.recorded_count += 1;
+    }
+
+    /// The recorded events as a sequence, each step lasting until the next one, e.g. to export it
+    /// A press followed by the release of the same button or key becomes a click or a key step,
+    /// the other presses & releases are left out
+    pub fn to_sequence(&self) -> Sequence {
+        let mut steps: Vec<(u64, Action)> = Vec::new();
+        let mut entries = self.entries.iter().peekable();
+        while let Some(entry) = entries.next() {
+            // Whether the next event releases what this one pressed
+            let mut released = |event: &str| {
+                entries
+                    .next_if(|next| {
+                        next.event == event && next.button == entry.button && next.key == entry.key
+                    })
+                    .is_some()
+            };
+            let action = match entry.event.as_str() {
+                "move" => entry.x.zip(entry.y).map(|(x, y)| Action::Move { x, y }),
+                "press" => parse_name(&entry.button)
+                    .filter(|_| released("release"))
+                    .map(|button| Action::Click {
+                        button,
+                        click_type: ClickType::Single,
+                        coord: None,
+                    }),
+                "key_press" => parse_name(&entry.key)
+                    .filter(|_| released("key_release"))
+                    .map(|key| Action::Key { key }),
+                "wheel" => Some(Action::Wheel {
+                    delta_x: entry.delta_x.unwrap_or_default(),
+                    delta_y: entry.delta_y.unwrap_or_default(),
+                }),
+                _ => None,
+            };
+            steps.extend(action.map(|action| (entry.timestamp_ms, action)));
+        }
+
+        let ends = steps
+            .iter()
+            .skip(1)
+            .map(|(timestamp_ms, _)| Some(*timestamp_ms))
+            .chain([None]);
+        Sequence {
+            steps: steps
+                .iter()
+                .zip(ends)
+                .map(|((start, action), end)| {
+                    Step::new(action.clone(), end.unwrap_or(*start).saturating_sub(*start))
+                })
+                .collect(),
+            repeat: 1,
+        }
+    }
+
+    /// Write the history to a file, overwriting it
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write
+    /// * `format` - The file format
+    pub fn export(&self, path: &Path, format: HistoryFormat) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        match format {
+            HistoryFormat::Csv => self.write_csv(&mut writer)?,
+            HistoryFormat::JsonLines => self.write_json_lines(&mut writer)?,
+        }
+        writer.flush()
+    }
+
+    /// Write the history as CSV with a header row, missing values are empty
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Where to write
+    pub fn write_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+        fn field<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(T::to_string).unwrap_or_default()
+        }
+
+        writeln!(
+            writer,
+            "timestamp_ms,event,button,key,x,y,delta_x,delta_y,mode"
+        )?;
+        for entry in &self.entries {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{:?}",
+                entry.timestamp_ms,
+                entry.event,
+                field(&entry.button),
+                field(&entry.key),
+                field(&entry.x),
+                field(&entry.y),
+                field(&entry.delta_x),
+                field(&entry.delta_y),
+                // Same names as in JSON
+                entry.mode
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the history as JSON Lines, one object per event
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Where to write
+    pub fn write_json_lines(&self, writer: &mut impl Write) -> io::Result<()> {
+        for entry in &self.entries {
+            serde_json::to_writer(&mut *writer, entry)?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse the name of a button or key, as recorded
+///
+/// # Arguments
+///
+/// * `name` - The name, e.g. `Left` or `KeyA`
+fn parse_name<T: DeserializeOwned>(name: &Option<String>) -> Option<T> {
+    let deserializer: StrDeserializer<'_, serde::de::value::Error> =
+        name.as_deref()?.into_deserializer();
+    T::deserialize(deserializer).ok()
+}
+
+/// Backend recording every event sent successfully by the wrapped backend
+pub struct RecordingBackend<'a> {
+    inner: &'a mut dyn InputBackend,
+    history: &'a mut ClickHistory,
+    mode: AppMode,
+}
+
+impl<'a> RecordingBackend<'a> {
+    /// Wrap a backend
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The backend sending the events
+    /// * `history` - Where to record them
+    /// * `mode` - The app mode the events are sent in
+    pub fn new(
+        inner: &'a mut dyn InputBackend,
+        history: &'a mut ClickHistory,
+        mode: AppMode,
+    ) -> Self {
+        Self {
+            inner,
+            history,
+            mode,
+        }
+    }
+
+    /// Record the event if it was sent
+    ///
+    /// # Arguments
+    ///
+    /// * `event_type` - The event
+    /// * `result` - Whether it was sent
+    fn record(
+        &mut self,
+        event_type: EventType,
+        result: Result<(), InputError>,
+    ) -> Result<(), InputError> {
+        if result.is_ok() {
+            // Events sent before any move are at the mouse position, if the backend knows it
+            if self.history.position.is_none()
+                && let Some((x, y)) = self.inner.mouse_position()
+            {
+                self.history.position = Some((f64::from(x), f64::from(y)));
+            }
+            self.history.record(&event_type, self.mode);
+        }
+        result
+    }
+}
+
+impl InputBackend for RecordingBackend<'_> {
+    fn mouse_position(&self) -> Option<(i32, i32)> {
+        self.inner.mouse_position()
+    }
+
+    fn monitors(&self) -> Option<Vec<Monitor>> {
+        self.inner.monitors()
+    }
+
+    fn windows(&self) -> Option<Vec<WindowInfo>> {
+        self.inner.windows()
+    }
+
+    fn active_window(&self) -> Option<u32> {
+        self.inner.active_window()
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.inner.pixel(x, y)
+    }
+
+    fn capture(&self, region: Region) -> Option<RgbImage> {
+        self.inner.capture(region)
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) -> Result<(), InputError> {
+        let result = self.inner.move_to(x, y);
+        self.record(EventType::MouseMove { x, y }, result)
+    }
+
+    fn press(&mut self, button: Button) -> Result<(), InputError> {
+        let result = self.inner.press(button);
+        self.record(EventType::ButtonPress(button), result)
+    }
+
+    fn release(&mut self, button: Button) -> Result<(), InputError> {
+        let result = self.inner.release(button);
+        self.record(EventType::ButtonRelease(button), result)
+    }
+
+    fn wheel(&mut self, delta_x: i64, delta_y: i64) -> Result<(), InputError> {
+        let result = self.inner.wheel(delta_x, delta_y);
+        self.record(EventType::Wheel { delta_x, delta_y }, result)
+    }
+
+    fn key_press(&mut self, key: Key) -> Result<(), InputError> {
+        let result = self.inner.key_press(key);
+        self.record(EventType::KeyPress(key), result)
+    }
+
+    fn key_release(&mut self, key: Key) -> Result<(), InputError> {
+        let result = self.inner.key_release(key);
+        self.record(EventType::KeyRelease(key), result)
+    }
+}
@@ -0,0 +1,946 @@
+//! Click engine driving the simulated input events
+//!
+//! Build a [`ClickJob`] and [`run`](ClickJob::run) it to autoclick from any program:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rdev::Button;
+//! use rusty_autoclicker::{
+//!     engine::{ClickJob, Target},
+//!     types::{AppMode, ClickButton},
+//! };
+//!
+//! let report = ClickJob::new()
+//!     .interval(Duration::from_millis(250))
+//!     .target(Target::Coord(300.0, 400.0))
+//!     .button(ClickButton::Mouse(Button::Left))
+//!     .mode(AppMode::Humanlike)
+//!     .amount(10)
+//!     .run()?;
+//! assert_eq!(report.click_counter, 10);
+//! # Ok::<(), rusty_autoclicker::engine::backend::InputError>(())
+//! ```
+//!
+//! Programs with their own event loop, like the GUI, call [`ClickJob::click`] when a click is due.
+//! The events go through an [`InputBackend`], see the [`backend`] module, and the waits through a
+//! [`Clock`]. With a [`VirtualClock`](clock::VirtualClock) and a [seed](ClickJob::seed), runs are
+//! deterministic and return immediately, see `tests/engine.rs`.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use rand::{Rng, RngCore, SeedableRng, rngs::StdRng};
+use rdev::{Button, EventType};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+use crate::types::{
+    AppMode, ClickButton, ClickInfo, ClickPosition, ClickType, CoordMode, StopReason,
+};
+
+pub mod backend;
+pub mod clock;
+pub mod defines;
+pub mod history;
+pub mod pixel;
+pub mod runner;
+pub mod screen;
+pub mod sequence;
+pub mod stats;
+pub mod template;
+pub mod window;
+pub mod xdotool;
+
+use backend::{BackendKind, InputBackend, InputError, NullBackend};
+use clock::{Clock, SystemClock};
+use defines::*;
+use pixel::{PixelCondition, PixelTrigger};
+use runner::JobRunner;
+use screen::{Monitor, Region};
+use template::{ImageTarget, MissingAction, Template};
+use window::{FocusAction, FocusGuard, WindowMatch};
+
+/// Where to click
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Target {
+    /// Wherever the mouse currently is
+    Mouse,
+    /// At the given screen coordinates
+    Coord(f64, f64),
+}
+
+impl From<(f64, f64)> for Target {
+    fn from((x, y): (f64, f64)) -> Self {
+        Target::Coord(x, y)
+    }
+}
+
+/// A labeled point of a target list, in the coordinate mode of the job
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TargetPoint {
+    pub label: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl TargetPoint {
+    pub fn new(label: impl Into<String>, x: f64, y: f64) -> Self {
+        Self {
+            label: label.into(),
+            x,
+            y,
+        }
+    }
+}
+
+/// Outcome of [`ClickJob::run`]
+#[derive(PartialEq, Clone, Debug)]
+pub struct JobReport {
+    /// The amount of clicks sent
+    pub click_counter: u64,
+    /// The amount of clicks that could not be sent
+    pub failure_counter: u64,
+    /// The amount of clicks dropped while the guarded window was not focused
+    pub skipped_counter: u64,
+    /// The error of the last failed click, if any
+    pub last_error: Option<ClickError>,
+    /// Why the job stopped
+    pub stop_reason: StopReason,
+    /// How long the job ran
+    pub elapsed: Duration,
+}
+
+/// What happened while running a job, see [`ClickJob::run_until`]
+#[derive(PartialEq, Clone, Debug)]
+pub enum JobEvent {
+    /// A click was sent, with the click counter
+    Clicked(u64),
+    /// The target window is missing or the guarded window is not focused, clicking resumes when
+    /// it is back
+    Paused(String),
+    /// A click was dropped because the guarded window is not focused
+    Skipped(String),
+    /// Clicking again after a pause or skipped clicks
+    Resumed,
+}
+
+/// How a click that was not sent affects the job
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum ClickErrorKind {
+    /// The click failed and counts as a failure
+    #[default]
+    Failed,
+    /// The target window is missing or the guarded window is not focused, the click should be
+    /// retried shortly
+    Paused,
+    /// The pixel the job waits for does not have its color yet, the click should be retried soon
+    Waiting,
+    /// The guarded window is not focused or the pixel does not have its color, the click is
+    /// dropped
+    Skipped,
+    /// The watched pixel changed or the target image is missing, the job should stop
+    Stopped(StopReason),
+}
+
+impl ClickErrorKind {
+    /// How long to wait before trying a held back click again, `None` for the next click
+    pub fn retry_interval(self) -> Option<Duration> {
+        match self {
+            ClickErrorKind::Paused => Some(WINDOW_RETRY_INTERVAL),
+            ClickErrorKind::Waiting => Some(PIXEL_POLL_INTERVAL),
+            _ => None,
+        }
+    }
+}
+
+/// An event of a click that the input backend could not send
+#[derive(PartialEq, Clone, Debug)]
+pub struct ClickError {
+    /// The event that failed, `None` if the click coordinates could not be resolved
+    pub event_type: Option<EventType>,
+    /// Why it failed
+    pub error: InputError,
+    /// Whether the click failed or was held back on purpose
+    pub kind: ClickErrorKind,
+}
+
+impl ClickError {
+    /// Error resolving the click coordinates
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Why they could not be resolved
+    pub fn target(error: InputError) -> Self {
+        Self {
+            event_type: None,
+            error,
+            kind: ClickErrorKind::Failed,
+        }
+    }
+}
+
+impl fmt::Display for ClickError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.event_type {
+            Some(event_type) => write!(f, "Could not send {event_type:?}: {}", self.error),
+            None => match self.kind {
+                ClickErrorKind::Failed => write!(f, "Could not click: {}", self.error),
+                ClickErrorKind::Paused => write!(f, "Paused: {}", self.error),
+                ClickErrorKind::Waiting => write!(f, "Waiting: {}", self.error),
+                ClickErrorKind::Skipped => write!(f, "Skipped: {}", self.error),
+                ClickErrorKind::Stopped(_) => write!(f, "Stopped: {}", self.error),
+            },
+        }
+    }
+}
+
+impl std::error::Error for ClickError {}
+
+/// A repeated click, configured with the builder methods
+///
+/// Defaults to a single left click at the mouse position every 100 ms, forever, in bot mode.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ClickJob {
+    pub app_mode: AppMode,
+    pub click_info: ClickInfo,
+    pub interval: Duration,
+    pub click_amount: u64,
+    pub movement_delay: Duration,
+    pub backend: BackendKind,
+    /// Seed of the random humanlike timings, `None` for a random seed
+    pub seed: Option<u64>,
+    /// Consecutive failed clicks after which the job stops, 0 = never
+    pub max_failures: u32,
+    /// The window the coordinates are relative to, with [`CoordMode::Window`]
+    pub window: Option<WindowMatch>,
+    /// The image the coordinates are relative to, with [`CoordMode::Image`]
+    pub image: Option<ImageTarget>,
+    /// Only click while this window is focused
+    pub focus_guard: Option<FocusGuard>,
+    /// Wait for, click while or stop when a pixel has a color
+    pub pixel_condition: Option<PixelCondition>,
+    /// Points clicked in turn instead of the click coordinates, in the coordinate mode
+    pub points: Vec<TargetPoint>,
+}
+
+impl Default for ClickJob {
+    fn default() -> Self {
+        Self {
+            app_mode: AppMode::Bot,
+            click_info: ClickInfo {
+                click_btn: ClickButton::Mouse(Button::Left),
+                click_coord: (0.0, 0.0),
+                click_position: ClickPosition::Mouse,
+                click_type: ClickType::Single,
+                coord_mode: CoordMode::Absolute,
+            },
+            interval: DEFAULT_INTERVAL,
+            click_amount: 0,
+            movement_delay: DEFAULT_MOVEMENT_DELAY,
+            backend: BackendKind::default(),
+            seed: None,
+            max_failures: DEFAULT_MAX_FAILURES,
+            window: None,
+            image: None,
+            focus_guard: None,
+            pixel_condition: None,
+            points: Vec::new(),
+        }
+    }
+}
+
+impl ClickJob {
+    /// Create a job with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time between the start of two clicks
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set where to click
+    pub fn target(mut self, target: impl Into<Target>) -> Self {
+        match target.into() {
+            Target::Mouse => self.click_info.click_position = ClickPosition::Mouse,
+            Target::Coord(x, y) => {
+                self.click_info.click_position = ClickPosition::Coord;
+                self.click_info.click_coord = (x, y);
+            }
+        }
+        self
+    }
+
+    /// Click the points in turn, starting over after the last one, instead of the coordinates
+    /// The points are expressed like the coordinates, see [`coord_mode`](Self::coord_mode)
+    pub fn points(mut self, points: Vec<TargetPoint>) -> Self {
+        self.click_info.click_position = ClickPosition::Coord;
+        self.points = points;
+        self
+    }
+
+    /// The job sending a given click, clicking the point of the target list whose turn it is
+    /// Jobs without points or clicking at the mouse send every click the same way
+    ///
+    /// # Arguments
+    ///
+    /// * `click_counter` - The clicks sent before this one
+    pub fn at_point(&self, click_counter: u64) -> Cow<'_, ClickJob> {
+        if self.points.is_empty() || self.click_info.click_position == ClickPosition::Mouse {
+            return Cow::Borrowed(self);
+        }
+        let point = &self.points[(click_counter % self.points.len() as u64) as usize];
+        let mut job = self.clone();
+        job.click_info.click_coord = (point.x, point.y);
+        Cow::Owned(job)
+    }
+
+    /// The job clicking relative to where its target image was already found, so that the screen
+    /// is not searched again
+    /// Jobs whose coordinates are not relative to an image send their clicks the same way
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The center of the image in pixels
+    pub fn at_image_center(&self, center: (f64, f64)) -> Cow<'_, ClickJob> {
+        if self.click_info.coord_mode != CoordMode::Image
+            || self.click_info.click_position == ClickPosition::Mouse
+        {
+            return Cow::Borrowed(self);
+        }
+        let mut job = self.clone();
+        job.click_info.coord_mode = CoordMode::Absolute;
+        job.click_info.click_coord.0 += center.0;
+        job.click_info.click_coord.1 += center.1;
+        job.image = None;
+        Cow::Owned(job)
+    }
+
+    /// Set how the target coordinates are expressed, e.g. in percent of a monitor
+    pub fn coord_mode(mut self, coord_mode: CoordMode) -> Self {
+        self.click_info.coord_mode = coord_mode;
+        self
+    }
+
+    /// Make the target coordinates relative to the top-left corner of a window
+    /// The window is looked up at each click, so the target follows it when it moves
+    pub fn window(mut self, window: WindowMatch) -> Self {
+        self.click_info.coord_mode = CoordMode::Window;
+        self.window = Some(window);
+        self
+    }
+
+    /// Make the target coordinates relative to the center of an image found on the screen
+    /// The image is searched before each click, so the target follows it when it moves
+    pub fn image(mut self, image: ImageTarget) -> Self {
+        self.click_info.coord_mode = CoordMode::Image;
+        self.image = Some(image);
+        self
+    }
+
+    /// Only click while the given window is focused, skipping or pausing otherwise
+    pub fn focus_guard(mut self, focus_guard: FocusGuard) -> Self {
+        self.focus_guard = Some(focus_guard);
+        self
+    }
+
+    /// Make the clicks depend on the color of a pixel of the screen
+    pub fn pixel_condition(mut self, pixel_condition: PixelCondition) -> Self {
+        self.pixel_condition = Some(pixel_condition);
+        self
+    }
+
+    /// Set the mouse button or keyboard key to press
+    pub fn button(mut self, click_btn: ClickButton) -> Self {
+        self.click_info.click_btn = click_btn;
+        self
+    }
+
+    /// Set single or double clicks
+    pub fn click_type(mut self, click_type: ClickType) -> Self {
+        self.click_info.click_type = click_type;
+        self
+    }
+
+    /// Set bot or humanlike clicking
+    pub fn mode(mut self, app_mode: AppMode) -> Self {
+        self.app_mode = app_mode;
+        self
+    }
+
+    /// Set the amount of clicks after which the job stops, 0 = forever
+    pub fn amount(mut self, click_amount: u64) -> Self {
+        self.click_amount = click_amount;
+        self
+    }
+
+    /// Set the delay between mouse movements in humanlike mode
+    pub fn movement_delay(mut self, movement_delay: Duration) -> Self {
+        self.movement_delay = movement_delay;
+        self
+    }
+
+    /// Set the input backend used by [`run`](Self::run) and [`run_until`](Self::run_until)
+    pub fn backend(mut self, backend: BackendKind) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Set the amount of consecutive failed clicks after which the job stops, 0 = never
+    pub fn max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures;
+        self
+    }
+
+    /// Set the seed of the random humanlike timings, `None` for a random seed
+    pub fn seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Create the random number generator of the job, seeded if a seed is set
+    pub fn new_rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        }
+    }
+
+    /// Check that the target coordinates or points fall on a monitor of the current display, that
+    /// the window patterns are valid and that the watched pixel can be captured
+    /// Always succeeds when clicking at the mouse position or in a window that is not open yet
+    pub fn check_target(&self) -> Result<(), InputError> {
+        let points = (0..self.points.len().max(1) as u64).map(|index| {
+            let job = self.at_point(index);
+            job.target_pixels(&NullBackend).map(|_| ()).map_err(|e| {
+                match self.points.get(index as usize) {
+                    Some(point) => ClickError {
+                        error: InputError::new(format!("{}: {}", point.label, e.error)),
+                        ..e
+                    },
+                    None => e,
+                }
+            })
+        });
+        for result in points.chain([
+            self.check_focus(&NullBackend),
+            self.check_pixel(&NullBackend),
+        ]) {
+            if let Err(e) = result
+                && e.kind == ClickErrorKind::Failed
+            {
+                return Err(e.error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the guarded window is focused, always succeeds without a focus guard
+    /// The focused window is read from the backend, or from the display if it does not know
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The input backend
+    pub fn check_focus(&self, backend: &dyn InputBackend) -> Result<(), ClickError> {
+        let Some(focus_guard) = &self.focus_guard else {
+            return Ok(());
+        };
+        let active_window = backend
+            .active_window()
+            .map_or_else(window::active_window, |id| Ok((id != 0).then_some(id)))
+            .map_err(ClickError::target)?;
+        let windows = backend
+            .windows()
+            .map_or_else(window::windows, Ok)
+            .map_err(ClickError::target)?;
+        let focused = active_window
+            .and_then(|id| windows.iter().find(|window| window.id == id))
+            .map(|window| focus_guard.window.matches(window))
+            .transpose()
+            .map_err(ClickError::target)?
+            .unwrap_or(false);
+        if focused {
+            return Ok(());
+        }
+
+        // Validate the pattern even when no window is focused
+        focus_guard
+            .window
+            .matches(&Default::default())
+            .map_err(ClickError::target)?;
+        Err(ClickError {
+            event_type: None,
+            error: InputError::new(format!("{} is not focused", focus_guard.window)),
+            kind: match focus_guard.action {
+                FocusAction::Skip => ClickErrorKind::Skipped,
+                FocusAction::Pause => ClickErrorKind::Paused,
+            },
+        })
+    }
+
+    /// Check the pixel condition, always succeeds without one
+    /// The pixel is read from the backend, or captured from the display if it does not know it
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The input backend
+    pub fn check_pixel(&self, backend: &dyn InputBackend) -> Result<(), ClickError> {
+        let Some(condition) = &self.pixel_condition else {
+            return Ok(());
+        };
+        let color = backend
+            .pixel(condition.x, condition.y)
+            .map_or_else(|| pixel::pixel_at(condition.x, condition.y), Ok)
+            .map_err(ClickError::target)?;
+        if condition.matches(&color) {
+            return Ok(());
+        }
+
+        Err(ClickError {
+            event_type: None,
+            error: InputError::new(format!("{condition} is {color}")),
+            kind: match condition.trigger {
+                PixelTrigger::WaitUntil => ClickErrorKind::Waiting,
+                PixelTrigger::While => ClickErrorKind::Skipped,
+                PixelTrigger::StopOnChange => ClickErrorKind::Stopped(StopReason::PixelChanged),
+            },
+        })
+    }
+
+    /// Convert the target coordinates to pixels of the screen, `None` when clicking at the mouse
+    /// The monitors, windows & screen captures come from the backend, or from the display if it
+    /// does not know them
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The input backend
+    pub fn target_pixels(
+        &self,
+        backend: &dyn InputBackend,
+    ) -> Result<Option<(f64, f64)>, ClickError> {
+        let click_info = self.click_info;
+        if click_info.click_position == ClickPosition::Mouse {
+            return Ok(None);
+        }
+
+        match click_info.coord_mode {
+            CoordMode::Window => {
+                let Some(window_match) = &self.window else {
+                    return Err(ClickError::target(InputError::new("No target window set")));
+                };
+                let windows = backend
+                    .windows()
+                    .map_or_else(window::windows, Ok)
+                    .map_err(ClickError::target)?;
+                match window_match.find(&windows).map_err(ClickError::target)? {
+                    Some(window) => window
+                        .to_pixels(click_info.click_coord)
+                        .map(Some)
+                        .map_err(ClickError::target),
+                    None => Err(ClickError {
+                        event_type: None,
+                        error: InputError::new(format!("{window_match} not found")),
+                        kind: ClickErrorKind::Paused,
+                    }),
+                }
+            }
+            CoordMode::Image => {
+                let Some(image_target) = &self.image else {
+                    return Err(ClickError::target(InputError::new("No target image set")));
+                };
+                self.find_image(image_target, backend).map(|center| {
+                    Some((
+                        center.0 + click_info.click_coord.0,
+                        center.1 + click_info.click_coord.1,
+                    ))
+                })
+            }
+            coord_mode => {
+                let monitors = backend
+                    .monitors()
+                    .map_or_else(screen::monitors, Ok)
+                    .map_err(ClickError::target)?;
+                screen::to_pixels(coord_mode, click_info.click_coord, &monitors)
+                    .map(Some)
+                    .map_err(ClickError::target)
+            }
+        }
+    }
+
+    /// Search the target image on the screen or in its region, e.g. in a background thread for
+    /// [`JobRunner::search_image_elsewhere`](runner::JobRunner::search_image_elsewhere)
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The input backend
+    pub fn image_center(&self, backend: &dyn InputBackend) -> Result<(f64, f64), ClickError> {
+        let Some(image_target) = &self.image else {
+            return Err(ClickError::target(InputError::new("No target image set")));
+        };
+        self.find_image(image_target, backend)
+    }
+
+    /// Find the center of the target image on the screen or in its region
+    ///
+    /// # Arguments
+    ///
+    /// * `image_target` - The image & where to search it
+    /// * `backend` - The input backend
+    fn find_image(
+        &self,
+        image_target: &ImageTarget,
+        backend: &dyn InputBackend,
+    ) -> Result<(f64, f64), ClickError> {
+        let template = Template::load(&image_target.path).map_err(ClickError::target)?;
+        let monitors = backend
+            .monitors()
+            .map_or_else(screen::monitors, Ok)
+            .map_err(ClickError::target)?;
+        let screen = Monitor::bounds(&monitors)
+            .map(Region::from)
+            .ok_or_else(|| ClickError::target(InputError::new("No monitor found")))?;
+        let region = match image_target.region {
+            Some(region) => region.intersection(&screen).ok_or_else(|| {
+                ClickError::target(InputError::new(format!("{region} is not on the screen")))
+            })?,
+            None => screen,
+        };
+        let capture = backend
+            .capture(region)
+            .map_or_else(|| pixel::capture(region), Ok)
+            .map_err(ClickError::target)?;
+
+        match template.find(&capture, (region.x, region.y)) {
+            Some(found) if found.score >= image_target.confidence => {
+                trace!(?found, "Found {image_target}");
+                Ok(found.center())
+            }
+            found => Err(ClickError {
+                event_type: None,
+                error: InputError::new(match found {
+                    Some(found) => format!(
+                        "{image_target} not found, best match {:.0}%",
+                        found.score.max(0.0) * 100.0
+                    ),
+                    None => format!("{image_target} not found, it is larger than {region}"),
+                }),
+                kind: match image_target.when_missing {
+                    MissingAction::Pause => ClickErrorKind::Paused,
+                    MissingAction::Stop => ClickErrorKind::Stopped(StopReason::ImageMissing),
+                },
+            }),
+        }
+    }
+
+    /// Send a single click of the job, stopping at the first event that fails
+    /// Nothing is sent while the guarded window is not focused or the pixel condition is not met
+    /// Relative coordinates are converted to pixels with the current monitors or window
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The input backend to send the events with
+    /// * `clock` - The clock to wait with
+    /// * `rng` - The random number generator, see [`new_rng`](Self::new_rng)
+    /// * `mouse_coord` - The current mouse coordinates, where humanlike movement starts
+    pub fn click(
+        &self,
+        backend: &mut dyn InputBackend,
+        clock: &mut dyn Clock,
+        rng: &mut dyn RngCore,
+        mouse_coord: (i32, i32),
+    ) -> Result<(), ClickError> {
+        self.check_focus(backend)?;
+        self.check_pixel(backend)?;
+
+        let mut click_info = self.click_info;
+        // Absolute coordinates are sent as they are, without reading the monitors at each click
+        if click_info.coord_mode != CoordMode::Absolute
+            && let Some(pixels) = self.target_pixels(backend)?
+        {
+            click_info.click_coord = pixels;
+            click_info.coord_mode = CoordMode::Absolute;
+        }
+
+        autoclick(
+            backend,
+            clock,
+            self.app_mode,
+            click_info,
+            mouse_coord,
+            self.movement_delay.as_millis() as u64,
+            rng,
+        )
+    }
+
+    /// Click until the click amount is reached, blocking the current thread
+    /// A job with a click amount of 0 never returns, see [`run_until`](Self::run_until)
+    pub fn run(&self) -> Result<JobReport, InputError> {
+        self.run_until(&AtomicBool::new(false), |_| ())
+    }
+
+    /// Click until the click amount is reached or `stop_requested` is set, blocking the current thread
+    /// Fails if the input backend cannot be created
+    ///
+    /// # Arguments
+    ///
+    /// * `stop_requested` - Set from another thread (e.g. a Ctrl-C handler) to stop the job
+    /// * `on_event` - Called after each click and when the job pauses or resumes
+    pub fn run_until(
+        &self,
+        stop_requested: &AtomicBool,
+        on_event: impl FnMut(JobEvent),
+    ) -> Result<JobReport, InputError> {
+        let mut backend = self.backend.create()?;
+        Ok(self.run_on(backend.as_mut(), &mut SystemClock, stop_requested, on_event))
+    }
+
+    /// Like [`run_until`](Self::run_until), with a given input backend and clock, e.g. a
+    /// [`MockBackend`](backend::MockBackend) and a [`VirtualClock`](clock::VirtualClock) in tests
+    /// The first click is sent immediately, failed clicks are not counted as clicks
+    /// While the target window is missing, the job pauses and looks for it again regularly, and
+    /// the same goes for the guarded window not being focused, unless its clicks are skipped
+    /// A job waiting for a pixel polls it until it has its color, then clicks without checking it
+    /// The points of a target list are clicked in turn, a click that is not sent retrying its point
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The input backend to send the events with
+    /// * `clock` - The clock to schedule the clicks with
+    /// * `stop_requested` - Set from another thread to stop the job
+    /// * `on_event` - Called after each click and when the job pauses or resumes
+    pub fn run_on(
+        &self,
+        backend: &mut dyn InputBackend,
+        clock: &mut dyn Clock,
+        stop_requested: &AtomicBool,
+        mut on_event: impl FnMut(JobEvent),
+    ) -> JobReport {
+        let mut runner = JobRunner::new(self.clone(), backend, clock.now());
+        loop {
+            if stop_requested.load(Ordering::SeqCst) {
+                return runner.report(StopReason::User, clock.now());
+            }
+
+            let now = clock.now();
+            let due = runner.due();
+            if now < due {
+                // Sleep in small steps to react to stop requests quickly
+                clock.sleep((due - now).min(POLL_INTERVAL));
+                continue;
+            }
+            if let Some(stop_reason) = runner.step(backend, clock, &mut on_event) {
+                return runner.report(stop_reason, clock.now());
+            }
+        }
+    }
+}
+
+/// Send the simulated event through the input backend
+///
+/// # Arguments
+///
+/// * `backend` - The input backend
+/// * `event_type` - The event type to simulate
+fn send(backend: &mut dyn InputBackend, event_type: &EventType) -> Result<(), ClickError> {
+    backend.send(event_type).map_err(|error| ClickError {
+        event_type: Some(*event_type),
+        error,
+        kind: ClickErrorKind::Failed,
+    })
+}
+
+/// Press then release a mouse button or a key
+/// The release is sent even if the press failed, not to leave it held, and the first error is
+/// reported
+///
+/// # Arguments
+///
+/// * `backend` - The input backend
+/// * `clock` - The clock to wait with
+/// * `click_btn` - The mouse button or key
+/// * `hold` - How long to hold it down, if at all
+fn press_and_release(
+    backend: &mut dyn InputBackend,
+    clock: &mut dyn Clock,
+    click_btn: ClickButton,
+    hold: Option<Duration>,
+) -> Result<(), ClickError> {
+    let (press, release) = match click_btn {
+        ClickButton::Mouse(button) => (
+            EventType::ButtonPress(button),
+            EventType::ButtonRelease(button),
+        ),
+        ClickButton::Key(key) => (EventType::KeyPress(key), EventType::KeyRelease(key)),
+    };
+    let pressed = send(backend, &press);
+    if pressed.is_ok()
+        && let Some(hold) = hold
+    {
+        clock.sleep(hold);
+    }
+    let released = send(backend, &release);
+    pressed.and(released)
+}
+
+/// Move the mouse to the specified coordinates
+/// Work if app is in "Humandlike" mode only
+///
+/// # Arguments
+///
+/// * `backend` - The input backend
+/// * `clock` - The clock to wait with
+/// * `app_mode` - The app mode
+/// * `click_position` - The click position type
+/// * `click_coord` - The click coordinates
+/// * `start_coords` - The starting mouse coordinates
+/// * `movement_delay_in_ms` - The delay between mouse movements in milliseconds
+fn move_to(
+    backend: &mut dyn InputBackend,
+    clock: &mut dyn Clock,
+    app_mode: AppMode,
+    click_position: ClickPosition,
+    click_coord: (f64, f64),
+    start_coords: (f64, f64),
+    movement_delay_in_ms: u64,
+) -> Result<(), ClickError> {
+    if app_mode == AppMode::Humanlike && click_position == ClickPosition::Coord {
+        // Move mouse slowly to saved coordinates if requested
+        let mut current_x = start_coords.0;
+        let mut current_y = start_coords.1;
+        loop {
+            // horizontal movement: determine whether we need to move left, right or not at all
+            let delta_x: f64 = if current_x < click_coord.0 {
+                MOUSE_STEP_POS_X.min(click_coord.0 - current_x)
+            } else if current_x > click_coord.0 {
+                MOUSE_STEP_NEG_X.max(click_coord.0 - current_x)
+            } else {
+                0.0
+            };
+
+            // vertical movement: determine whether we need to move up, down or not at all
+            let delta_y: f64 = if current_y < click_coord.1 {
+                MOUSE_STEP_POS_Y.min(click_coord.1 - current_y)
+            } else if current_y > click_coord.1 {
+                MOUSE_STEP_NEG_Y.max(click_coord.1 - current_y)
+            } else {
+                0.0
+            };
+
+            current_x += delta_x;
+            current_y += delta_y;
+
+            trace!(delta_x, delta_y, current_x, current_y, "Moving mouse");
+            send(
+                backend,
+                &EventType::MouseMove {
+                    x: current_x,
+                    y: current_y,
+                },
+            )?;
+
+            clock.sleep(Duration::from_millis(movement_delay_in_ms));
+            if current_x == click_coord.0 && current_y == click_coord.1 {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Autoclick the mouse, stopping at the first event that fails
+/// A button or key is released even if pressing it failed
+///
+/// # Arguments
+///
+/// * `backend` - The input backend
+/// * `clock` - The clock to wait with
+/// * `app_mode` - The app mode
+/// * `click_info` - The click information
+/// * `mouse_coord` - The mouse coordinates
+/// * `movement_delay_in_ms` - The delay between mouse movements in milliseconds
+/// * `rng` - The random number generator of the humanlike timings
+pub fn autoclick(
+    backend: &mut dyn InputBackend,
+    clock: &mut dyn Clock,
+    app_mode: AppMode,
+    click_info: ClickInfo,
+    mouse_coord: (i32, i32),
+    movement_delay_in_ms: u64,
+    rng: &mut dyn RngCore,
+) -> Result<(), ClickError> {
+    // Set the amount of runs/clicks required
+    let run_amount: u8 = if click_info.click_type == ClickType::Single {
+        1
+    } else if click_info.click_type == ClickType::Double {
+        2
+    } else {
+        0
+    };
+
+    // Autoclick as fast as possible
+    if app_mode == AppMode::Bot {
+        for _n in 1..=run_amount {
+            // Move mouse to saved coordinates if requested
+            if click_info.click_position == ClickPosition::Coord {
+                send(
+                    backend,
+                    &EventType::MouseMove {
+                        x: click_info.click_coord.0,
+                        y: click_info.click_coord.1,
+                    },
+                )?;
+            }
+            press_and_release(backend, clock, click_info.click_btn, None)?;
+        }
+    // Autoclick to emulate a humanlike clicks
+    } else if app_mode == AppMode::Humanlike {
+        let click_x = click_info.click_coord.0;
+        let click_y = click_info.click_coord.1;
+        // move to target
+        debug!(
+            from_x = mouse_coord.0,
+            from_y = mouse_coord.1,
+            click_x,
+            click_y,
+            "Moving towards click coordinates"
+        );
+
+        // perform clicks
+        for n in 1..=run_amount {
+            // Sleep between clicks
+            if n % 2 == 0 {
+                clock.sleep(Duration::from_millis(
+                    rng.random_range(DURATION_DOUBLE_CLICK_MIN..DURATION_DOUBLE_CLICK_MAX),
+                ));
+            }
+
+            // Move mouse to saved coordinates if requested
+            if click_info.click_position == ClickPosition::Coord {
+                // only move if start pos and click pos are not identical
+                if click_x != f64::from(mouse_coord.0) || click_y != f64::from(mouse_coord.1) {
+                    move_to(
+                        backend,
+                        clock,
+                        app_mode,
+                        click_info.click_position,
+                        (click_x, click_y),
+                        (f64::from(mouse_coord.0), f64::from(mouse_coord.1)),
+                        movement_delay_in_ms,
+                    )?;
+                }
+            }
+            let hold =
+                Duration::from_millis(rng.random_range(DURATION_CLICK_MIN..DURATION_CLICK_MAX));
+            press_and_release(backend, clock, click_info.click_btn, Some(hold))?;
+        }
+    }
+    Ok(())
+}
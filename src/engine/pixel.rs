@@ -0,0 +1,264 @@
+//! Screen pixels, to click depending on the color of a point of the screen
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use image::RgbImage;
+
+use super::{backend::InputError, screen::Region};
+
+/// An RGB color, written `#rrggbb`
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Whether no channel differs from the other color by more than the tolerance
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The color to compare with
+    /// * `tolerance` - The largest difference per channel, 0 for the exact color
+    pub fn matches(&self, other: &Color, tolerance: u8) -> bool {
+        self.r.abs_diff(other.r) <= tolerance
+            && self.g.abs_diff(other.g) <= tolerance
+            && self.b.abs_diff(other.b) <= tolerance
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let hex = string.strip_prefix('#').unwrap_or(string);
+        let invalid = || format!("invalid color \"{string}\", use #rrggbb");
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16);
+        match (channel(0), channel(2), channel(4)) {
+            (Ok(r), Ok(g), Ok(b)) => Ok(Self { r, g, b }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_string()
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(string: String) -> Result<Self, Self::Error> {
+        string.parse()
+    }
+}
+
+/// How a pixel condition affects the clicks
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PixelTrigger {
+    /// Start clicking once the pixel has the color, then stop checking it
+    #[default]
+    WaitUntil,
+    /// Only click while the pixel has the color, skipping the other clicks
+    While,
+    /// Stop the job as soon as the pixel no longer has the color
+    StopOnChange,
+}
+
+impl fmt::Display for PixelTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PixelTrigger::WaitUntil => write!(f, "Wait until"),
+            PixelTrigger::While => write!(f, "Only while"),
+            PixelTrigger::StopOnChange => write!(f, "Stop when changed"),
+        }
+    }
+}
+
+/// A color expected at a point of the screen
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PixelCondition {
+    /// The point in pixels of the screen
+    pub x: i32,
+    pub y: i32,
+    pub color: Color,
+    /// The largest difference per channel that still matches
+    pub tolerance: u8,
+    pub trigger: PixelTrigger,
+}
+
+impl PixelCondition {
+    /// Whether the captured color matches the expected one
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color captured at the point
+    pub fn matches(&self, color: &Color) -> bool {
+        self.color.matches(color, self.tolerance)
+    }
+}
+
+impl fmt::Display for PixelCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pixel {}/{} of color {}", self.x, self.y, self.color)?;
+        if self.tolerance > 0 {
+            write!(f, " ±{}", self.tolerance)?;
+        }
+        Ok(())
+    }
+}
+
+/// The color of a pixel of the screen
+///
+/// # Arguments
+///
+/// * `x` - The X coordinate in pixels
+/// * `y` - The Y coordinate in pixels
+pub fn pixel_at(x: i32, y: i32) -> Result<Color, InputError> {
+    let image = capture(Region {
+        x,
+        y,
+        width: 1,
+        height: 1,
+    })?;
+    let [r, g, b] = image.get_pixel(0, 0).0;
+    Ok(Color { r, g, b })
+}
+
+/// Capture a region of the screen
+///
+/// # Arguments
+///
+/// * `region` - The region in pixels, it must be on the screen
+pub fn capture(region: Region) -> Result<RgbImage, InputError> {
+    #[cfg(target_os = "linux")]
+    {
+        x11::capture(region)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = region;
+        Err(InputError::new(
+            "Capturing the screen is only available on X11",
+        ))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use image::RgbImage;
+    use x11rb::{
+        connection::Connection,
+        protocol::xproto::{ConnectionExt, ImageFormat, ImageOrder},
+    };
+
+    use crate::engine::{backend::InputError, screen::Region};
+
+    fn x11_error(e: impl std::fmt::Display) -> InputError {
+        InputError::new(format!("X11 error: {e}"))
+    }
+
+    /// Scale the bits of a channel mask to 0..=255
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The pixel value
+    /// * `mask` - The mask of the channel in the pixel value
+    fn channel(value: u32, mask: u32) -> u8 {
+        if mask == 0 {
+            return 0;
+        }
+        let max = mask >> mask.trailing_zeros();
+        let channel = (value & mask) >> mask.trailing_zeros();
+        (u64::from(channel) * 255 / u64::from(max)) as u8
+    }
+
+    /// Capture a region of the root window of the X server named by `$DISPLAY`
+    pub fn capture(region: Region) -> Result<RgbImage, InputError> {
+        let (connection, screen_num) = x11rb::connect(None)
+            .map_err(|e| InputError::new(format!("Could not connect to the X server: {e}")))?;
+        let setup = connection.setup();
+        let screen = &setup.roots[screen_num];
+        let root = Region {
+            x: 0,
+            y: 0,
+            width: u32::from(screen.width_in_pixels),
+            height: u32::from(screen.height_in_pixels),
+        };
+        if region.width == 0 || region.height == 0 || !root.contains_region(&region) {
+            return Err(InputError::new(format!("{region} is not on the screen")));
+        }
+
+        let image = connection
+            .get_image(
+                ImageFormat::Z_PIXMAP,
+                screen.root,
+                region.x as i16,
+                region.y as i16,
+                region.width as u16,
+                region.height as u16,
+                u32::MAX,
+            )
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        let format = setup
+            .pixmap_formats
+            .iter()
+            .find(|format| format.depth == image.depth)
+            .ok_or_else(|| InputError::new("Unknown pixel format of the screen capture"))?;
+        let visual = screen
+            .allowed_depths
+            .iter()
+            .flat_map(|depth| &depth.visuals)
+            .find(|visual| visual.visual_id == image.visual)
+            .ok_or_else(|| InputError::new("Unknown visual of the screen capture"))?;
+
+        // Rows are padded to the scanline pad of the format
+        let bytes_per_pixel = usize::from(format.bits_per_pixel).div_ceil(8);
+        let pad = usize::from(format.scanline_pad) / 8;
+        let stride = (region.width as usize * bytes_per_pixel).div_ceil(pad) * pad;
+        if image.data.len() < stride * region.height as usize {
+            return Err(InputError::new("Truncated screen capture"));
+        }
+        Ok(RgbImage::from_fn(region.width, region.height, |x, y| {
+            let start = y as usize * stride + x as usize * bytes_per_pixel;
+            let bytes = &image.data[start..start + bytes_per_pixel];
+            let value = match setup.image_byte_order {
+                ImageOrder::MSB_FIRST => bytes
+                    .iter()
+                    .fold(0u32, |value, byte| (value << 8) | u32::from(*byte)),
+                _ => bytes
+                    .iter()
+                    .rev()
+                    .fold(0u32, |value, byte| (value << 8) | u32::from(*byte)),
+            };
+            image::Rgb([
+                channel(value, visual.red_mask),
+                channel(value, visual.green_mask),
+                channel(value, visual.blue_mask),
+            ])
+        }))
+    }
+}
@@ -0,0 +1,311 @@
+//! Running jobs click by click, to run several jobs at once on the same backend
+
+use std::time::Instant;
+
+use device_query::{DeviceQuery, DeviceState};
+use rand::rngs::StdRng;
+use tracing::{info, warn};
+
+use super::{
+    ClickError, ClickErrorKind, ClickJob, JobEvent, JobReport, backend::InputBackend, clock::Clock,
+    defines::*, pixel::PixelTrigger,
+};
+use crate::types::{ClickPosition, CoordMode, StopReason};
+
+/// The state of a running job between its clicks
+/// Call [`step`](Self::step) whenever the job is [`due`](Self::due), until it returns a stop reason
+pub struct JobRunner {
+    job: ClickJob,
+    /// The job without its pixel condition, once the pixel it waited for was there
+    triggered_job: Option<ClickJob>,
+    rng: StdRng,
+    /// Reads the mouse when the backend does not know it
+    device_state: Option<DeviceState>,
+    start: Instant,
+    /// When the next click is due: one interval after the last one, or the retry interval after
+    /// a pause
+    next_due: Instant,
+    click_counter: u64,
+    failure_counter: u64,
+    skipped_counter: u64,
+    consecutive_failures: u32,
+    last_error: Option<ClickError>,
+    /// Why the job is paused or skipping its clicks, if it is
+    pause: Option<ClickError>,
+    /// Whether the target image is searched elsewhere, see
+    /// [`search_image_elsewhere`](Self::search_image_elsewhere)
+    searches_image_elsewhere: bool,
+    /// The center of the target image found elsewhere, used by the next click
+    image_center: Option<Result<(f64, f64), ClickError>>,
+}
+
+impl JobRunner {
+    /// Start running a job, its first click being due immediately
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job to run
+    /// * `backend` - The input backend the clicks will be sent with
+    /// * `now` - The current time of the clock
+    pub fn new(job: ClickJob, backend: &dyn InputBackend, now: Instant) -> Self {
+        // Only read the mouse from the OS if the backend does not know it
+        let device_state = match backend.mouse_position() {
+            Some(_) => None,
+            None => DeviceState::checked_new(),
+        };
+        Self {
+            rng: job.new_rng(),
+            job,
+            triggered_job: None,
+            device_state,
+            start: now,
+            next_due: now,
+            click_counter: 0,
+            failure_counter: 0,
+            skipped_counter: 0,
+            consecutive_failures: 0,
+            last_error: None,
+            pause: None,
+            searches_image_elsewhere: false,
+            image_center: None,
+        }
+    }
+
+    /// Click where the target image was found by a search run elsewhere, e.g. in a background
+    /// thread so that a GUI does not freeze, instead of searching the screen at each click
+    /// The clicks wait until the center is given with [`set_image_center`](Self::set_image_center),
+    /// each center being used by one click
+    pub fn search_image_elsewhere(mut self) -> Self {
+        self.searches_image_elsewhere = true;
+        self
+    }
+
+    /// Whether the next click waits for the center of the target image, see
+    /// [`search_image_elsewhere`](Self::search_image_elsewhere)
+    pub fn needs_image_center(&self) -> bool {
+        self.searches_image_elsewhere
+            && self.job.click_info.coord_mode == CoordMode::Image
+            && self.job.click_info.click_position == ClickPosition::Coord
+            && self.image_center.is_none()
+    }
+
+    /// Give the center of the target image for the next click, or why it was not found
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The center of the image in pixels, or the error of the search
+    pub fn set_image_center(&mut self, center: Result<(f64, f64), ClickError>) {
+        self.image_center = Some(center);
+    }
+
+    /// Delay the first click, e.g. to not click right away on the button that started the job
+    ///
+    /// # Arguments
+    ///
+    /// * `due` - When the first click is due
+    pub fn first_click_at(mut self, due: Instant) -> Self {
+        self.next_due = due;
+        self
+    }
+
+    /// The job being run
+    pub fn job(&self) -> &ClickJob {
+        &self.job
+    }
+
+    /// The amount of clicks sent
+    pub fn click_counter(&self) -> u64 {
+        self.click_counter
+    }
+
+    /// The amount of clicks that could not be sent
+    pub fn failure_counter(&self) -> u64 {
+        self.failure_counter
+    }
+
+    /// The error of the last failed click, if any
+    pub fn last_error(&self) -> Option<&ClickError> {
+        self.last_error.as_ref()
+    }
+
+    /// Whether the job is paused or skipping its clicks
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_some()
+    }
+
+    /// Why the job is paused or skipping its clicks, if it is
+    pub fn pause(&self) -> Option<&ClickError> {
+        self.pause.as_ref()
+    }
+
+    /// When the next click is due
+    pub fn due(&self) -> Instant {
+        self.next_due
+    }
+
+    /// Send the click that is due, returning why the job stopped if it is over
+    /// Failed clicks are not counted as clicks, and stop the job after too many in a row
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The input backend to send the events with
+    /// * `clock` - The clock to wait with
+    /// * `on_event` - Called after the click and when the job pauses or resumes
+    pub fn step(
+        &mut self,
+        backend: &mut dyn InputBackend,
+        clock: &mut dyn Clock,
+        on_event: &mut dyn FnMut(JobEvent),
+    ) -> Option<StopReason> {
+        if self.job.click_amount != 0 && self.click_counter >= self.job.click_amount {
+            return Some(StopReason::Completed);
+        }
+        let now = clock.now();
+        self.next_due = now + self.job.interval;
+
+        // Without a mouse state, humanlike movement starts at the target
+        let mouse_coord = backend
+            .mouse_position()
+            .or_else(|| {
+                self.device_state
+                    .as_ref()
+                    .map(|device_state| device_state.get_mouse().coords)
+            })
+            .unwrap_or((
+                self.job.click_info.click_coord.0 as i32,
+                self.job.click_info.click_coord.1 as i32,
+            ));
+        let job = self
+            .triggered_job
+            .as_ref()
+            .unwrap_or(&self.job)
+            .at_point(self.click_counter);
+        let result = if self.needs_image_center() {
+            // Look again shortly, without counting it as a pause
+            self.next_due = now + POLL_INTERVAL;
+            return None;
+        } else if let Some(center) = self.image_center.take() {
+            center.and_then(|center| {
+                job.at_image_center(center)
+                    .click(backend, clock, &mut self.rng, mouse_coord)
+            })
+        } else {
+            job.click(backend, clock, &mut self.rng, mouse_coord)
+        };
+        match result {
+            Ok(()) => {
+                // The pixel the job waited for is there, click without checking it anymore
+                if self.triggered_job.is_none()
+                    && let Some(condition) = self.job.pixel_condition
+                    && condition.trigger == PixelTrigger::WaitUntil
+                {
+                    info!("{condition} found, clicking");
+                    self.triggered_job = Some(ClickJob {
+                        pixel_condition: None,
+                        ..self.job.clone()
+                    });
+                }
+                if self.pause.take().is_some() {
+                    info!("Resuming");
+                    on_event(JobEvent::Resumed);
+                }
+                self.consecutive_failures = 0;
+                self.click_counter += 1;
+                on_event(JobEvent::Clicked(self.click_counter));
+                if self.job.click_amount != 0 && self.click_counter >= self.job.click_amount {
+                    return Some(StopReason::Completed);
+                }
+            }
+            Err(e) if e.kind == ClickErrorKind::Skipped => {
+                // Only log the first of a run of skipped clicks
+                if self.pause.is_none() {
+                    warn!("{e}");
+                }
+                self.skipped_counter += 1;
+                on_event(JobEvent::Skipped(e.error.to_string()));
+                self.pause = Some(e);
+            }
+            Err(
+                e @ ClickError {
+                    kind: ClickErrorKind::Stopped(stop_reason),
+                    ..
+                },
+            ) => {
+                info!("{e}");
+                return Some(stop_reason);
+            }
+            Err(e) if matches!(e.kind, ClickErrorKind::Paused | ClickErrorKind::Waiting) => {
+                if self.pause.is_none() {
+                    warn!("{e}");
+                    on_event(JobEvent::Paused(e.error.to_string()));
+                }
+                // Look again after the retry interval rather than the click interval
+                let retry_interval = e.kind.retry_interval().unwrap_or(WINDOW_RETRY_INTERVAL);
+                self.next_due = now + retry_interval.min(self.job.interval);
+                self.pause = Some(e);
+            }
+            Err(e) => {
+                warn!("{e}");
+                self.failure_counter += 1;
+                self.consecutive_failures += 1;
+                self.last_error = Some(e);
+                if self.job.max_failures != 0 && self.consecutive_failures >= self.job.max_failures
+                {
+                    return Some(StopReason::Failed);
+                }
+            }
+        }
+        None
+    }
+
+    /// The outcome of the job so far
+    ///
+    /// # Arguments
+    ///
+    /// * `stop_reason` - Why the job stopped
+    /// * `now` - The current time of the clock
+    pub fn report(&self, stop_reason: StopReason, now: Instant) -> JobReport {
+        JobReport {
+            click_counter: self.click_counter,
+            failure_counter: self.failure_counter,
+            skipped_counter: self.skipped_counter,
+            last_error: self.last_error.clone(),
+            stop_reason,
+            elapsed: now.saturating_duration_since(self.start),
+        }
+    }
+}
+
+/// Send the clicks of the runners that are due, earliest first, the first runner first when
+/// several are due at once. Each runner sends at most one click
+/// A click is always sent whole, a runner due during the click of another, e.g. during a
+/// humanlike movement, clicks right after it
+/// Returns the runners that stopped, with why
+///
+/// # Arguments
+///
+/// * `runners` - The running jobs, with their index
+/// * `backend` - The input backend to send the events with
+/// * `clock` - The clock to schedule the clicks with
+/// * `on_event` - Called with the index of the runner after each click and when a job pauses or
+///   resumes
+pub fn step_due_runners<'a>(
+    runners: impl IntoIterator<Item = (usize, &'a mut JobRunner)>,
+    backend: &mut dyn InputBackend,
+    clock: &mut dyn Clock,
+    mut on_event: impl FnMut(usize, JobEvent),
+) -> Vec<(usize, StopReason)> {
+    let now = clock.now();
+    let mut due: Vec<(usize, &mut JobRunner)> = runners
+        .into_iter()
+        .filter(|(_, runner)| runner.due() <= now)
+        .collect();
+    due.sort_by_key(|(index, runner)| (runner.due(), *index));
+
+    due.into_iter()
+        .filter_map(|(index, runner)| {
+            let stop_reason = runner.step(backend, clock, &mut |event| on_event(index, event))?;
+            Some((index, stop_reason))
+        })
+        .collect()
+}
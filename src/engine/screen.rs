@@ -0,0 +1,319 @@
+//! Display geometry, to convert resolution-independent coordinates to pixels at click time
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::backend::InputError;
+use crate::types::CoordMode;
+
+/// A monitor, in pixels of the virtual screen
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Monitor {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Monitor {
+    /// Whether the point is on the monitor
+    ///
+    /// # Arguments
+    ///
+    /// * `coord` - The point in pixels
+    pub fn contains(&self, (x, y): (f64, f64)) -> bool {
+        x >= f64::from(self.x)
+            && y >= f64::from(self.y)
+            && x < f64::from(self.x) + f64::from(self.width)
+            && y < f64::from(self.y) + f64::from(self.height)
+    }
+
+    /// Smallest rectangle containing all the monitors, i.e. the whole virtual screen
+    ///
+    /// # Arguments
+    ///
+    /// * `monitors` - The monitors
+    pub fn bounds(monitors: &[Monitor]) -> Option<Monitor> {
+        let left = monitors.iter().map(|monitor| monitor.x).min()?;
+        let top = monitors.iter().map(|monitor| monitor.y).min()?;
+        let right = monitors
+            .iter()
+            .map(|monitor| i64::from(monitor.x) + i64::from(monitor.width))
+            .max()?;
+        let bottom = monitors
+            .iter()
+            .map(|monitor| i64::from(monitor.y) + i64::from(monitor.height))
+            .max()?;
+        Some(Monitor {
+            x: left,
+            y: top,
+            width: (right - i64::from(left)) as u32,
+            height: (bottom - i64::from(top)) as u32,
+        })
+    }
+
+    /// Convert a percentage of the monitor to pixels
+    ///
+    /// # Arguments
+    ///
+    /// * `percent` - The X & Y percentages, from 0 to 100
+    fn at_percent(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        // 100% is the last pixel, not the first pixel past the monitor
+        let max_x = f64::from(self.width.saturating_sub(1));
+        let max_y = f64::from(self.height.saturating_sub(1));
+        (
+            (f64::from(self.x) + x / 100.0 * max_x).round(),
+            (f64::from(self.y) + y / 100.0 * max_y).round(),
+        )
+    }
+
+    /// Convert pixels to a percentage of the monitor
+    ///
+    /// # Arguments
+    ///
+    /// * `coord` - The point in pixels
+    fn percent_of(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let percent = |offset: f64, size: u32| {
+            if size > 1 {
+                offset / f64::from(size - 1) * 100.0
+            } else {
+                0.0
+            }
+        };
+        (
+            percent(x - f64::from(self.x), self.width),
+            percent(y - f64::from(self.y), self.height),
+        )
+    }
+}
+
+/// A rectangle of the virtual screen in pixels, e.g. to capture or search an image in
+/// Written like X11 geometries, `WIDTHxHEIGHT+X+Y`
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// Whether the other region is entirely inside this one
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other region
+    pub fn contains_region(&self, other: &Region) -> bool {
+        let right = |region: &Region| i64::from(region.x) + i64::from(region.width);
+        let bottom = |region: &Region| i64::from(region.y) + i64::from(region.height);
+        other.x >= self.x
+            && other.y >= self.y
+            && right(other) <= right(self)
+            && bottom(other) <= bottom(self)
+    }
+
+    /// The part of the region inside the other one, `None` if they do not overlap
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other region
+    pub fn intersection(&self, other: &Region) -> Option<Region> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (i64::from(self.x) + i64::from(self.width))
+            .min(i64::from(other.x) + i64::from(other.width));
+        let bottom = (i64::from(self.y) + i64::from(self.height))
+            .min(i64::from(other.y) + i64::from(other.height));
+        (right > i64::from(left) && bottom > i64::from(top)).then(|| Region {
+            x: left,
+            y: top,
+            width: (right - i64::from(left)) as u32,
+            height: (bottom - i64::from(top)) as u32,
+        })
+    }
+}
+
+impl From<Monitor> for Region {
+    fn from(monitor: Monitor) -> Self {
+        Self {
+            x: monitor.x,
+            y: monitor.y,
+            width: monitor.width,
+            height: monitor.height,
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}{:+}{:+}", self.width, self.height, self.x, self.y)
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid region \"{string}\", use WIDTHxHEIGHT+X+Y");
+        let (size, position) = string
+            .find(['+', '-'])
+            .map(|index| string.split_at(index))
+            .ok_or_else(invalid)?;
+        let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+        // The sign of Y starts the second number
+        let y_index = position[1..]
+            .find(['+', '-'])
+            .map(|index| index + 1)
+            .ok_or_else(invalid)?;
+        let (x, y) = position.split_at(y_index);
+        let region = Region {
+            x: x.parse().map_err(|_| invalid())?,
+            y: y.parse().map_err(|_| invalid())?,
+            width: width.parse().map_err(|_| invalid())?,
+            height: height.parse().map_err(|_| invalid())?,
+        };
+        if region.width == 0 || region.height == 0 {
+            return Err(invalid());
+        }
+        Ok(region)
+    }
+}
+
+/// The monitor the coordinates are relative to, `None` for absolute coordinates
+///
+/// # Arguments
+///
+/// * `coord_mode` - How the coordinates are expressed
+/// * `monitors` - The current monitors, the primary one first
+fn reference(coord_mode: CoordMode, monitors: &[Monitor]) -> Result<Option<Monitor>, InputError> {
+    match coord_mode {
+        CoordMode::Absolute => Ok(None),
+        CoordMode::ScreenPercent => Monitor::bounds(monitors)
+            .map(Some)
+            .ok_or_else(|| InputError::new("No monitor found")),
+        CoordMode::Window => Err(InputError::new(
+            "Window coordinates are converted with the window, see `WindowInfo::to_pixels`",
+        )),
+        CoordMode::Image => Err(InputError::new(
+            "Image coordinates are converted with the image, see `Template::find`",
+        )),
+        CoordMode::MonitorPercent(index) => {
+            monitors.get(index).copied().map(Some).ok_or_else(|| {
+                InputError::new(format!(
+                    "Monitor {index} does not exist, there are {} monitor(s)",
+                    monitors.len()
+                ))
+            })
+        }
+    }
+}
+
+/// Convert coordinates to pixels and check that they fall on a monitor
+///
+/// # Arguments
+///
+/// * `coord_mode` - How the coordinates are expressed
+/// * `coord` - The coordinates
+/// * `monitors` - The current monitors, the primary one first
+pub fn to_pixels(
+    coord_mode: CoordMode,
+    coord: (f64, f64),
+    monitors: &[Monitor],
+) -> Result<(f64, f64), InputError> {
+    let pixels = match reference(coord_mode, monitors)? {
+        Some(monitor) => monitor.at_percent(coord),
+        None => coord,
+    };
+
+    if monitors.iter().any(|monitor| monitor.contains(pixels)) {
+        Ok(pixels)
+    } else {
+        Err(InputError::new(format!(
+            "{}/{} is not on any screen",
+            pixels.0, pixels.1
+        )))
+    }
+}
+
+/// Convert pixels to coordinates of the given mode, e.g. when picking them with the mouse
+///
+/// # Arguments
+///
+/// * `coord_mode` - How the coordinates are expressed
+/// * `pixels` - The point in pixels
+/// * `monitors` - The current monitors, the primary one first
+pub fn from_pixels(
+    coord_mode: CoordMode,
+    pixels: (f64, f64),
+    monitors: &[Monitor],
+) -> Result<(f64, f64), InputError> {
+    Ok(match reference(coord_mode, monitors)? {
+        Some(monitor) => monitor.percent_of(pixels),
+        None => pixels,
+    })
+}
+
+/// The monitors of the current display, the primary one first
+pub fn monitors() -> Result<Vec<Monitor>, InputError> {
+    #[cfg(target_os = "linux")]
+    {
+        x11_monitors()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let (width, height) = rdev::display_size()
+            .map_err(|e| InputError::new(format!("Could not get the display size: {e:?}")))?;
+        Ok(vec![Monitor {
+            x: 0,
+            y: 0,
+            width: width as u32,
+            height: height as u32,
+        }])
+    }
+}
+
+/// The RandR monitors of the X server named by `$DISPLAY`, or its root window without RandR
+#[cfg(target_os = "linux")]
+fn x11_monitors() -> Result<Vec<Monitor>, InputError> {
+    use x11rb::{connection::Connection, protocol::randr::ConnectionExt};
+
+    let (connection, screen_num) = x11rb::connect(None)
+        .map_err(|e| InputError::new(format!("Could not connect to the X server: {e}")))?;
+    let screen = &connection.setup().roots[screen_num];
+
+    let randr_monitors = connection
+        .randr_get_monitors(screen.root, true)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok());
+    let mut monitors: Vec<(bool, Monitor)> = randr_monitors
+        .map(|reply| {
+            reply
+                .monitors
+                .iter()
+                .map(|info| {
+                    let monitor = Monitor {
+                        x: i32::from(info.x),
+                        y: i32::from(info.y),
+                        width: u32::from(info.width),
+                        height: u32::from(info.height),
+                    };
+                    (info.primary, monitor)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if monitors.is_empty() {
+        let monitor = Monitor {
+            x: 0,
+            y: 0,
+            width: u32::from(screen.width_in_pixels),
+            height: u32::from(screen.height_in_pixels),
+        };
+        monitors.push((true, monitor));
+    }
+
+    // Stable sort, keeping the RandR order of the other monitors
+    monitors.sort_by_key(|(primary, _)| !primary);
+    Ok(monitors.into_iter().map(|(_, monitor)| monitor).collect())
+}
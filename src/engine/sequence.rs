@@ -0,0 +1,648 @@
+//! Sequences of timed actions, played one after the other like a timeline
+//!
+//! Each [`Step`] starts its [duration](Step::duration_ms) after the previous one, or right after
+//! it if its action took longer. The actions are sent with the same primitives as the clicks of a
+//! [`ClickJob`], in its mode & with its movement delay.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use device_query::{DeviceQuery, DeviceState};
+use rand::rngs::StdRng;
+use rdev::{Button, EventType, Key};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::{
+    ClickError, ClickJob, autoclick,
+    backend::{InputBackend, InputError, NullBackend},
+    clock::Clock,
+    defines::*,
+    move_to, send,
+};
+use crate::types::{
+    AppMode, ClickButton, ClickInfo, ClickPosition, ClickType, CoordMode, StopReason,
+};
+
+/// What a step of a sequence does
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    /// Click a mouse button at the mouse position, or at screen coordinates
+    Click {
+        button: Button,
+        click_type: ClickType,
+        coord: Option<(f64, f64)>,
+    },
+    /// Press & release a keyboard key
+    Key { key: Key },
+    /// Move the mouse to screen coordinates
+    Move { x: f64, y: f64 },
+    /// Do nothing until the next step
+    Wait,
+    /// Type a text key by key, with a US keyboard layout
+    Text { text: String },
+    /// Scroll by the given amount of wheel steps, positive is right/up
+    Wheel { delta_x: i64, delta_y: i64 },
+}
+
+impl Action {
+    /// The kind of action, e.g. `Click`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Click { .. } => "Click",
+            Action::Key { .. } => "Key",
+            Action::Move { .. } => "Move",
+            Action::Wait => "Wait",
+            Action::Text { .. } => "Text",
+            Action::Wheel { .. } => "Wheel",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Click {
+                button,
+                click_type,
+                coord,
+            } => {
+                write!(f, "Click {button:?}")?;
+                if *click_type == ClickType::Double {
+                    write!(f, " ×2")?;
+                }
+                match coord {
+                    Some((x, y)) => write!(f, " at {x}, {y}"),
+                    None => Ok(()),
+                }
+            }
+            Action::Key { key } => write!(f, "Key {key:?}"),
+            Action::Move { x, y } => write!(f, "Move to {x}, {y}"),
+            Action::Wait => write!(f, "Wait"),
+            Action::Text { text } => write!(f, "Type {text:?}"),
+            Action::Wheel { delta_x, delta_y } => write!(f, "Wheel {delta_x}, {delta_y}"),
+        }
+    }
+}
+
+/// An action of a sequence & the time until the next one starts
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Step {
+    pub action: Action,
+    /// Time between the start of this step and the start of the next one, in milliseconds
+    pub duration_ms: u64,
+}
+
+impl Step {
+    pub fn new(action: Action, duration_ms: u64) -> Self {
+        Self {
+            action,
+            duration_ms,
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+/// Steps played in order, the whole sequence being repeated
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sequence {
+    pub steps: Vec<Step>,
+    /// Times the steps are played, 0 = forever
+    pub repeat: u64,
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self {
+            steps: Vec::new(),
+            repeat: 1,
+        }
+    }
+}
+
+impl Sequence {
+    /// The time from the start of the first step to the end of the last one, when no action
+    /// takes longer than its step
+    pub fn total_duration(&self) -> Duration {
+        self.start_of(self.steps.len())
+    }
+
+    /// The time from the start of the first step to the start of a step
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the step
+    pub fn start_of(&self, index: usize) -> Duration {
+        self.steps.iter().take(index).map(Step::duration).sum()
+    }
+
+    /// Check that the texts of the sequence can be typed
+    pub fn check(&self) -> Result<(), InputError> {
+        for (index, step) in self.steps.iter().enumerate() {
+            if let Action::Text { text } = &step.action {
+                text_keys(text).map_err(|e| InputError::new(format!("Step {}: {e}", index + 1)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Play the sequence until it is over or `stop_requested` is set, blocking the current thread
+    /// Stops at the first event that fails
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job whose mode, movement delay & seed the actions are sent with
+    /// * `backend` - The input backend to send the events with
+    /// * `clock` - The clock to schedule the steps with
+    /// * `stop_requested` - Set from another thread to stop the sequence
+    /// * `on_step` - Called with the index of each step before it is played
+    pub fn run_on(
+        &self,
+        job: &ClickJob,
+        backend: &mut dyn InputBackend,
+        clock: &mut dyn Clock,
+        stop_requested: &AtomicBool,
+        mut on_step: impl FnMut(usize),
+    ) -> Result<StopReason, ClickError> {
+        let mut player = SequencePlayer::new(self.clone(), job, backend, clock.now());
+        loop {
+            if stop_requested.load(Ordering::SeqCst) {
+                return Ok(StopReason::User);
+            }
+
+            let now = clock.now();
+            let due = player.due();
+            if now < due {
+                // Sleep in small steps to react to stop requests quickly
+                clock.sleep((due - now).min(POLL_INTERVAL));
+                continue;
+            }
+            if player.is_over() {
+                return Ok(StopReason::Completed);
+            }
+            on_step(player.next_step());
+            player.step(backend, clock)?;
+        }
+    }
+}
+
+impl ClickJob {
+    /// The clicks of the job as a sequence, each one lasting the click interval, e.g. to export it
+    /// Relative coordinates are converted to pixels with the current monitors. The focus guard,
+    /// the pixel condition & the humanlike timings are left out
+    /// Fails for coordinates relative to a window or an image, which move, and for more than
+    /// [`MAX_UNROLLED_CLICKS`] clicks that do not make whole rounds of the points
+    pub fn to_sequence(&self) -> Result<Sequence, InputError> {
+        if matches!(
+            self.click_info.coord_mode,
+            CoordMode::Window | CoordMode::Image
+        ) && self.click_info.click_position == ClickPosition::Coord
+        {
+            return Err(InputError::new(
+                "Coordinates relative to a window or an image cannot be exported",
+            ));
+        }
+
+        // The steps of each click, one per point of the target list
+        let mut clicks = Vec::new();
+        for index in 0..self.points.len().max(1) as u64 {
+            let job = self.at_point(index);
+            // Absolute coordinates are kept as they are, without reading the monitors
+            let coord = match job.click_info.coord_mode {
+                CoordMode::Absolute => (job.click_info.click_position == ClickPosition::Coord)
+                    .then_some(job.click_info.click_coord),
+                _ => job.target_pixels(&NullBackend).map_err(|e| e.error)?,
+            };
+            let interval_ms = self.interval.as_millis() as u64;
+            let steps = match job.click_info.click_btn {
+                ClickButton::Mouse(button) => vec![Step::new(
+                    Action::Click {
+                        button,
+                        click_type: job.click_info.click_type,
+                        coord,
+                    },
+                    interval_ms,
+                )],
+                ClickButton::Key(key) => {
+                    let presses = match job.click_info.click_type {
+                        ClickType::Single => 1,
+                        ClickType::Double => 2,
+                    };
+                    let mut steps = vec![Step::new(Action::Key { key }, 0); presses];
+                    steps[presses - 1].duration_ms = interval_ms;
+                    steps
+                }
+            };
+            clicks.push(steps);
+        }
+
+        // A click amount that is not a whole number of rounds is unrolled, if it is not too large
+        let rounds = clicks.len() as u64;
+        let (steps, repeat) = match self.click_amount {
+            0 => (clicks.concat(), 0),
+            amount if amount % rounds == 0 => (clicks.concat(), amount / rounds),
+            amount if amount > MAX_UNROLLED_CLICKS => {
+                return Err(InputError::new(format!(
+                    "{amount} clicks on {rounds} points do not make whole rounds and are too many \
+                     to export, use a multiple of {rounds} or at most {MAX_UNROLLED_CLICKS} clicks"
+                )));
+            }
+            amount => (
+                clicks
+                    .iter()
+                    .cycle()
+                    .take(amount as usize)
+                    .flatten()
+                    .cloned()
+                    .collect(),
+                1,
+            ),
+        };
+        Ok(Sequence { steps, repeat })
+    }
+}
+
+/// The state of a sequence being played, between its steps
+/// Call [`step`](Self::step) whenever the next step is [`due`](Self::due), until the sequence
+/// [`is_over`](Self::is_over)
+pub struct SequencePlayer {
+    sequence: Sequence,
+    app_mode: AppMode,
+    movement_delay: Duration,
+    rng: StdRng,
+    /// Reads the mouse when the backend does not know it
+    device_state: Option<DeviceState>,
+    /// Where the last action left the mouse, when neither the backend nor the OS know it
+    mouse_coord: (i32, i32),
+    /// The index of the step to play next
+    next_step: usize,
+    /// The step played last & when it started
+    current: Option<(usize, Instant)>,
+    due: Instant,
+    loop_counter: u64,
+}
+
+impl SequencePlayer {
+    /// Start playing a sequence, its first step being due immediately
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The sequence to play
+    /// * `job` - The job whose mode, movement delay & seed the actions are sent with
+    /// * `backend` - The input backend the actions will be sent with
+    /// * `now` - The current time of the clock
+    pub fn new(
+        sequence: Sequence,
+        job: &ClickJob,
+        backend: &dyn InputBackend,
+        now: Instant,
+    ) -> Self {
+        // Only read the mouse from the OS if the backend does not know it
+        let device_state = match backend.mouse_position() {
+            Some(_) => None,
+            None => DeviceState::checked_new(),
+        };
+        Self {
+            sequence,
+            app_mode: job.app_mode,
+            movement_delay: job.movement_delay,
+            rng: job.new_rng(),
+            device_state,
+            mouse_coord: (0, 0),
+            next_step: 0,
+            current: None,
+            due: now,
+            loop_counter: 0,
+        }
+    }
+
+    /// The sequence being played
+    pub fn sequence(&self) -> &Sequence {
+        &self.sequence
+    }
+
+    /// The index of the step to play next
+    pub fn next_step(&self) -> usize {
+        self.next_step
+    }
+
+    /// The index of the step played last, `None` before the first one
+    pub fn current_step(&self) -> Option<usize> {
+        self.current.map(|(index, _)| index)
+    }
+
+    /// The times the whole sequence was played
+    pub fn loop_counter(&self) -> u64 {
+        self.loop_counter
+    }
+
+    /// When the next step is due, one step duration after the start of the last one
+    pub fn due(&self) -> Instant {
+        self.due
+    }
+
+    /// Whether every step was played as many times as the sequence is repeated
+    pub fn is_over(&self) -> bool {
+        self.sequence.steps.is_empty()
+            || (self.sequence.repeat != 0 && self.loop_counter >= self.sequence.repeat)
+    }
+
+    /// The time from the start of the first step to the point being played, the playhead
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time of the clock
+    pub fn position(&self, now: Instant) -> Duration {
+        match self.current {
+            Some((index, start)) => {
+                self.sequence.start_of(index)
+                    + now
+                        .saturating_duration_since(start)
+                        .min(self.sequence.steps[index].duration())
+            }
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Play the step that is due, stopping at the first event that fails
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The input backend to send the events with
+    /// * `clock` - The clock to wait with
+    pub fn step(
+        &mut self,
+        backend: &mut dyn InputBackend,
+        clock: &mut dyn Clock,
+    ) -> Result<(), ClickError> {
+        if self.is_over() {
+            return Ok(());
+        }
+        let index = self.next_step;
+        let step = self.sequence.steps[index].clone();
+        let now = clock.now();
+        self.current = Some((index, now));
+        self.due = now + step.duration();
+        self.next_step = (index + 1) % self.sequence.steps.len();
+        if self.next_step == 0 {
+            self.loop_counter += 1;
+        }
+
+        debug!(index, "Playing {}", step.action);
+        self.play(&step.action, backend, clock)
+    }
+
+    /// Send the events of an action
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to send
+    /// * `backend` - The input backend to send the events with
+    /// * `clock` - The clock to wait with
+    fn play(
+        &mut self,
+        action: &Action,
+        backend: &mut dyn InputBackend,
+        clock: &mut dyn Clock,
+    ) -> Result<(), ClickError> {
+        let mouse_coord = backend
+            .mouse_position()
+            .or_else(|| {
+                self.device_state
+                    .as_ref()
+                    .map(|device_state| device_state.get_mouse().coords)
+            })
+            .unwrap_or(self.mouse_coord);
+
+        match action {
+            Action::Click {
+                button,
+                click_type,
+                coord,
+            } => {
+                self.press(
+                    backend,
+                    clock,
+                    ClickButton::Mouse(*button),
+                    *click_type,
+                    *coord,
+                    mouse_coord,
+                )?;
+                if let Some((x, y)) = coord {
+                    self.mouse_coord = (*x as i32, *y as i32);
+                }
+            }
+            Action::Key { key } => {
+                self.press(
+                    backend,
+                    clock,
+                    ClickButton::Key(*key),
+                    ClickType::Single,
+                    None,
+                    mouse_coord,
+                )?;
+            }
+            Action::Move { x, y } => {
+                match self.app_mode {
+                    AppMode::Humanlike => move_to(
+                        backend,
+                        clock,
+                        self.app_mode,
+                        ClickPosition::Coord,
+                        (*x, *y),
+                        (f64::from(mouse_coord.0), f64::from(mouse_coord.1)),
+                        self.movement_delay.as_millis() as u64,
+                    )?,
+                    AppMode::Bot => send(backend, &EventType::MouseMove { x: *x, y: *y })?,
+                }
+                self.mouse_coord = (*x as i32, *y as i32);
+            }
+            Action::Wait => {}
+            Action::Text { text } => {
+                for (key, shift) in text_keys(text).map_err(ClickError::target)? {
+                    if shift {
+                        send(backend, &EventType::KeyPress(Key::ShiftLeft))?;
+                    }
+                    let pressed = self.press(
+                        backend,
+                        clock,
+                        ClickButton::Key(key),
+                        ClickType::Single,
+                        None,
+                        mouse_coord,
+                    );
+                    // Never leave Shift held, even when the key could not be pressed
+                    let released = if shift {
+                        send(backend, &EventType::KeyRelease(Key::ShiftLeft))
+                    } else {
+                        Ok(())
+                    };
+                    pressed?;
+                    released?;
+                }
+            }
+            Action::Wheel { delta_x, delta_y } => send(
+                backend,
+                &EventType::Wheel {
+                    delta_x: *delta_x,
+                    delta_y: *delta_y,
+                },
+            )?,
+        }
+        Ok(())
+    }
+
+    /// Press & release a mouse button or a key, like a click of a job
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The input backend to send the events with
+    /// * `clock` - The clock to wait with
+    /// * `click_btn` - The mouse button or key
+    /// * `click_type` - Single or double click
+    /// * `coord` - Where to click, `None` for the mouse position
+    /// * `mouse_coord` - The current mouse coordinates, where humanlike movement starts
+    fn press(
+        &mut self,
+        backend: &mut dyn InputBackend,
+        clock: &mut dyn Clock,
+        click_btn: ClickButton,
+        click_type: ClickType,
+        coord: Option<(f64, f64)>,
+        mouse_coord: (i32, i32),
+    ) -> Result<(), ClickError> {
+        let click_info = ClickInfo {
+            click_btn,
+            click_coord: coord.unwrap_or_default(),
+            click_position: match coord {
+                Some(_) => ClickPosition::Coord,
+                None => ClickPosition::Mouse,
+            },
+            click_type,
+            coord_mode: CoordMode::Absolute,
+        };
+        autoclick(
+            backend,
+            clock,
+            self.app_mode,
+            click_info,
+            mouse_coord,
+            self.movement_delay.as_millis() as u64,
+            &mut self.rng,
+        )
+    }
+}
+
+/// The keys typing a text with a US keyboard layout, with whether shift is held
+///
+/// # Arguments
+///
+/// * `text` - The text to type
+pub fn text_keys(text: &str) -> Result<Vec<(Key, bool)>, InputError> {
+    text.chars()
+        .map(|c| char_key(c).ok_or_else(|| InputError::new(format!("Cannot type {c:?}"))))
+        .collect()
+}
+
+/// The key typing a character with a US keyboard layout, with whether shift is held
+///
+/// # Arguments
+///
+/// * `c` - The character to type
+fn char_key(c: char) -> Option<(Key, bool)> {
+    const LETTERS: [Key; 26] = [
+        Key::KeyA,
+        Key::KeyB,
+        Key::KeyC,
+        Key::KeyD,
+        Key::KeyE,
+        Key::KeyF,
+        Key::KeyG,
+        Key::KeyH,
+        Key::KeyI,
+        Key::KeyJ,
+        Key::KeyK,
+        Key::KeyL,
+        Key::KeyM,
+        Key::KeyN,
+        Key::KeyO,
+        Key::KeyP,
+        Key::KeyQ,
+        Key::KeyR,
+        Key::KeyS,
+        Key::KeyT,
+        Key::KeyU,
+        Key::KeyV,
+        Key::KeyW,
+        Key::KeyX,
+        Key::KeyY,
+        Key::KeyZ,
+    ];
+    const DIGITS: [Key; 10] = [
+        Key::Num0,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+    ];
+
+    if c.is_ascii_alphabetic() {
+        let index = (c.to_ascii_lowercase() as u8 - b'a') as usize;
+        return Some((LETTERS[index], c.is_ascii_uppercase()));
+    }
+    if c.is_ascii_digit() {
+        return Some((DIGITS[(c as u8 - b'0') as usize], false));
+    }
+    let key = match c {
+        ' ' => (Key::Space, false),
+        '\n' => (Key::Return, false),
+        '\t' => (Key::Tab, false),
+        '-' => (Key::Minus, false),
+        '=' => (Key::Equal, false),
+        '[' => (Key::LeftBracket, false),
+        ']' => (Key::RightBracket, false),
+        ';' => (Key::SemiColon, false),
+        '\'' => (Key::Quote, false),
+        '\\' => (Key::BackSlash, false),
+        ',' => (Key::Comma, false),
+        '.' => (Key::Dot, false),
+        '/' => (Key::Slash, false),
+        '`' => (Key::BackQuote, false),
+        '!' => (Key::Num1, true),
+        '@' => (Key::Num2, true),
+        '#' => (Key::Num3, true),
+        '$' => (Key::Num4, true),
+        '%' => (Key::Num5, true),
+        '^' => (Key::Num6, true),
+        '&' => (Key::Num7, true),
+        '*' => (Key::Num8, true),
+        '(' => (Key::Num9, true),
+        ')' => (Key::Num0, true),
+        '_' => (Key::Minus, true),
+        '+' => (Key::Equal, true),
+        '{' => (Key::LeftBracket, true),
+        '}' => (Key::RightBracket, true),
+        ':' => (Key::SemiColon, true),
+        '"' => (Key::Quote, true),
+        '|' => (Key::BackSlash, true),
+        '<' => (Key::Comma, true),
+        '>' => (Key::Dot, true),
+        '?' => (Key::Slash, true),
+        '~' => (Key::BackQuote, true),
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,149 @@
+//! Timing statistics of a running job, to check that the configured rate is actually reached
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use super::defines::STATS_WINDOW;
+
+/// Timestamps of the clicks of a session
+///
+/// Call [`start`](Self::start) when autoclicking starts, [`record`](Self::record) after each
+/// click and [`stop`](Self::stop) when it stops. Only the last `STATS_WINDOW` intervals are kept.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct ClickStats {
+    /// The interval the clicks should be sent at
+    pub target_interval: Duration,
+    start: Option<Instant>,
+    stop: Option<Instant>,
+    last_click: Option<Instant>,
+    click_counter: u64,
+    /// Recent time between two consecutive clicks, oldest first
+    intervals: VecDeque<Duration>,
+}
+
+/// Summary of [`ClickStats`], the deviations are absolute differences to the target interval
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct StatsSummary {
+    /// The amount of clicks sent
+    pub click_counter: u64,
+    /// Time since the session started
+    pub run_time: Duration,
+    /// Measured clicks per second over the recent intervals
+    pub clicks_per_second: f64,
+    pub mean_deviation: Duration,
+    pub p50_deviation: Duration,
+    pub p95_deviation: Duration,
+    pub p99_deviation: Duration,
+}
+
+impl ClickStats {
+    /// Clear the statistics and start a new session
+    ///
+    /// # Arguments
+    ///
+    /// * `target_interval` - The interval the clicks should be sent at
+    /// * `now` - When the session starts
+    pub fn start(&mut self, target_interval: Duration, now: Instant) {
+        *self = Self {
+            target_interval,
+            start: Some(now),
+            ..Self::default()
+        };
+    }
+
+    /// End the session, freezing the run time
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - When the session stops
+    pub fn stop(&mut self, now: Instant) {
+        if self.is_running() {
+            self.stop = Some(now);
+        }
+    }
+
+    /// Whether a session was started and not stopped yet
+    pub fn is_running(&self) -> bool {
+        self.start.is_some() && self.stop.is_none()
+    }
+
+    /// Record a click
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - When the click was sent
+    pub fn record(&mut self, now: Instant) {
+        if let Some(last_click) = self.last_click {
+            if self.intervals.len() >= STATS_WINDOW {
+                self.intervals.pop_front();
+            }
+            self.intervals
+                .push_back(now.saturating_duration_since(last_click));
+        }
+        self.start.get_or_insert(now);
+        self.last_click = Some(now);
+        self.click_counter += 1;
+    }
+
+    /// The recent time between two consecutive clicks, oldest first
+    pub fn intervals(&self) -> &VecDeque<Duration> {
+        &self.intervals
+    }
+
+    /// Compute the summary of the session
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, used for the run time of a running session
+    pub fn summary(&self, now: Instant) -> StatsSummary {
+        let run_time = match self.start {
+            Some(start) => self.stop.unwrap_or(now).saturating_duration_since(start),
+            None => Duration::ZERO,
+        };
+
+        let mut deviations: Vec<Duration> = self
+            .intervals
+            .iter()
+            .map(|interval| interval.abs_diff(self.target_interval))
+            .collect();
+        deviations.sort_unstable();
+
+        let total: Duration = self.intervals.iter().sum();
+        let clicks_per_second = if total.is_zero() {
+            0.0
+        } else {
+            self.intervals.len() as f64 / total.as_secs_f64()
+        };
+        let mean_deviation = if deviations.is_empty() {
+            Duration::ZERO
+        } else {
+            deviations.iter().sum::<Duration>() / deviations.len() as u32
+        };
+
+        StatsSummary {
+            click_counter: self.click_counter,
+            run_time,
+            clicks_per_second,
+            mean_deviation,
+            p50_deviation: percentile(&deviations, 50),
+            p95_deviation: percentile(&deviations, 95),
+            p99_deviation: percentile(&deviations, 99),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted durations, zero if there are none
+///
+/// # Arguments
+///
+/// * `sorted` - The durations, sorted in ascending order
+/// * `percent` - The percentile, from 0 to 100
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
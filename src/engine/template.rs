@@ -0,0 +1,398 @@
+//! Image targets, locating a reference image on the screen to click on it
+
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use image::{DynamicImage, GrayImage, RgbImage};
+use serde::{Deserialize, Serialize};
+
+use super::{backend::InputError, defines::*, screen::Region};
+
+/// What to do when the image is not on the screen
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingAction {
+    /// Look for the image again until it shows up
+    #[default]
+    Pause,
+    /// Stop the job
+    Stop,
+}
+
+/// A reference image to click on, the click coordinates being an offset from its center
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageTarget {
+    /// The PNG file of the image
+    pub path: PathBuf,
+    /// Where to search, `None` for the whole screen
+    pub region: Option<Region>,
+    /// The lowest match score, from 0 to 1, to consider the image found
+    pub confidence: f32,
+    pub when_missing: MissingAction,
+}
+
+impl Default for ImageTarget {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::new(),
+            region: None,
+            confidence: DEFAULT_IMAGE_CONFIDENCE,
+            when_missing: MissingAction::default(),
+        }
+    }
+}
+
+impl fmt::Display for ImageTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.path.file_name().unwrap_or(self.path.as_os_str());
+        write!(f, "image {}", name.to_string_lossy())
+    }
+}
+
+/// Where the image was found on the screen
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct TemplateMatch {
+    /// The top-left corner of the image on the screen
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// How well the image matches, from -1 to 1, 1 being identical
+    pub score: f32,
+}
+
+impl TemplateMatch {
+    /// The center of the image on the screen
+    pub fn center(&self) -> (f64, f64) {
+        (
+            f64::from(self.x) + f64::from(self.width / 2),
+            f64::from(self.y) + f64::from(self.height / 2),
+        )
+    }
+}
+
+/// A grayscale image stored as floats, to compute correlations
+#[derive(Clone, Debug)]
+struct Gray {
+    width: usize,
+    height: usize,
+    pixels: Vec<f32>,
+}
+
+impl Gray {
+    fn from_luma(image: &GrayImage) -> Self {
+        Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            pixels: image.pixels().map(|pixel| f32::from(pixel.0[0])).collect(),
+        }
+    }
+
+    /// The image without its first columns & rows
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - How many columns to drop
+    /// * `y` - How many rows to drop
+    fn shift(&self, x: usize, y: usize) -> Self {
+        let width = self.width.saturating_sub(x);
+        let height = self.height.saturating_sub(y);
+        Self {
+            width,
+            height,
+            pixels: (y..y + height)
+                .flat_map(|row| &self.pixels[row * self.width + x..][..width])
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Average blocks of `factor`² pixels, dropping the incomplete blocks of the edges
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - The size of the blocks
+    fn downscale(&self, factor: usize) -> Self {
+        let width = self.width / factor;
+        let height = self.height / factor;
+        let mut pixels = vec![0f32; width * height];
+        let area = (factor * factor) as f32;
+        for y in 0..height * factor {
+            for x in 0..width * factor {
+                pixels[(y / factor) * width + x / factor] += self.pixels[y * self.width + x] / area;
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// Sums & squared sums of the pixels above & left of each point, to get the mean & variance of
+/// any rectangle in constant time
+struct Integral {
+    stride: usize,
+    sums: Vec<f64>,
+    squares: Vec<f64>,
+}
+
+impl Integral {
+    fn new(image: &Gray) -> Self {
+        let stride = image.width + 1;
+        let mut sums = vec![0f64; stride * (image.height + 1)];
+        let mut squares = sums.clone();
+        for y in 0..image.height {
+            let (mut row_sum, mut row_square) = (0f64, 0f64);
+            for x in 0..image.width {
+                let value = f64::from(image.pixels[y * image.width + x]);
+                row_sum += value;
+                row_square += value * value;
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+                squares[(y + 1) * stride + x + 1] = squares[y * stride + x + 1] + row_square;
+            }
+        }
+        Self {
+            stride,
+            sums,
+            squares,
+        }
+    }
+
+    /// Sum & squared sum of the pixels of a rectangle
+    fn rect(&self, x: usize, y: usize, width: usize, height: usize) -> (f64, f64) {
+        let at = |values: &[f64], x: usize, y: usize| values[y * self.stride + x];
+        let sum = |values: &[f64]| {
+            at(values, x + width, y + height) - at(values, x, y + height) - at(values, x + width, y)
+                + at(values, x, y)
+        };
+        (sum(&self.sums), sum(&self.squares))
+    }
+}
+
+/// The pixels of a template minus their mean, and their norm
+#[derive(Clone, Debug)]
+struct Pattern {
+    width: usize,
+    height: usize,
+    pixels: Vec<f32>,
+    norm: f64,
+}
+
+impl Pattern {
+    fn new(image: &Gray) -> Option<Self> {
+        let count = image.pixels.len() as f32;
+        let mean = image.pixels.iter().sum::<f32>() / count;
+        let pixels: Vec<f32> = image.pixels.iter().map(|pixel| pixel - mean).collect();
+        let norm = pixels
+            .iter()
+            .map(|pixel| f64::from(*pixel) * f64::from(*pixel))
+            .sum::<f64>()
+            .sqrt();
+        // A flat image matches everywhere or nowhere
+        (norm > 1e-3).then_some(Self {
+            width: image.width,
+            height: image.height,
+            pixels,
+            norm,
+        })
+    }
+
+    /// Normalized cross-correlation of the pattern with the screen at a point
+    fn score(&self, screen: &Gray, integral: &Integral, x: usize, y: usize) -> f32 {
+        let (sum, square) = integral.rect(x, y, self.width, self.height);
+        let count = (self.width * self.height) as f64;
+        let variance = square - sum * sum / count;
+        if variance <= 1e-6 {
+            return 0.0;
+        }
+
+        let mut correlation = 0f64;
+        for row in 0..self.height {
+            let screen_row = &screen.pixels[(y + row) * screen.width + x..][..self.width];
+            let pattern_row = &self.pixels[row * self.width..][..self.width];
+            correlation += pattern_row
+                .iter()
+                .zip(screen_row)
+                .map(|(pattern, screen)| pattern * screen)
+                .sum::<f32>() as f64;
+        }
+        (correlation / (self.norm * variance.sqrt())) as f32
+    }
+
+    /// The best scoring points of the screen where the pattern fits, best first
+    /// Only the best points are kept while scoring, not the score of every point
+    ///
+    /// # Arguments
+    ///
+    /// * `screen` - The screen to search
+    /// * `count` - How many points to keep
+    fn best(&self, screen: &Gray, count: usize) -> Vec<(f32, usize, usize)> {
+        if self.width > screen.width || self.height > screen.height {
+            return Vec::new();
+        }
+        let integral = Integral::new(screen);
+        let mut best: Vec<(f32, usize, usize)> = Vec::with_capacity(count + 1);
+        for y in 0..=screen.height - self.height {
+            for x in 0..=screen.width - self.width {
+                let score = self.score(screen, &integral, x, y);
+                if best.len() == count && best.last().is_some_and(|last| score <= last.0) {
+                    continue;
+                }
+                let index = best.partition_point(|other| other.0 >= score);
+                best.insert(index, (score, x, y));
+                best.truncate(count);
+            }
+        }
+        best
+    }
+}
+
+/// The loaded templates by file, with the modification time of the file
+type TemplateCache = HashMap<PathBuf, (SystemTime, Arc<Template>)>;
+
+/// A downscaled pattern, with the columns & rows dropped before downscaling it
+type ShiftedPattern = ((usize, usize), Pattern);
+
+/// A reference image prepared for searching, see [`Template::find`]
+#[derive(Clone, Debug)]
+pub struct Template {
+    pattern: Pattern,
+    /// The pattern downscaled by `factor`, searched first on a downscaled screen
+    /// Once per shift of the blocks, the image rarely lying on the blocks of the screen
+    coarse: Option<(usize, Vec<ShiftedPattern>)>,
+}
+
+impl Template {
+    /// Prepare an image for searching
+    /// Fails if the image is empty or of a single color, it would match anywhere
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The reference image
+    pub fn from_image(image: &DynamicImage) -> Result<Self, InputError> {
+        let gray = Gray::from_luma(&image.to_luma8());
+        let pattern = Pattern::new(&gray)
+            .ok_or_else(|| InputError::new("The image is empty or of a single color"))?;
+
+        // Largest factor keeping enough detail in the downscaled image, small images being
+        // halved anyway not to be searched at full size over the whole screen
+        let coarse = [8, 4, 2]
+            .into_iter()
+            .filter(|factor| {
+                *factor == 2
+                    || (gray.width / factor >= TEMPLATE_COARSE_MIN_SIZE
+                        && gray.height / factor >= TEMPLATE_COARSE_MIN_SIZE)
+            })
+            .find_map(|factor| {
+                let shifts = [0, factor / 2];
+                let coarse: Vec<_> = shifts
+                    .iter()
+                    .flat_map(|y| shifts.iter().map(move |x| (*x, *y)))
+                    .filter_map(|(x, y)| {
+                        Pattern::new(&gray.shift(x, y).downscale(factor))
+                            .map(|coarse| ((x, y), coarse))
+                    })
+                    .collect();
+                (!coarse.is_empty()).then_some((factor, coarse))
+            });
+        Ok(Self { pattern, coarse })
+    }
+
+    /// Load a reference image from a file, PNG or any format of the `image` crate
+    /// The file is only read again when it changes
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The image file
+    pub fn load(path: &Path) -> Result<Arc<Self>, InputError> {
+        static CACHE: Mutex<Option<TemplateCache>> = Mutex::new(None);
+
+        let error = |e: &dyn fmt::Display| {
+            InputError::new(format!("Could not load the image {}: {e}", path.display()))
+        };
+        let modified = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| error(&e))?;
+        let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = cache.get_or_insert_with(HashMap::new);
+        if let Some((cached_modified, template)) = cache.get(path)
+            && *cached_modified == modified
+        {
+            return Ok(template.clone());
+        }
+
+        let image = image::open(path).map_err(|e| error(&e))?;
+        let template = Arc::new(Self::from_image(&image).map_err(|e| error(&e))?);
+        cache.insert(path.to_owned(), (modified, template.clone()));
+        Ok(template)
+    }
+
+    /// The size of the reference image
+    pub fn size(&self) -> (u32, u32) {
+        (self.pattern.width as u32, self.pattern.height as u32)
+    }
+
+    /// Find the best match of the image in a capture of the screen, `None` if it does not fit
+    /// The image is searched on a downscaled screen first, then refined around the best spots
+    ///
+    /// # Arguments
+    ///
+    /// * `capture` - The captured screen or region
+    /// * `origin` - The position of the capture on the screen
+    pub fn find(&self, capture: &RgbImage, origin: (i32, i32)) -> Option<TemplateMatch> {
+        let screen = Gray::from_luma(&DynamicImage::ImageRgb8(capture.clone()).to_luma8());
+        let pattern = &self.pattern;
+        if pattern.width > screen.width || pattern.height > screen.height {
+            return None;
+        }
+
+        let best = match &self.coarse {
+            // Only images too small or too plain to be downscaled
+            None => pattern.best(&screen, 1).into_iter().next(),
+            Some((factor, coarse)) => {
+                let downscaled = screen.downscale(*factor);
+                let candidates = coarse.iter().flat_map(|((shift_x, shift_y), coarse)| {
+                    coarse
+                        .best(&downscaled, TEMPLATE_CANDIDATES)
+                        .into_iter()
+                        .map(move |(_, x, y)| {
+                            (
+                                (x * factor).saturating_sub(*shift_x),
+                                (y * factor).saturating_sub(*shift_y),
+                            )
+                        })
+                });
+
+                // Search around each candidate at full resolution
+                let integral = Integral::new(&screen);
+                let max_x = screen.width - pattern.width;
+                let max_y = screen.height - pattern.height;
+                candidates
+                    .flat_map(|(x, y)| {
+                        let (x, y) = (x.min(max_x), y.min(max_y));
+                        let xs = x.saturating_sub(*factor)..=(x + factor).min(max_x);
+                        let ys = y.saturating_sub(*factor)..=(y + factor).min(max_y);
+                        ys.flat_map(move |y| xs.clone().map(move |x| (x, y)))
+                    })
+                    .map(|(x, y)| (pattern.score(&screen, &integral, x, y), x, y))
+                    .max_by(|a, b| a.0.total_cmp(&b.0))
+            }
+        };
+
+        best.map(|(score, x, y)| TemplateMatch {
+            x: origin.0 + x as i32,
+            y: origin.1 + y as i32,
+            width: pattern.width as u32,
+            height: pattern.height as u32,
+            score,
+        })
+    }
+}
@@ -0,0 +1,383 @@
+//! Top-level windows of the display, to click relative to a window wherever it moves
+
+use std::fmt;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::backend::InputError;
+
+/// A top-level window, its position in pixels of the virtual screen
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WindowInfo {
+    /// X11 window id
+    pub id: u32,
+    pub title: String,
+    /// First part of `WM_CLASS`, usually the program name
+    pub instance: String,
+    /// Second part of `WM_CLASS`, usually the capitalized program name
+    pub class: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowInfo {
+    /// Whether the point is in the window
+    ///
+    /// # Arguments
+    ///
+    /// * `coord` - The point in pixels of the screen
+    pub fn contains(&self, (x, y): (f64, f64)) -> bool {
+        x >= f64::from(self.x)
+            && y >= f64::from(self.y)
+            && x < f64::from(self.x) + f64::from(self.width)
+            && y < f64::from(self.y) + f64::from(self.height)
+    }
+
+    /// Convert coordinates relative to the window to pixels of the screen
+    /// Fails if they are outside of the window
+    ///
+    /// # Arguments
+    ///
+    /// * `coord` - The coordinates relative to the top-left corner of the window
+    pub fn to_pixels(&self, coord: (f64, f64)) -> Result<(f64, f64), InputError> {
+        let pixels = (f64::from(self.x) + coord.0, f64::from(self.y) + coord.1);
+        if self.contains(pixels) {
+            Ok(pixels)
+        } else {
+            Err(InputError::new(format!(
+                "{}/{} is outside of the window \"{}\" ({}x{})",
+                coord.0, coord.1, self.title, self.width, self.height
+            )))
+        }
+    }
+
+    /// Convert pixels of the screen to coordinates relative to the window
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels` - The point in pixels of the screen
+    pub fn from_pixels(&self, pixels: (f64, f64)) -> (f64, f64) {
+        (pixels.0 - f64::from(self.x), pixels.1 - f64::from(self.y))
+    }
+}
+
+/// How to find the target window
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMatch {
+    /// The window with the given X11 id, e.g. picked by clicking it
+    Id(u32),
+    /// The topmost window whose title matches the regex
+    Title(String),
+    /// The topmost window whose `WM_CLASS` instance or class matches the regex
+    Class(String),
+}
+
+/// No window picked yet
+impl Default for WindowMatch {
+    fn default() -> Self {
+        WindowMatch::Id(0)
+    }
+}
+
+impl fmt::Display for WindowMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowMatch::Id(id) => write!(f, "window {id:#x}"),
+            WindowMatch::Title(pattern) => write!(f, "window titled /{pattern}/"),
+            WindowMatch::Class(pattern) => write!(f, "window of class /{pattern}/"),
+        }
+    }
+}
+
+impl WindowMatch {
+    /// Compile the pattern of a title or class match
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The regex
+    fn regex(pattern: &str) -> Result<Regex, InputError> {
+        if pattern.is_empty() {
+            return Err(InputError::new("No window title or class to look for"));
+        }
+        Regex::new(pattern).map_err(|e| InputError::new(format!("Invalid window pattern: {e}")))
+    }
+
+    /// Whether the window matches
+    /// Fails if no window was picked, or if the pattern is empty or not a valid regex
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The window to test
+    pub fn matches(&self, window: &WindowInfo) -> Result<bool, InputError> {
+        Ok(self.find(std::slice::from_ref(window))?.is_some())
+    }
+
+    /// Find the matching window, the topmost one if several match
+    /// Fails if no window was picked, or if the pattern is empty or not a valid regex
+    ///
+    /// # Arguments
+    ///
+    /// * `windows` - The top-level windows, from bottom to top
+    pub fn find<'a>(
+        &self,
+        windows: &'a [WindowInfo],
+    ) -> Result<Option<&'a WindowInfo>, InputError> {
+        let mut windows = windows.iter().rev();
+        Ok(match self {
+            WindowMatch::Id(0) => return Err(InputError::new("No target window picked")),
+            WindowMatch::Id(id) => windows.find(|window| window.id == *id),
+            WindowMatch::Title(pattern) => {
+                let regex = Self::regex(pattern)?;
+                windows.find(|window| regex.is_match(&window.title))
+            }
+            WindowMatch::Class(pattern) => {
+                let regex = Self::regex(pattern)?;
+                windows.find(|window| {
+                    regex.is_match(&window.instance) || regex.is_match(&window.class)
+                })
+            }
+        })
+    }
+}
+
+/// What to do with a click while the guarded window is not focused
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusAction {
+    /// Drop the click, the next one is sent at the usual interval
+    #[default]
+    Skip,
+    /// Hold the click until the window is focused again
+    Pause,
+}
+
+/// Only click while the given window is focused, e.g. never into a chat or terminal by accident
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusGuard {
+    pub window: WindowMatch,
+    pub action: FocusAction,
+}
+
+/// The topmost window containing the point, e.g. to pick a window by clicking it
+///
+/// # Arguments
+///
+/// * `windows` - The top-level windows, from bottom to top
+/// * `pixels` - The point in pixels of the screen
+pub fn window_at(windows: &[WindowInfo], pixels: (f64, f64)) -> Option<&WindowInfo> {
+    windows.iter().rev().find(|window| window.contains(pixels))
+}
+
+/// The top-level windows of the display, from bottom to top
+pub fn windows() -> Result<Vec<WindowInfo>, InputError> {
+    #[cfg(target_os = "linux")]
+    {
+        x11::windows()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(InputError::new("Window targets are only available on X11"))
+    }
+}
+
+/// The id of the focused top-level window, `None` if no window is focused
+pub fn active_window() -> Result<Option<u32>, InputError> {
+    #[cfg(target_os = "linux")]
+    {
+        x11::active_window()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(InputError::new("The focus guard is only available on X11"))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use x11rb::{
+        connection::Connection,
+        protocol::xproto::{AtomEnum, ConnectionExt, InputFocus, MapState, Window},
+        rust_connection::RustConnection,
+    };
+
+    use super::WindowInfo;
+    use crate::engine::backend::InputError;
+
+    /// Longest title or class read, in 32-bit units
+    const PROPERTY_LENGTH: u32 = 256;
+
+    fn x11_error(e: impl std::fmt::Display) -> InputError {
+        InputError::new(format!("X11 error: {e}"))
+    }
+
+    /// Intern an atom by name
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - The X connection
+    /// * `name` - The atom name
+    fn atom(connection: &RustConnection, name: &str) -> Result<u32, InputError> {
+        Ok(connection
+            .intern_atom(false, name.as_bytes())
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?
+            .atom)
+    }
+
+    /// Read a property as bytes, empty if it is not set
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - The X connection
+    /// * `window` - The window to read the property of
+    /// * `property` - The property atom
+    fn property(connection: &RustConnection, window: Window, property: u32) -> Vec<u8> {
+        connection
+            .get_property(false, window, property, AtomEnum::ANY, 0, PROPERTY_LENGTH)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.value)
+            .unwrap_or_default()
+    }
+
+    /// The managed windows in stacking order, or the mapped children of the root window without
+    /// an EWMH window manager, e.g. under Xvfb
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - The X connection
+    /// * `root` - The root window
+    fn top_level_windows(
+        connection: &RustConnection,
+        root: Window,
+    ) -> Result<Vec<Window>, InputError> {
+        let client_list = atom(connection, "_NET_CLIENT_LIST_STACKING")?;
+        let managed: Vec<Window> = connection
+            .get_property(false, root, client_list, AtomEnum::WINDOW, 0, u32::MAX)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?
+            .value32()
+            .map(|windows| windows.collect())
+            .unwrap_or_default();
+        if !managed.is_empty() {
+            return Ok(managed);
+        }
+
+        // Children are returned from bottom to top
+        let children = connection
+            .query_tree(root)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?
+            .children;
+        Ok(children
+            .into_iter()
+            .filter(|&window| {
+                connection
+                    .get_window_attributes(window)
+                    .ok()
+                    .and_then(|cookie| cookie.reply().ok())
+                    .is_some_and(|attributes| {
+                        attributes.map_state == MapState::VIEWABLE && !attributes.override_redirect
+                    })
+            })
+            .collect())
+    }
+
+    /// The top-level windows of the X server named by `$DISPLAY`, from bottom to top
+    pub fn windows() -> Result<Vec<WindowInfo>, InputError> {
+        let (connection, screen_num) = x11rb::connect(None)
+            .map_err(|e| InputError::new(format!("Could not connect to the X server: {e}")))?;
+        let root = connection.setup().roots[screen_num].root;
+        let net_wm_name = atom(&connection, "_NET_WM_NAME")?;
+
+        let mut windows = Vec::new();
+        for window in top_level_windows(&connection, root)? {
+            // The window may have been destroyed in the meantime
+            let Some(geometry) = connection
+                .get_geometry(window)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+            else {
+                continue;
+            };
+            let Some(position) = connection
+                .translate_coordinates(window, root, 0, 0)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+            else {
+                continue;
+            };
+
+            let mut title = property(&connection, window, net_wm_name);
+            if title.is_empty() {
+                title = property(&connection, window, AtomEnum::WM_NAME.into());
+            }
+            let wm_class = property(&connection, window, AtomEnum::WM_CLASS.into());
+            let mut wm_class = wm_class
+                .split(|&byte| byte == 0)
+                .map(|part| String::from_utf8_lossy(part).into_owned());
+
+            windows.push(WindowInfo {
+                id: window,
+                title: String::from_utf8_lossy(&title).into_owned(),
+                instance: wm_class.next().unwrap_or_default(),
+                class: wm_class.next().unwrap_or_default(),
+                x: i32::from(position.dst_x),
+                y: i32::from(position.dst_y),
+                width: u32::from(geometry.width),
+                height: u32::from(geometry.height),
+            });
+        }
+        Ok(windows)
+    }
+
+    /// The window of the X server named by `$DISPLAY` that the window manager reports as active,
+    /// or the top-level window holding the input focus without an EWMH window manager
+    pub fn active_window() -> Result<Option<u32>, InputError> {
+        let (connection, screen_num) = x11rb::connect(None)
+            .map_err(|e| InputError::new(format!("Could not connect to the X server: {e}")))?;
+        let root = connection.setup().roots[screen_num].root;
+
+        let net_active_window = atom(&connection, "_NET_ACTIVE_WINDOW")?;
+        let active = connection
+            .get_property(false, root, net_active_window, AtomEnum::WINDOW, 0, 1)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        if let Some(window) = active.value32().and_then(|mut windows| windows.next()) {
+            return Ok((window != x11rb::NONE).then_some(window));
+        }
+
+        let mut window = connection
+            .get_input_focus()
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?
+            .focus;
+        if window == x11rb::NONE || window == u32::from(InputFocus::POINTER_ROOT) || window == root
+        {
+            return Ok(None);
+        }
+        // The focus may be on a child window, walk up to the child of the root window
+        loop {
+            let parent = connection
+                .query_tree(window)
+                .map_err(x11_error)?
+                .reply()
+                .map_err(x11_error)?
+                .parent;
+            if parent == root || parent == x11rb::NONE {
+                return Ok(Some(window));
+            }
+            window = parent;
+        }
+    }
+}
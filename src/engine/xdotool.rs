@@ -0,0 +1,645 @@
+//! Import xdotool shell scripts into sequences & export sequences as xdotool scripts
+//!
+//! The common subset of xdotool is understood: `mousemove`, `click`, `key`, `type` & `sleep`,
+//! with their `--repeat` & `--delay` options, chained on one line or not, and the shell `sleep`.
+//! A script may be wrapped in a `for _ in $(seq N); do … done` or `while true; do … done` loop,
+//! which becomes the repeat count of the sequence, as written by [`write_script`].
+
+use std::{
+    iter::{self, Peekable},
+    slice,
+    time::Duration,
+};
+
+use rdev::{Button, Key};
+
+use super::{
+    backend::InputError,
+    sequence::{Action, Sequence, Step},
+};
+use crate::types::ClickType;
+
+/// The xdotool commands, ending the arguments of `key` & `type` when chained
+const COMMANDS: [&str; 5] = ["mousemove", "click", "key", "type", "sleep"];
+
+/// Default time between repeated clicks of xdotool, in milliseconds
+const CLICK_DELAY_MS: u64 = 100;
+
+/// Default time between keystrokes of xdotool, in milliseconds
+const KEY_DELAY_MS: u64 = 12;
+
+/// Largest `--repeat` of a command
+const MAX_REPEAT: u64 = 100_000;
+
+/// Longest `--delay` of a command, an hour in milliseconds
+const MAX_DELAY_MS: u64 = 3_600_000;
+
+/// Most steps a script is imported to, repeated commands being unrolled
+const MAX_STEPS: u64 = 100_000;
+
+/// The X keysyms of the keys, the first one of a key being the one written
+const KEYSYMS: &[(Key, &str)] = &[
+    (Key::KeyA, "a"),
+    (Key::KeyB, "b"),
+    (Key::KeyC, "c"),
+    (Key::KeyD, "d"),
+    (Key::KeyE, "e"),
+    (Key::KeyF, "f"),
+    (Key::KeyG, "g"),
+    (Key::KeyH, "h"),
+    (Key::KeyI, "i"),
+    (Key::KeyJ, "j"),
+    (Key::KeyK, "k"),
+    (Key::KeyL, "l"),
+    (Key::KeyM, "m"),
+    (Key::KeyN, "n"),
+    (Key::KeyO, "o"),
+    (Key::KeyP, "p"),
+    (Key::KeyQ, "q"),
+    (Key::KeyR, "r"),
+    (Key::KeyS, "s"),
+    (Key::KeyT, "t"),
+    (Key::KeyU, "u"),
+    (Key::KeyV, "v"),
+    (Key::KeyW, "w"),
+    (Key::KeyX, "x"),
+    (Key::KeyY, "y"),
+    (Key::KeyZ, "z"),
+    (Key::Num0, "0"),
+    (Key::Num1, "1"),
+    (Key::Num2, "2"),
+    (Key::Num3, "3"),
+    (Key::Num4, "4"),
+    (Key::Num5, "5"),
+    (Key::Num6, "6"),
+    (Key::Num7, "7"),
+    (Key::Num8, "8"),
+    (Key::Num9, "9"),
+    (Key::Return, "Return"),
+    (Key::Return, "Enter"),
+    (Key::Tab, "Tab"),
+    (Key::Space, "space"),
+    (Key::Backspace, "BackSpace"),
+    (Key::Escape, "Escape"),
+    (Key::Delete, "Delete"),
+    (Key::Insert, "Insert"),
+    (Key::Home, "Home"),
+    (Key::End, "End"),
+    (Key::PageUp, "Page_Up"),
+    (Key::PageUp, "Prior"),
+    (Key::PageDown, "Page_Down"),
+    (Key::PageDown, "Next"),
+    (Key::UpArrow, "Up"),
+    (Key::DownArrow, "Down"),
+    (Key::LeftArrow, "Left"),
+    (Key::RightArrow, "Right"),
+    (Key::F1, "F1"),
+    (Key::F2, "F2"),
+    (Key::F3, "F3"),
+    (Key::F4, "F4"),
+    (Key::F5, "F5"),
+    (Key::F6, "F6"),
+    (Key::F7, "F7"),
+    (Key::F8, "F8"),
+    (Key::F9, "F9"),
+    (Key::F10, "F10"),
+    (Key::F11, "F11"),
+    (Key::F12, "F12"),
+    (Key::ShiftLeft, "Shift_L"),
+    (Key::ShiftLeft, "shift"),
+    (Key::ShiftRight, "Shift_R"),
+    (Key::ControlLeft, "Control_L"),
+    (Key::ControlLeft, "ctrl"),
+    (Key::ControlRight, "Control_R"),
+    (Key::Alt, "Alt_L"),
+    (Key::Alt, "alt"),
+    (Key::AltGr, "ISO_Level3_Shift"),
+    (Key::MetaLeft, "Super_L"),
+    (Key::MetaLeft, "super"),
+    (Key::MetaRight, "Super_R"),
+    (Key::CapsLock, "Caps_Lock"),
+    (Key::NumLock, "Num_Lock"),
+    (Key::ScrollLock, "Scroll_Lock"),
+    (Key::PrintScreen, "Print"),
+    (Key::Pause, "Pause"),
+    (Key::Minus, "minus"),
+    (Key::Equal, "equal"),
+    (Key::LeftBracket, "bracketleft"),
+    (Key::RightBracket, "bracketright"),
+    (Key::SemiColon, "semicolon"),
+    (Key::Quote, "apostrophe"),
+    (Key::BackSlash, "backslash"),
+    (Key::IntlBackslash, "less"),
+    (Key::BackQuote, "grave"),
+    (Key::Comma, "comma"),
+    (Key::Dot, "period"),
+    (Key::Slash, "slash"),
+    (Key::KpReturn, "KP_Enter"),
+    (Key::KpMinus, "KP_Subtract"),
+    (Key::KpPlus, "KP_Add"),
+    (Key::KpMultiply, "KP_Multiply"),
+    (Key::KpDivide, "KP_Divide"),
+    (Key::KpDelete, "KP_Delete"),
+    (Key::Kp0, "KP_0"),
+    (Key::Kp1, "KP_1"),
+    (Key::Kp2, "KP_2"),
+    (Key::Kp3, "KP_3"),
+    (Key::Kp4, "KP_4"),
+    (Key::Kp5, "KP_5"),
+    (Key::Kp6, "KP_6"),
+    (Key::Kp7, "KP_7"),
+    (Key::Kp8, "KP_8"),
+    (Key::Kp9, "KP_9"),
+];
+
+/// The X keysym of a key, `None` if it has none
+///
+/// # Arguments
+///
+/// * `key` - The key
+pub fn keysym(key: Key) -> Option<&'static str> {
+    KEYSYMS
+        .iter()
+        .find(|(other, _)| *other == key)
+        .map(|(_, keysym)| *keysym)
+}
+
+/// The key of an X keysym, `None` if it is unknown
+///
+/// # Arguments
+///
+/// * `keysym` - The keysym, e.g. `Return` or `a`
+pub fn parse_keysym(keysym: &str) -> Option<Key> {
+    KEYSYMS
+        .iter()
+        .find(|(_, other)| *other == keysym)
+        .map(|(key, _)| *key)
+}
+
+/// The xdotool number of a mouse button
+///
+/// # Arguments
+///
+/// * `button` - The button
+fn button_number(button: Button) -> u8 {
+    match button {
+        Button::Left => 1,
+        Button::Middle => 2,
+        Button::Right => 3,
+        Button::Unknown(number) => number,
+    }
+}
+
+/// Split a shell script into commands of words, following the shell quoting rules
+/// Commands end at new lines, `;` & `&&`, and comments start with `#` at the start of a word
+///
+/// # Arguments
+///
+/// * `script` - The script
+fn split_commands(script: &str) -> Result<Vec<Vec<String>>, InputError> {
+    let mut commands = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = script.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(InputError::new("Unterminated ' quote")),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.peek(), Some('"' | '\\' | '$' | '`')) => {
+                            word.extend(chars.next());
+                        }
+                        Some(c) => word.push(c),
+                        None => return Err(InputError::new("Unterminated \" quote")),
+                    }
+                }
+            }
+            // Line continuation or escaped character
+            '\\' => match chars.next() {
+                Some('\n') | None => (),
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+            },
+            '#' if word.is_none() => while chars.next_if(|c| *c != '\n').is_some() {},
+            c if c == '\n' || c == ';' || (c == '&' && chars.next_if_eq(&'&').is_some()) => {
+                words.extend(word.take());
+                if !words.is_empty() {
+                    commands.push(std::mem::take(&mut words));
+                }
+            }
+            c if c.is_whitespace() => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word.take());
+    if !words.is_empty() {
+        commands.push(words);
+    }
+    Ok(commands)
+}
+
+/// Parse a duration in seconds, e.g. `0.5`, with the `s`, `m` & `h` suffixes of the shell `sleep`
+///
+/// # Arguments
+///
+/// * `string` - The duration
+fn parse_seconds(string: &str) -> Result<Duration, InputError> {
+    let (number, unit) = match string.strip_suffix(['s', 'm', 'h']) {
+        Some(number) => (number, &string[number.len()..]),
+        None => (string, "s"),
+    };
+    let error = || InputError::new(format!("Invalid duration \"{string}\""));
+    let seconds = number.parse::<f64>().map_err(|_| error())?;
+    let factor = match unit {
+        "m" => 60f64,
+        "h" => 3600f64,
+        _ => 1f64,
+    };
+    // Negative, infinite or too long for a Duration
+    Duration::try_from_secs_f64(seconds * factor).map_err(|_| error())
+}
+
+/// Parse a non-negative integer option value
+///
+/// # Arguments
+///
+/// * `option` - The name of the option, for the error
+/// * `value` - The value, if any
+/// * `max` - The largest value allowed
+fn parse_count(option: &str, value: Option<&String>, max: u64) -> Result<u64, InputError> {
+    let count = value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| InputError::new(format!("{option} needs a number")))?;
+    if count > max {
+        return Err(InputError::new(format!("{option} must be at most {max}")));
+    }
+    Ok(count)
+}
+
+/// The arguments of `key` & `type`, every argument up to the next chained command
+///
+/// # Arguments
+///
+/// * `args` - The remaining arguments of the command line
+fn chained_args<'a>(args: &mut Peekable<slice::Iter<'a, String>>) -> Vec<&'a String> {
+    iter::from_fn(|| args.next_if(|arg| !COMMANDS.contains(&arg.as_str()))).collect()
+}
+
+/// The steps being imported, `sleep` & delays lengthening the last one
+#[derive(Default)]
+struct Importer {
+    steps: Vec<Step>,
+}
+
+impl Importer {
+    /// Add a step starting right after the previous one
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action of the step
+    fn push(&mut self, action: Action) {
+        self.steps.push(Step::new(action, 0));
+    }
+
+    /// Fail if adding steps would make the sequence too long
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - How many steps are about to be added
+    fn reserve(&self, count: u64) -> Result<(), InputError> {
+        if (self.steps.len() as u64).saturating_add(count) > MAX_STEPS {
+            return Err(InputError::new(format!(
+                "The script is longer than {MAX_STEPS} steps"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Wait before the next step, after the last step or in a wait step at the start
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - How long to wait
+    fn wait(&mut self, duration: Duration) {
+        let duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        match self.steps.last_mut() {
+            Some(step) => step.duration_ms = step.duration_ms.saturating_add(duration_ms),
+            None => self.steps.push(Step::new(Action::Wait, duration_ms)),
+        }
+    }
+
+    /// Add the actions of an `xdotool` command line, whose commands may be chained
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The arguments following `xdotool`
+    fn xdotool(&mut self, args: &[String]) -> Result<(), InputError> {
+        let mut args = args.iter().peekable();
+        while let Some(command) = args.next() {
+            // Options before the arguments, with their value if any
+            let mut repeat = 1u64;
+            let mut delay = None;
+            while let Some(option) = args.next_if(|arg| arg.starts_with("--")) {
+                match option.as_str() {
+                    "--repeat" => repeat = parse_count(option, args.next(), MAX_REPEAT)?,
+                    "--delay" => delay = Some(parse_count(option, args.next(), MAX_DELAY_MS)?),
+                    "--sync" | "--clearmodifiers" => (),
+                    option => {
+                        return Err(InputError::new(format!(
+                            "Unsupported option {option} of {command}"
+                        )));
+                    }
+                }
+            }
+            match command.as_str() {
+                "mousemove" => {
+                    let coord = |value: Option<&String>| {
+                        value
+                            .and_then(|value| value.parse::<f64>().ok())
+                            .ok_or_else(|| InputError::new("mousemove needs X & Y coordinates"))
+                    };
+                    let (x, y) = (coord(args.next())?, coord(args.next())?);
+                    self.push(Action::Move { x, y });
+                }
+                "click" => {
+                    let number = args
+                        .next()
+                        .and_then(|number| number.parse::<u8>().ok())
+                        .filter(|number| *number > 0)
+                        .ok_or_else(|| InputError::new("click needs a button number"))?;
+                    let delay = delay.unwrap_or(CLICK_DELAY_MS);
+                    // The wheel "buttons" 4 to 7 scroll up, down, left & right
+                    if let Some((delta_x, delta_y)) = match number {
+                        4 => Some((0, 1)),
+                        5 => Some((0, -1)),
+                        6 => Some((-1, 0)),
+                        7 => Some((1, 0)),
+                        _ => None,
+                    } {
+                        // Bounded by MAX_REPEAT
+                        let steps = repeat as i64;
+                        self.push(Action::Wheel {
+                            delta_x: delta_x * steps,
+                            delta_y: delta_y * steps,
+                        });
+                        self.wait(Duration::from_millis(
+                            delay.saturating_mul(repeat.saturating_sub(1)),
+                        ));
+                        continue;
+                    }
+                    self.reserve(repeat)?;
+                    let button = match number {
+                        1 => Button::Left,
+                        2 => Button::Middle,
+                        3 => Button::Right,
+                        number => Button::Unknown(number),
+                    };
+                    for index in 0..repeat {
+                        if index > 0 {
+                            self.wait(Duration::from_millis(delay));
+                        }
+                        self.push(Action::Click {
+                            button,
+                            click_type: ClickType::Single,
+                            coord: None,
+                        });
+                    }
+                }
+                "key" => {
+                    let keysyms = chained_args(&mut args);
+                    if keysyms.is_empty() {
+                        return Err(InputError::new("key needs a keysym"));
+                    }
+                    let delay = Duration::from_millis(delay.unwrap_or(KEY_DELAY_MS));
+                    let keys = keysyms
+                        .iter()
+                        .map(|keysym| match parse_keysym(keysym) {
+                            Some(key) => Ok(key),
+                            None if keysym.contains('+') => Err(InputError::new(format!(
+                                "Key combinations like {keysym} are not supported"
+                            ))),
+                            None => Err(InputError::new(format!("Unknown keysym {keysym}"))),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    self.reserve(repeat.saturating_mul(keys.len() as u64))?;
+                    for _ in 0..repeat {
+                        for key in &keys {
+                            self.push(Action::Key { key: *key });
+                            self.wait(delay);
+                        }
+                    }
+                }
+                "type" => {
+                    let text: String = chained_args(&mut args)
+                        .into_iter()
+                        .map(String::as_str)
+                        .collect();
+                    let delay = delay.unwrap_or(KEY_DELAY_MS);
+                    let duration =
+                        Duration::from_millis(delay.saturating_mul(text.chars().count() as u64));
+                    self.push(Action::Text { text });
+                    self.wait(duration);
+                }
+                "sleep" => {
+                    let seconds = args
+                        .next()
+                        .ok_or_else(|| InputError::new("sleep needs a duration"))?;
+                    self.wait(parse_seconds(seconds)?);
+                }
+                command => {
+                    return Err(InputError::new(format!(
+                        "Unsupported xdotool command {command}"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Import an xdotool script as a sequence
+/// Each step lasts until the next one, `sleep` & the delays of xdotool lengthening the last step
+///
+/// # Arguments
+///
+/// * `script` - The shell script
+pub fn parse_script(script: &str) -> Result<Sequence, InputError> {
+    let mut importer = Importer::default();
+    let mut repeat = None;
+    let mut loop_done = false;
+
+    for (index, words) in split_commands(script)?.iter().enumerate() {
+        let error = |e: InputError| InputError::new(format!("Command {}: {e}", index + 1));
+        let mut words: Vec<&str> = words.iter().map(String::as_str).collect();
+        // The body of the loop may start on the line of `do`
+        if words.first() == Some(&"do") && repeat.is_some() {
+            words.remove(0);
+            if words.is_empty() {
+                continue;
+            }
+        }
+        if loop_done {
+            return Err(error(InputError::new(
+                "Commands after the loop are not supported",
+            )));
+        }
+
+        match words.as_slice() {
+            ["for", _, "in", "$(seq", count] if repeat.is_none() && importer.steps.is_empty() => {
+                let count = count
+                    .strip_suffix(')')
+                    .and_then(|count| count.parse().ok())
+                    .ok_or_else(|| error(InputError::new("Invalid loop count")))?;
+                repeat = Some(count);
+            }
+            ["while", "true"] if repeat.is_none() && importer.steps.is_empty() => {
+                repeat = Some(0);
+            }
+            ["done"] if repeat.is_some() => loop_done = true,
+            ["for" | "while" | "until" | "done", ..] => {
+                return Err(error(InputError::new(
+                    "Only a loop around the whole script is supported",
+                )));
+            }
+            ["xdotool", args @ ..] => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                importer.xdotool(&args).map_err(error)?;
+            }
+            ["sleep", seconds] => importer.wait(parse_seconds(seconds).map_err(error)?),
+            [command, ..] => {
+                return Err(error(InputError::new(format!(
+                    "Unsupported command {command}"
+                ))));
+            }
+            [] => (),
+        }
+    }
+    if repeat.is_some() && !loop_done {
+        return Err(InputError::new("The loop is missing its done"));
+    }
+
+    Ok(Sequence {
+        steps: importer.steps,
+        repeat: repeat.unwrap_or(1),
+    })
+}
+
+/// Quote a word for the shell, if needed
+///
+/// # Arguments
+///
+/// * `word` - The word
+fn quote(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.,:/+=@%".contains(c))
+    {
+        return word.to_owned();
+    }
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// Format a duration in seconds for `sleep`, e.g. `0.25`
+///
+/// # Arguments
+///
+/// * `duration_ms` - The duration in milliseconds
+fn format_seconds(duration_ms: u64) -> String {
+    let seconds = format!("{}.{:03}", duration_ms / 1000, duration_ms % 1000);
+    seconds
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_owned()
+}
+
+/// Export a sequence as an xdotool shell script, repeated with a loop
+/// The delays of xdotool are disabled, the steps being timed with `sleep` only
+/// Fails if a key has no X keysym
+///
+/// # Arguments
+///
+/// * `sequence` - The sequence
+pub fn write_script(sequence: &Sequence) -> Result<String, InputError> {
+    let mut lines = Vec::new();
+    for (index, step) in sequence.steps.iter().enumerate() {
+        let command = match &step.action {
+            Action::Click {
+                button,
+                click_type,
+                coord,
+            } => {
+                let mut command = String::from("xdotool");
+                if let Some((x, y)) = coord {
+                    command += &format!(" mousemove {x} {y}");
+                }
+                command += " click";
+                if *click_type == ClickType::Double {
+                    command += " --repeat 2";
+                }
+                command += &format!(" {}", button_number(*button));
+                Some(command)
+            }
+            Action::Key { key } => {
+                let keysym = keysym(*key).ok_or_else(|| {
+                    InputError::new(format!("Step {}: {key:?} has no keysym", index + 1))
+                })?;
+                Some(format!("xdotool key --delay 0 {keysym}"))
+            }
+            Action::Move { x, y } => Some(format!("xdotool mousemove {x} {y}")),
+            Action::Wait => None,
+            Action::Text { text } => Some(format!("xdotool type --delay 0 {}", quote(text))),
+            Action::Wheel { delta_x, delta_y } => {
+                let mut command = String::from("xdotool");
+                for (delta, positive, negative) in [(delta_y, 4, 5), (delta_x, 7, 6)] {
+                    let number = match delta.signum() {
+                        1 => positive,
+                        -1 => negative,
+                        _ => continue,
+                    };
+                    match delta.unsigned_abs() {
+                        1 => command += &format!(" click {number}"),
+                        steps => command += &format!(" click --repeat {steps} --delay 0 {number}"),
+                    }
+                }
+                (command != "xdotool").then_some(command)
+            }
+        };
+        lines.extend(command);
+        if step.duration_ms > 0 {
+            lines.push(format!("sleep {}", format_seconds(step.duration_ms)));
+        }
+    }
+
+    let mut script = String::from("#!/bin/sh\n# Exported from Rusty AutoClicker\n");
+    let indent = match sequence.repeat {
+        1 => "",
+        0 => {
+            script += "while true; do\n";
+            "    "
+        }
+        count => {
+            script += &format!("for _ in $(seq {count}); do\n");
+            "    "
+        }
+    };
+    for line in lines {
+        script += &format!("{indent}{line}\n");
+    }
+    if sequence.repeat != 1 {
+        script += "done\n";
+    }
+    Ok(script)
+}
@@ -0,0 +1,179 @@
+//! Numbers typed in text inputs, validated against a range instead of being rewritten as typed
+
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
+
+/// A type of number that can be typed in a [`NumberField`]
+pub trait Number: Copy + Default + PartialOrd + FromStr + Display {
+    /// Shown when the text is not a number of this type
+    const PARSE_ERROR: &'static str;
+}
+
+impl Number for u64 {
+    const PARSE_ERROR: &'static str = "Not a whole number";
+}
+
+impl Number for i64 {
+    const PARSE_ERROR: &'static str = "Not a whole number";
+}
+
+impl Number for f64 {
+    const PARSE_ERROR: &'static str = "Not a number";
+}
+
+/// The text of a number input & the last valid number typed in it
+///
+/// The text is kept as typed, and the number only changes while the text is a number within
+/// the range. An empty text is the default number, e.g. 0.
+#[derive(PartialEq, Clone, Debug)]
+pub struct NumberField<T: Number> {
+    text: String,
+    value: T,
+    range: RangeInclusive<T>,
+    error: Option<String>,
+}
+
+impl<T: Number> NumberField<T> {
+    /// Create a field showing a number
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The number
+    /// * `range` - The numbers allowed
+    pub fn new(value: T, range: RangeInclusive<T>) -> Self {
+        let mut field = Self {
+            text: String::new(),
+            value,
+            range,
+            error: None,
+        };
+        field.set(value);
+        field
+    }
+
+    /// The last valid number
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// The text as typed
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Why the text is not a valid number, if it is not
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Show a number, flagged as invalid if it is out of range, e.g. from an edited profile
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The number
+    pub fn set(&mut self, value: T) {
+        self.set_text(value.to_string());
+    }
+
+    /// Replace the text, keeping the number if the text is not a valid one
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.validate();
+    }
+
+    /// Change the numbers allowed, validating the text again
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The numbers allowed
+    pub fn set_range(&mut self, range: RangeInclusive<T>) {
+        self.range = range;
+        self.validate();
+    }
+
+    /// Parse the text & check it is in the range
+    fn validate(&mut self) {
+        let text = self.text.trim();
+        let parsed = if text.is_empty() {
+            Ok(T::default())
+        } else {
+            text.parse::<T>().map_err(|_| T::PARSE_ERROR.to_owned())
+        };
+        self.error = match parsed {
+            Ok(value) if self.range.contains(&value) => {
+                self.value = value;
+                None
+            }
+            Ok(_) => Some(format!(
+                "Must be between {} and {}",
+                self.range.start(),
+                self.range.end()
+            )),
+            Err(e) => Some(e),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_text_is_the_default_number() {
+        let mut field = NumberField::new(5u64, 0..=10);
+
+        field.set_text("  ");
+
+        assert_eq!(field.value(), 0);
+        assert_eq!(field.error(), None);
+    }
+
+    #[test]
+    fn out_of_range_text_keeps_the_last_valid_number() {
+        let mut field = NumberField::new(5u64, 0..=10);
+
+        field.set_text("11");
+
+        assert_eq!(field.value(), 5);
+        assert_eq!(field.text(), "11");
+        assert_eq!(field.error(), Some("Must be between 0 and 10"));
+    }
+
+    #[test]
+    fn text_that_is_not_a_number_is_rejected() {
+        let mut field = NumberField::new(5u64, 0..=10);
+
+        field.set_text("1.5");
+
+        assert_eq!(field.value(), 5);
+        assert_eq!(field.error(), Some(u64::PARSE_ERROR));
+    }
+
+    #[test]
+    fn nan_and_infinity_are_out_of_range() {
+        let mut field = NumberField::new(1.5f64, 0.0..=10.0);
+
+        for text in ["NaN", "inf", "-inf"] {
+            field.set_text(text);
+
+            assert_eq!(field.value(), 1.5);
+            assert_eq!(field.error(), Some("Must be between 0 and 10"));
+        }
+    }
+
+    #[test]
+    fn changing_the_range_validates_the_text_again() {
+        let mut field = NumberField::new(50.0f64, 0.0..=1000.0);
+
+        field.set_range(0.0..=10.0);
+        assert_eq!(field.value(), 50.0);
+        assert_eq!(field.error(), Some("Must be between 0 and 10"));
+
+        field.set_range(0.0..=100.0);
+        assert_eq!(field.value(), 50.0);
+        assert_eq!(field.error(), None);
+    }
+}
@@ -1,15 +1,15 @@
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 use device_query::{DeviceQuery, DeviceState, Keycode};
 use eframe::egui;
+use rusty_autoclicker::engine::{backend::NullBackend, runner::JobRunner};
+use tracing::{error, trace};
 
-use crate::{
-    RustyAutoClickerApp,
-    types::ClickInfo,
-    utils::{autoclick, sanitize_i64_string, sanitize_string},
-};
+use crate::RustyAutoClickerApp;
 
+mod overlay;
 mod sections;
+mod timeline;
 mod windows;
 
 impl eframe::App for RustyAutoClickerApp {
@@ -17,23 +17,13 @@ impl eframe::App for RustyAutoClickerApp {
         egui::Rgba::TRANSPARENT.to_array() // Make sure we don't paint anything behind the rounded corners
     }
 
-    /// Called by the frame work to save state before shutdown.
-    /// Note that you must enable the `persistence` feature for this to work.
-    #[cfg(feature = "persistence")]
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
-    }
-
     /// Called each time the UI needs repainting, which may be many times per second.
     /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Print time to between start of old and new frames
-        #[cfg(debug_assertions)]
-        println!(
-            "Frame delta: {:?}",
-            Instant::now()
-                .checked_duration_since(self.frame_start)
-                .unwrap()
+        // Log time to between start of old and new frames
+        trace!(
+            frame_delta = ?Instant::now().saturating_duration_since(self.frame_start),
+            "Frame start"
         );
 
         self.frame_start = Instant::now();
@@ -43,37 +33,8 @@ impl eframe::App for RustyAutoClickerApp {
         let mouse = device_state.get_mouse();
         let keys = device_state.get_keys();
 
-        // Input sanitation
-        sanitize_string(&mut self.hr_str, 5usize);
-        sanitize_string(&mut self.min_str, 5usize);
-        sanitize_string(&mut self.sec_str, 5usize);
-        sanitize_string(&mut self.ms_str, 5usize);
-        sanitize_string(&mut self.click_amount_str, 5usize);
-        sanitize_i64_string(&mut self.click_x_str, 7usize);
-        sanitize_i64_string(&mut self.click_y_str, 7usize);
-        sanitize_string(&mut self.movement_sec_str, 5usize);
-        sanitize_string(&mut self.movement_ms_str, 5usize);
-
-        // Parse time Strings to u64
-        let hr: u64 = self.hr_str.parse().unwrap_or_default();
-        let min: u64 = self.min_str.parse().unwrap_or_default();
-        let sec: u64 = self.sec_str.parse().unwrap_or_default();
-        let ms: u64 = self.ms_str.parse().unwrap_or_default();
-        // println!("{} hr {} min {} sec {} ms", &hr, min, sec, ms);
-
-        // Parse movement Strings to u64
-        let movement_sec: u64 = self.movement_sec_str.parse().unwrap_or_default();
-        let movement_ms: u64 = self.movement_ms_str.parse().unwrap_or_default();
-
-        // Calculate movement delay
-        let movement_delay_in_ms = (movement_sec * 1000u64) + movement_ms;
-
-        // Parse click amount String to u64
-        let click_amount: u64 = self.click_amount_str.parse().unwrap_or_default();
-
-        // Parse mouse coordinates Strings to f64
-        let click_x: f64 = self.click_x_str.parse().unwrap_or_default();
-        let click_y: f64 = self.click_y_str.parse().unwrap_or_default();
+        // Click amount typed in the input, the last valid one while the input is invalid
+        let click_amount = self.click_amount.value();
 
         // Close hotkeys window if escape pressed & released
         if self.hotkey_window_open {
@@ -98,7 +59,11 @@ impl eframe::App for RustyAutoClickerApp {
         // }
 
         // Calculate click interval
-        let interval: u64 = (hr * 3600000) + (min * 60000) + (sec * 1000) + ms;
+        let interval = self.interval();
+
+        // Apply commands received on the control socket
+        #[cfg(unix)]
+        self.handle_ipc_commands();
 
         let update_now = Instant::now();
 
@@ -113,50 +78,60 @@ impl eframe::App for RustyAutoClickerApp {
                 && !self.is_setting_coord
                 && !self.is_setting_set_coord_key
                 && !self.hotkey_window_open
+                && !self.profile_window_open
             {
                 // Set only if app is not busy
                 // Start autoclick, first click is instantaneous
-                Self::start_autoclick(self, interval);
+                Self::start_autoclick(self, false);
+            }
+        }
+
+        // Start a new session when autoclicking starts: statistics, history & job runner
+        self.click_stats.target_interval = interval;
+        if self.is_autoclicking && !self.click_stats.is_running() {
+            // Do not start clicking with a number that is not the one shown
+            if let Some(e) = self.invalid_input() {
+                error!("Not autoclicking: {e}");
+                self.input_error = Some(e);
+                self.is_autoclicking = false;
+            }
+        }
+        if self.is_autoclicking && !self.click_stats.is_running() {
+            // Do not start clicking off screen, e.g. on a monitor that was unplugged
+            if let Err(e) = self.main_job().check_target() {
+                error!("Not autoclicking: {e}");
+                self.input_error = Some(e.to_string());
+                self.is_autoclicking = false;
+            }
+        }
+        if self.is_autoclicking && !self.click_stats.is_running() {
+            self.click_stats.start(interval, update_now);
+            self.click_counter = 0u64;
+            self.main_runner = Some(
+                // The target image is searched in the background, not to freeze the window
+                JobRunner::new(self.main_job(), &NullBackend, update_now)
+                    .first_click_at(self.first_click_due)
+                    .search_image_elsewhere(),
+            );
+            self.click_history.clear();
+            self.history_export_status = None;
+            // Place the target markers on the current monitors, windows & image
+            if self.show_targets {
+                self.refresh_targets();
             }
+        } else if !self.is_autoclicking && self.click_stats.is_running() {
+            self.click_stats.stop(update_now);
+            self.main_runner = None;
         }
 
         // Send click event
         if self.is_autoclicking
-            && update_now
-                .checked_duration_since(self.last_now)
-                .unwrap_or(Duration::ZERO)
-                .as_millis() as u64
-                >= interval
+            && self
+                .main_runner
+                .as_ref()
+                .is_some_and(|runner| runner.due() <= update_now)
         {
-            #[cfg(debug_assertions)]
-            println!(
-                "{:?} {:?} Click: {:?}",
-                self.click_type,
-                self.click_btn,
-                update_now
-                    .checked_duration_since(self.last_now)
-                    .unwrap_or(Duration::ZERO),
-            );
-            self.last_now = Instant::now();
-
-            autoclick(
-                self.app_mode,
-                ClickInfo {
-                    click_btn: self.click_btn,
-                    click_coord: (click_x, click_y),
-                    click_position: self.click_position,
-                    click_type: self.click_type,
-                },
-                mouse.coords,
-                movement_delay_in_ms,
-                self.rng_thread.clone(),
-            );
-
-            // Increment click counter and stop autoclicking if completed
-            self.click_counter += 1u64;
-            if click_amount != 0u64 && self.click_counter >= click_amount {
-                self.is_autoclicking = false;
-            }
+            self.step_main_job(mouse.coords);
         }
         // Set hotkey for autoclick
         else if self.is_setting_autoclick_key && self.keys_pressed.is_some() {
@@ -180,17 +155,41 @@ impl eframe::App for RustyAutoClickerApp {
         }
         // Set mouse coordinates
         else if self.is_setting_coord {
-            self.click_x_str = mouse.coords.0.to_string();
-            self.click_y_str = mouse.coords.1.to_string();
+            let point = self.update_picked_point(mouse.coords, &keys);
+            let pixels = (f64::from(point.0), f64::from(point.1));
+            let confirmed = self.picker_clicked(mouse.button_pressed[1])
+                || self
+                    .key_set_coord
+                    .is_some_and(|key| self.key_just_pressed(&keys, key));
+            if self.is_capturing_points {
+                // Add a point with each left click, finish with enter
+                if confirmed {
+                    self.capture_point(pixels);
+                }
+                if self.key_just_pressed(&keys, Keycode::Backspace) {
+                    self.captured_points.pop();
+                }
+                if self.key_just_pressed(&keys, Keycode::Enter) {
+                    Self::exit_coordinate_setting(self, ctx);
+                }
+            } else {
+                if self.is_picking_pixel {
+                    self.pick_pixel(point);
+                } else {
+                    self.set_click_coord(pixels);
+                }
 
-            // Stop if mouse left click
-            if mouse.button_pressed[1]
-                || (self.key_set_coord.is_some() && keys.contains(&self.key_set_coord.unwrap()))
-            {
-                Self::exit_coordinate_setting(self, ctx);
+                // Stop if mouse left click or enter
+                if confirmed || keys.contains(&Keycode::Enter) {
+                    Self::exit_coordinate_setting(self, ctx);
+                }
             }
         }
 
+        // Extra jobs, toggled by their own hotkeys, & the timeline
+        self.update_jobs(&keys);
+        self.update_sequence();
+
         // Save state of pressed keys
         self.keys_pressed = Some(keys.clone());
 
@@ -201,43 +200,20 @@ impl eframe::App for RustyAutoClickerApp {
             self.hotkey_window_open = true
         }
 
-        if self.is_setting_coord {
-            egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-                egui::MenuBar::new().ui(ui, |ui| {
-                    ui.horizontal_wrapped(|ui| {
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                            if self.is_autoclicking || self.hotkey_window_open {
-                                ui.disable();
-                            };
-                            ui.add(
-                                egui::TextEdit::singleline(&mut self.click_y_str)
-                                    .desired_width(50.0f32)
-                                    .hint_text("0"),
-                            );
-                            ui.label("Y");
-                            if self.is_autoclicking || self.hotkey_window_open {
-                                ui.disable();
-                            };
-                            ui.add(
-                                egui::TextEdit::singleline(&mut self.click_x_str)
-                                    .desired_width(50.0f32)
-                                    .hint_text("0"),
-                            );
-                            ui.label("X");
-                            ui.separator();
-                            ui.label(format!(
-                                " Set with \"{:}\" / \"L Click\"",
-                                self.key_set_coord.unwrap()
-                            ));
-                        });
-                    });
-                })
-            });
-            Self::follow_cursor(self, ctx);
+        // GUI, only the compact bar while autoclicking in compact mode
+        self.update_compact_mode(ctx);
+        if self.is_compact {
+            self.show_compact_bar(ctx);
         } else {
-            // GUI
             // Top panel with menu bar
             self.show_topbar(ctx);
+            self.show_error_banner(ctx);
+            self.show_pause_banner(ctx);
+
+            // Bottom panels, added before the central panel so that it does not overlap them
+            self.show_bottombar(ctx);
+            self.show_log_panel(ctx);
+            self.show_jobs_panel(ctx);
 
             egui::CentralPanel::default().show(ctx, |ui| {
                 // The central panel the region left after adding TopPanel's and SidePanel's
@@ -253,29 +229,74 @@ impl eframe::App for RustyAutoClickerApp {
                 ui.separator();
                 self.show_click_position(ui, ctx);
                 ui.separator();
+                self.show_coord_mode(ui);
+                ui.separator();
                 self.show_infos(ui, &mouse, &keys);
                 ui.separator();
                 self.show_autoclicker(ui);
             });
 
-            self.show_bottombar(ctx);
+            // Hotkeys window
+            if self.hotkey_window_open {
+                self.show_hotkeys_window(ctx);
+            }
+
+            // Profile name window
+            if self.profile_window_open {
+                self.show_profile_window(ctx);
+            }
+
+            // Options window
+            if self.options_window_open {
+                self.show_options_window(ctx);
+            }
+
+            // Statistics window
+            if self.stats_window_open {
+                self.show_stats_window(ctx);
+            }
+
+            // Timeline window
+            if self.timeline_window_open {
+                self.show_timeline_window(ctx);
+            }
         }
 
-        // Hotkeys window
-        if self.hotkey_window_open {
-            self.show_hotkeys_window(ctx);
+        // Overlay showing the clicks of a dry run
+        if self.dry_run {
+            self.show_dry_run_overlay(ctx);
+        }
+
+        // Overlay showing the targets & the clicks while autoclicking
+        if self.show_targets {
+            self.show_target_overlay(ctx);
+        }
+
+        // Overlay to pick the click coordinates or the pixel of the pixel condition
+        if self.is_setting_coord {
+            self.show_picker_overlay(ctx);
         }
 
+        // Stream clicks & start/stop changes to the control socket clients
+        #[cfg(unix)]
+        self.report_ipc_events();
+
+        // Pick the guarded window once the user had time to focus it
+        self.pick_focus_window_if_due();
+
+        // Place the target image & hand it to the main job once it was searched
+        self.poll_image_search();
+
+        // Save settings if changed
+        self.save_settings_if_changed();
+
         // Keep updating frame
         ctx.request_repaint();
 
-        // Print time to process frame
-        #[cfg(debug_assertions)]
-        println!(
-            "Frame time: {:?}",
-            Instant::now()
-                .checked_duration_since(self.frame_start)
-                .unwrap()
+        // Log time to process frame
+        trace!(
+            frame_time = ?Instant::now().saturating_duration_since(self.frame_start),
+            "Frame end"
         );
     }
 }
@@ -0,0 +1,304 @@
+use std::time::Instant;
+
+use eframe::egui::{self, Color32, Context, Pos2, Stroke};
+
+use rusty_autoclicker::types::CoordMode;
+
+use super::sections::show_color_swatch;
+use crate::{RustyAutoClickerApp, defines::*, utils::format_coord};
+
+/// Draw on a transparent window covering the monitor
+///
+/// # Arguments
+///
+/// * `ctx` - The ctx of the main window
+/// * `id` - Unique name of the overlay
+/// * `mouse_passthrough` - Whether clicks go through the overlay to the windows below
+/// * `add_contents` - Fills the overlay, given a function mapping screen pixels to overlay points
+pub fn show_overlay(
+    ctx: &Context,
+    id: &str,
+    mouse_passthrough: bool,
+    mut add_contents: impl FnMut(&mut egui::Ui, &dyn Fn(f64, f64) -> Pos2),
+) {
+    let monitor_size = ctx
+        .input(|i| i.viewport().monitor_size)
+        .unwrap_or(egui::vec2(WINDOW_WIDTH, WINDOW_HEIGHT));
+
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of(id),
+        egui::ViewportBuilder::default()
+            .with_title(id)
+            .with_position(egui::pos2(0f32, 0f32))
+            .with_inner_size(monitor_size)
+            .with_decorations(false)
+            .with_transparent(true)
+            .with_always_on_top()
+            .with_mouse_passthrough(mouse_passthrough)
+            .with_taskbar(false),
+        |ctx, _class| {
+            let pixels_per_point = ctx.pixels_per_point();
+            let to_pos = |x: f64, y: f64| {
+                egui::pos2(x as f32 / pixels_per_point, y as f32 / pixels_per_point)
+            };
+            egui::CentralPanel::default()
+                .frame(egui::Frame::NONE)
+                .show(ctx, |ui| add_contents(ui, &to_pos));
+        },
+    );
+}
+
+impl RustyAutoClickerApp {
+    pub fn show_dry_run_overlay(&mut self, ctx: &Context) {
+        // Forget the markers that faded out
+        let now = Instant::now();
+        self.dry_run_markers
+            .retain(|(_, time)| now.saturating_duration_since(*time) < DRY_RUN_MARKER_LIFETIME);
+        if !self.is_autoclicking && self.dry_run_markers.is_empty() {
+            self.dry_run_path.clear();
+            return;
+        }
+
+        let color = Color32::from_rgb(255, 140, 0);
+        show_overlay(ctx, "Dry run", true, |ui, to_pos| {
+            let painter = ui.painter();
+            // Planned humanlike movement
+            let path: Vec<Pos2> = self
+                .dry_run_path
+                .iter()
+                .map(|(x, y)| to_pos(*x, *y))
+                .collect();
+            painter.add(egui::Shape::line(path, Stroke::new(1.5f32, color)));
+
+            // Would-be clicks, fading out
+            for ((x, y), time) in &self.dry_run_markers {
+                let age = now.saturating_duration_since(*time).as_secs_f32()
+                    / DRY_RUN_MARKER_LIFETIME.as_secs_f32();
+                let faded = color.gamma_multiply(1f32 - age.min(1f32));
+                painter.circle_stroke(to_pos(*x, *y), 10f32, Stroke::new(2f32, faded));
+                painter.circle_filled(to_pos(*x, *y), 2f32, faded);
+            }
+        });
+    }
+}
+
+impl RustyAutoClickerApp {
+    /// Mark the targets while autoclicking at coordinates, flashing each click as it lands
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The ctx of the main window
+    pub fn show_target_overlay(&mut self, ctx: &Context) {
+        // Forget the flashes that faded out
+        let now = Instant::now();
+        self.click_flashes
+            .retain(|(_, time)| now.saturating_duration_since(*time) < CLICK_FLASH_LIFETIME);
+        let targets = if self.is_autoclicking {
+            self.target_markers()
+        } else {
+            Vec::new()
+        };
+        if targets.is_empty() && self.click_flashes.is_empty() {
+            return;
+        }
+
+        let color = Color32::from_rgb(0, 200, 120);
+        show_overlay(ctx, "Targets", true, |ui, to_pos| {
+            let painter = ui.painter();
+            for (label, (x, y)) in &targets {
+                let point = to_pos(*x, *y);
+                let stroke = Stroke::new(2f32, color);
+                painter.circle_stroke(point, 8f32, stroke);
+                painter.hline(point.x - 12f32..=point.x - 4f32, point.y, stroke);
+                painter.hline(point.x + 4f32..=point.x + 12f32, point.y, stroke);
+                painter.vline(point.x, point.y - 12f32..=point.y - 4f32, stroke);
+                painter.vline(point.x, point.y + 4f32..=point.y + 12f32, stroke);
+                painter.text(
+                    point + egui::vec2(12f32, -12f32),
+                    egui::Align2::LEFT_BOTTOM,
+                    label,
+                    egui::FontId::proportional(12f32),
+                    color,
+                );
+            }
+
+            // Clicks, growing & fading out
+            for ((x, y), time) in &self.click_flashes {
+                let age = now.saturating_duration_since(*time).as_secs_f32()
+                    / CLICK_FLASH_LIFETIME.as_secs_f32();
+                let faded = Color32::WHITE.gamma_multiply(1f32 - age.min(1f32));
+                painter.circle_stroke(to_pos(*x, *y), 6f32 + 14f32 * age, Stroke::new(3f32, faded));
+            }
+        });
+    }
+}
+
+impl RustyAutoClickerApp {
+    /// Fullscreen overlay to pick a point, with a crosshair & a magnifier of the pixels around it
+    /// showing its coordinates & color
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The ctx of the main window
+    pub fn show_picker_overlay(&self, ctx: &Context) {
+        let (x, y) = self.picked_point;
+        let coordinates = if self.is_picking_pixel || self.coord_mode == CoordMode::Absolute {
+            format!("{x}/{y}")
+        } else {
+            match self.pixels_to_coord(self.coord_mode, (f64::from(x), f64::from(y))) {
+                Ok((coord_x, coord_y)) => format!(
+                    "{x}/{y} → {}/{}",
+                    format_coord(coord_x, self.coord_mode),
+                    format_coord(coord_y, self.coord_mode)
+                ),
+                Err(_) => format!("{x}/{y}"),
+            }
+        };
+        let keys = match self.key_set_coord {
+            Some(key) if self.is_capturing_points => format!("\"{key}\" / \"L Click\""),
+            Some(key) => format!("\"{key}\" / \"Enter\" / \"L Click\""),
+            None if self.is_capturing_points => "\"L Click\"".to_owned(),
+            None => "\"Enter\" / \"L Click\"".to_owned(),
+        };
+        let hint = if self.is_capturing_points {
+            format!(
+                "{} points, add with {keys}, remove the last with \"Backspace\", finish with \"Enter\"",
+                self.captured_points.len()
+            )
+        } else {
+            format!("Set with {keys}, nudge with arrows")
+        };
+        let color = self.picked_color();
+        let captured: Vec<(&str, (f64, f64))> = self
+            .captured_points
+            .iter()
+            .filter_map(|point| {
+                let pixels = self.coord_to_pixels(self.coord_mode, (point.x, point.y));
+                pixels.ok().map(|pixels| (point.label.as_str(), pixels))
+            })
+            .collect();
+
+        show_overlay(ctx, "Pick coordinates", false, |ui, to_pos| {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+
+            // Crosshair, leaving the picked pixel visible
+            let screen = ui.max_rect();
+            let center = to_pos(f64::from(x) + 0.5f64, f64::from(y) + 0.5f64);
+            let gap = 4f32;
+            let stroke = Stroke::new(1f32, Color32::from_rgba_unmultiplied(255, 0, 0, 180));
+            let painter = ui.painter();
+            painter.hline(screen.left()..=center.x - gap, center.y, stroke);
+            painter.hline(center.x + gap..=screen.right(), center.y, stroke);
+            painter.vline(center.x, screen.top()..=center.y - gap, stroke);
+            painter.vline(center.x, center.y + gap..=screen.bottom(), stroke);
+
+            // Points captured so far
+            let marker_color = Color32::from_rgb(0, 160, 255);
+            for (label, (x, y)) in &captured {
+                let point = to_pos(*x + 0.5f64, *y + 0.5f64);
+                painter.circle_stroke(point, 6f32, Stroke::new(2f32, marker_color));
+                painter.text(
+                    point + egui::vec2(8f32, -8f32),
+                    egui::Align2::LEFT_BOTTOM,
+                    *label,
+                    egui::FontId::proportional(12f32),
+                    marker_color,
+                );
+            }
+
+            // Magnifier on the side of the point facing the middle of the screen
+            let right = center.x > screen.center().x;
+            let bottom = center.y > screen.center().y;
+            let pivot = egui::Align2([
+                if right {
+                    egui::Align::RIGHT
+                } else {
+                    egui::Align::LEFT
+                },
+                if bottom {
+                    egui::Align::BOTTOM
+                } else {
+                    egui::Align::TOP
+                },
+            ]);
+            let offset = egui::vec2(
+                if right { -24f32 } else { 24f32 },
+                if bottom { -24f32 } else { 24f32 },
+            );
+            egui::Area::new(egui::Id::new("magnifier"))
+                .order(egui::Order::Foreground)
+                .pivot(pivot)
+                .fixed_pos(center + offset)
+                .interactable(false)
+                .show(ui.ctx(), |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        self.show_magnifier(ui);
+                        ui.label(coordinates.as_str());
+                        ui.horizontal(|ui| match color {
+                            Some(color) => {
+                                show_color_swatch(ui, color);
+                                ui.label(color.to_string());
+                            }
+                            None => {
+                                ui.label("Could not capture the screen");
+                            }
+                        });
+                        ui.small(hint.as_str());
+                    });
+                });
+        });
+    }
+
+    /// The pixels around the picked point, zoomed, the picked one outlined
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - The ui to add the magnifier to
+    fn show_magnifier(&self, ui: &mut egui::Ui) {
+        let cells = (MAGNIFIER_RADIUS * 2 + 1) as f32;
+        let (rect, _) = ui.allocate_exact_size(
+            egui::Vec2::splat(cells * MAGNIFIER_ZOOM),
+            egui::Sense::hover(),
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0f32, Color32::BLACK);
+
+        // Cell of a pixel of the screen
+        let origin = (
+            self.picked_point.0 - MAGNIFIER_RADIUS,
+            self.picked_point.1 - MAGNIFIER_RADIUS,
+        );
+        let cell = |x: i32, y: i32| {
+            egui::Rect::from_min_size(
+                rect.min
+                    + egui::vec2((x - origin.0) as f32, (y - origin.1) as f32) * MAGNIFIER_ZOOM,
+                egui::Vec2::splat(MAGNIFIER_ZOOM),
+            )
+        };
+        if let Some((region, image)) = &self.magnifier {
+            for (x, y, pixel) in image.enumerate_pixels() {
+                let [r, g, b] = pixel.0;
+                painter.rect_filled(
+                    cell(region.x + x as i32, region.y + y as i32),
+                    0f32,
+                    Color32::from_rgb(r, g, b),
+                );
+            }
+        }
+
+        // Outlined in black & white to stand out on any color
+        let picked = cell(self.picked_point.0, self.picked_point.1);
+        painter.rect_stroke(
+            picked,
+            0f32,
+            Stroke::new(1f32, Color32::BLACK),
+            egui::StrokeKind::Inside,
+        );
+        painter.rect_stroke(
+            picked,
+            0f32,
+            Stroke::new(1f32, Color32::WHITE),
+            egui::StrokeKind::Outside,
+        );
+    }
+}
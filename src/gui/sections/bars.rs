@@ -1,6 +1,8 @@
 use eframe::egui::{self};
 
-use crate::{RustyAutoClickerApp, types::AppMode};
+use rusty_autoclicker::{engine::ClickErrorKind, types::AppMode};
+
+use crate::RustyAutoClickerApp;
 
 impl RustyAutoClickerApp {
     pub fn show_topbar(&mut self, ctx: &egui::Context) {
@@ -28,6 +30,18 @@ impl RustyAutoClickerApp {
                     }
                 }
 
+                ui.add_enabled(
+                    !self.is_autoclicking,
+                    egui::Checkbox::new(&mut self.dry_run, "Dry run"),
+                )
+                .on_hover_text("Show the clicks on screen instead of sending them");
+                ui.checkbox(&mut self.show_targets, "Show targets")
+                    .on_hover_text(
+                        "Mark the targets & flash the clicks on screen while autoclicking",
+                    );
+                ui.checkbox(&mut self.compact_mode, "Compact")
+                    .on_hover_text("Shrink to a small always-on-top bar while autoclicking");
+
                 ui.separator();
                 ui.label("Settings: ");
 
@@ -37,6 +51,14 @@ impl RustyAutoClickerApp {
                 {
                     self.hotkey_window_open = true
                 };
+                if ui
+                    .add_enabled(!self.is_autoclicking, egui::Button::new("⚙ Options"))
+                    .clicked()
+                {
+                    self.options_window_open = true
+                };
+                ui.toggle_value(&mut self.stats_window_open, "📊 Stats");
+                ui.toggle_value(&mut self.timeline_window_open, "🎞 Timeline");
 
                 ui.separator();
                 ui.label("App Mode: ");
@@ -52,6 +74,44 @@ impl RustyAutoClickerApp {
                 ui.selectable_value(&mut self.app_mode, AppMode::Humanlike, "😆 Humanlike")
                     .on_hover_text("Autoclick emulating human clicking");
             });
+
+            self.show_profiles(ui);
+        });
+    }
+
+    pub fn show_error_banner(&mut self, ctx: &egui::Context) {
+        let Some(error) = self.input_error.clone() else {
+            return;
+        };
+
+        egui::TopBottomPanel::top("error_banner").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+                    self.input_error = None;
+                    self.failure_counter = 0u64;
+                };
+                let text = if self.failure_counter > 1u64 {
+                    format!("⚠ {error} ({} failed clicks)", self.failure_counter)
+                } else {
+                    format!("⚠ {error}")
+                };
+                ui.colored_label(ui.visuals().error_fg_color, text)
+                    .on_hover_text(&error);
+            });
+        });
+    }
+
+    pub fn show_pause_banner(&mut self, ctx: &egui::Context) {
+        let Some(e) = self.click_paused() else {
+            return;
+        };
+
+        let text = match e.kind {
+            ClickErrorKind::Skipped => format!("⏭ Skipping clicks: {}", e.error),
+            _ => format!("⏸ Paused: {}, waiting for it to come back", e.error),
+        };
+        egui::TopBottomPanel::top("pause_banner").show(ctx, |ui| {
+            ui.colored_label(ui.visuals().warn_fg_color, text);
         });
     }
 
@@ -1,13 +1,14 @@
 use eframe::egui::{self};
 use rdev::{Button, Key};
+use rusty_autoclicker::types::ClickButton;
 
-use crate::{RustyAutoClickerApp, types::ClickButton};
+use crate::RustyAutoClickerApp;
 
 /// Helper macro to add a selectable value for a keyboard key in the UI.
 macro_rules! key_option {
-    ($ui:expr, $self:expr, $variant:ident) => {
+    ($ui:expr, $click_btn:expr, $variant:ident) => {
         $ui.selectable_value(
-            &mut $self.click_btn,
+            $click_btn,
             ClickButton::Key(Key::$variant),
             stringify!($variant),
         );
@@ -48,23 +49,7 @@ impl RustyAutoClickerApp {
         ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
             egui::ComboBox::from_id_salt("mouse_button")
                 .selected_text(format!("{}", self.click_btn))
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(
-                        &mut self.click_btn,
-                        ClickButton::Mouse(Button::Left),
-                        "Left",
-                    );
-                    ui.selectable_value(
-                        &mut self.click_btn,
-                        ClickButton::Mouse(Button::Right),
-                        "Right",
-                    );
-                    ui.selectable_value(
-                        &mut self.click_btn,
-                        ClickButton::Mouse(Button::Middle),
-                        "Middle",
-                    );
-                });
+                .show_ui(ui, |ui| show_mouse_options(ui, &mut self.click_btn));
         });
     }
 
@@ -72,127 +57,147 @@ impl RustyAutoClickerApp {
         ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
             egui::ComboBox::from_id_salt("keyboard_button")
                 .selected_text(format!("{}", self.click_btn))
-                .show_ui(ui, |ui| {
-                    // Modifier keys
-                    key_option!(ui, self, Alt);
-                    key_option!(ui, self, AltGr);
-                    key_option!(ui, self, CapsLock);
-                    key_option!(ui, self, ControlLeft);
-                    key_option!(ui, self, ControlRight);
-                    key_option!(ui, self, MetaLeft);
-                    key_option!(ui, self, MetaRight);
-                    key_option!(ui, self, ShiftLeft);
-                    key_option!(ui, self, ShiftRight);
-                    key_option!(ui, self, Function);
+                .show_ui(ui, |ui| show_key_options(ui, &mut self.click_btn));
+        });
+    }
+}
+
+/// Selectable values for the mouse buttons
+///
+/// # Arguments
+///
+/// * `ui` - The ui to add the values to
+/// * `click_btn` - The button to set
+pub fn show_mouse_options(ui: &mut egui::Ui, click_btn: &mut ClickButton) {
+    ui.selectable_value(click_btn, ClickButton::Mouse(Button::Left), "Left");
+    ui.selectable_value(click_btn, ClickButton::Mouse(Button::Right), "Right");
+    ui.selectable_value(click_btn, ClickButton::Mouse(Button::Middle), "Middle");
+}
 
-                    // Navigation
-                    key_option!(ui, self, UpArrow);
-                    key_option!(ui, self, DownArrow);
-                    key_option!(ui, self, LeftArrow);
-                    key_option!(ui, self, RightArrow);
-                    key_option!(ui, self, Home);
-                    key_option!(ui, self, End);
-                    key_option!(ui, self, PageUp);
-                    key_option!(ui, self, PageDown);
-                    key_option!(ui, self, Insert);
-                    key_option!(ui, self, Delete);
-                    key_option!(ui, self, Escape);
-                    key_option!(ui, self, Return);
-                    key_option!(ui, self, Tab);
-                    key_option!(ui, self, Space);
+/// Selectable values for the keyboard keys
+///
+/// # Arguments
+///
+/// * `ui` - The ui to add the values to
+/// * `click_btn` - The button to set
+pub fn show_key_options(ui: &mut egui::Ui, click_btn: &mut ClickButton) {
+    // Modifier keys
+    key_option!(ui, click_btn, Alt);
+    key_option!(ui, click_btn, AltGr);
+    key_option!(ui, click_btn, CapsLock);
+    key_option!(ui, click_btn, ControlLeft);
+    key_option!(ui, click_btn, ControlRight);
+    key_option!(ui, click_btn, MetaLeft);
+    key_option!(ui, click_btn, MetaRight);
+    key_option!(ui, click_btn, ShiftLeft);
+    key_option!(ui, click_btn, ShiftRight);
+    key_option!(ui, click_btn, Function);
 
-                    // Function keys
-                    key_option!(ui, self, F1);
-                    key_option!(ui, self, F2);
-                    key_option!(ui, self, F3);
-                    key_option!(ui, self, F4);
-                    key_option!(ui, self, F5);
-                    key_option!(ui, self, F6);
-                    key_option!(ui, self, F7);
-                    key_option!(ui, self, F8);
-                    key_option!(ui, self, F9);
-                    key_option!(ui, self, F10);
-                    key_option!(ui, self, F11);
-                    key_option!(ui, self, F12);
+    // Navigation
+    key_option!(ui, click_btn, UpArrow);
+    key_option!(ui, click_btn, DownArrow);
+    key_option!(ui, click_btn, LeftArrow);
+    key_option!(ui, click_btn, RightArrow);
+    key_option!(ui, click_btn, Home);
+    key_option!(ui, click_btn, End);
+    key_option!(ui, click_btn, PageUp);
+    key_option!(ui, click_btn, PageDown);
+    key_option!(ui, click_btn, Insert);
+    key_option!(ui, click_btn, Delete);
+    key_option!(ui, click_btn, Escape);
+    key_option!(ui, click_btn, Return);
+    key_option!(ui, click_btn, Tab);
+    key_option!(ui, click_btn, Space);
 
-                    // Print/Lock
-                    key_option!(ui, self, PrintScreen);
-                    key_option!(ui, self, ScrollLock);
-                    key_option!(ui, self, Pause);
-                    key_option!(ui, self, NumLock);
+    // Function keys
+    key_option!(ui, click_btn, F1);
+    key_option!(ui, click_btn, F2);
+    key_option!(ui, click_btn, F3);
+    key_option!(ui, click_btn, F4);
+    key_option!(ui, click_btn, F5);
+    key_option!(ui, click_btn, F6);
+    key_option!(ui, click_btn, F7);
+    key_option!(ui, click_btn, F8);
+    key_option!(ui, click_btn, F9);
+    key_option!(ui, click_btn, F10);
+    key_option!(ui, click_btn, F11);
+    key_option!(ui, click_btn, F12);
 
-                    // Top row number keys and symbols
-                    key_option!(ui, self, BackQuote);
-                    key_option!(ui, self, Num1);
-                    key_option!(ui, self, Num2);
-                    key_option!(ui, self, Num3);
-                    key_option!(ui, self, Num4);
-                    key_option!(ui, self, Num5);
-                    key_option!(ui, self, Num6);
-                    key_option!(ui, self, Num7);
-                    key_option!(ui, self, Num8);
-                    key_option!(ui, self, Num9);
-                    key_option!(ui, self, Num0);
-                    key_option!(ui, self, Minus);
-                    key_option!(ui, self, Equal);
+    // Print/Lock
+    key_option!(ui, click_btn, PrintScreen);
+    key_option!(ui, click_btn, ScrollLock);
+    key_option!(ui, click_btn, Pause);
+    key_option!(ui, click_btn, NumLock);
 
-                    // Letter keys
-                    key_option!(ui, self, KeyA);
-                    key_option!(ui, self, KeyB);
-                    key_option!(ui, self, KeyC);
-                    key_option!(ui, self, KeyD);
-                    key_option!(ui, self, KeyE);
-                    key_option!(ui, self, KeyF);
-                    key_option!(ui, self, KeyG);
-                    key_option!(ui, self, KeyH);
-                    key_option!(ui, self, KeyI);
-                    key_option!(ui, self, KeyJ);
-                    key_option!(ui, self, KeyK);
-                    key_option!(ui, self, KeyL);
-                    key_option!(ui, self, KeyM);
-                    key_option!(ui, self, KeyN);
-                    key_option!(ui, self, KeyO);
-                    key_option!(ui, self, KeyP);
-                    key_option!(ui, self, KeyQ);
-                    key_option!(ui, self, KeyR);
-                    key_option!(ui, self, KeyS);
-                    key_option!(ui, self, KeyT);
-                    key_option!(ui, self, KeyU);
-                    key_option!(ui, self, KeyV);
-                    key_option!(ui, self, KeyW);
-                    key_option!(ui, self, KeyX);
-                    key_option!(ui, self, KeyY);
-                    key_option!(ui, self, KeyZ);
+    // Top row number keys and symbols
+    key_option!(ui, click_btn, BackQuote);
+    key_option!(ui, click_btn, Num1);
+    key_option!(ui, click_btn, Num2);
+    key_option!(ui, click_btn, Num3);
+    key_option!(ui, click_btn, Num4);
+    key_option!(ui, click_btn, Num5);
+    key_option!(ui, click_btn, Num6);
+    key_option!(ui, click_btn, Num7);
+    key_option!(ui, click_btn, Num8);
+    key_option!(ui, click_btn, Num9);
+    key_option!(ui, click_btn, Num0);
+    key_option!(ui, click_btn, Minus);
+    key_option!(ui, click_btn, Equal);
 
-                    // Punctuation and symbol keys
-                    key_option!(ui, self, LeftBracket);
-                    key_option!(ui, self, RightBracket);
-                    key_option!(ui, self, SemiColon);
-                    key_option!(ui, self, Quote);
-                    key_option!(ui, self, BackSlash);
-                    key_option!(ui, self, IntlBackslash);
-                    key_option!(ui, self, Comma);
-                    key_option!(ui, self, Dot);
-                    key_option!(ui, self, Slash);
+    // Letter keys
+    key_option!(ui, click_btn, KeyA);
+    key_option!(ui, click_btn, KeyB);
+    key_option!(ui, click_btn, KeyC);
+    key_option!(ui, click_btn, KeyD);
+    key_option!(ui, click_btn, KeyE);
+    key_option!(ui, click_btn, KeyF);
+    key_option!(ui, click_btn, KeyG);
+    key_option!(ui, click_btn, KeyH);
+    key_option!(ui, click_btn, KeyI);
+    key_option!(ui, click_btn, KeyJ);
+    key_option!(ui, click_btn, KeyK);
+    key_option!(ui, click_btn, KeyL);
+    key_option!(ui, click_btn, KeyM);
+    key_option!(ui, click_btn, KeyN);
+    key_option!(ui, click_btn, KeyO);
+    key_option!(ui, click_btn, KeyP);
+    key_option!(ui, click_btn, KeyQ);
+    key_option!(ui, click_btn, KeyR);
+    key_option!(ui, click_btn, KeyS);
+    key_option!(ui, click_btn, KeyT);
+    key_option!(ui, click_btn, KeyU);
+    key_option!(ui, click_btn, KeyV);
+    key_option!(ui, click_btn, KeyW);
+    key_option!(ui, click_btn, KeyX);
+    key_option!(ui, click_btn, KeyY);
+    key_option!(ui, click_btn, KeyZ);
 
-                    // Keypad
-                    key_option!(ui, self, KpReturn);
-                    key_option!(ui, self, KpMinus);
-                    key_option!(ui, self, KpPlus);
-                    key_option!(ui, self, KpMultiply);
-                    key_option!(ui, self, KpDivide);
-                    key_option!(ui, self, Kp0);
-                    key_option!(ui, self, Kp1);
-                    key_option!(ui, self, Kp2);
-                    key_option!(ui, self, Kp3);
-                    key_option!(ui, self, Kp4);
-                    key_option!(ui, self, Kp5);
-                    key_option!(ui, self, Kp6);
-                    key_option!(ui, self, Kp7);
-                    key_option!(ui, self, Kp8);
-                    key_option!(ui, self, Kp9);
-                    key_option!(ui, self, KpDelete);
-                });
-        });
-    }
+    // Punctuation and symbol keys
+    key_option!(ui, click_btn, LeftBracket);
+    key_option!(ui, click_btn, RightBracket);
+    key_option!(ui, click_btn, SemiColon);
+    key_option!(ui, click_btn, Quote);
+    key_option!(ui, click_btn, BackSlash);
+    key_option!(ui, click_btn, IntlBackslash);
+    key_option!(ui, click_btn, Comma);
+    key_option!(ui, click_btn, Dot);
+    key_option!(ui, click_btn, Slash);
+
+    // Keypad
+    key_option!(ui, click_btn, KpReturn);
+    key_option!(ui, click_btn, KpMinus);
+    key_option!(ui, click_btn, KpPlus);
+    key_option!(ui, click_btn, KpMultiply);
+    key_option!(ui, click_btn, KpDivide);
+    key_option!(ui, click_btn, Kp0);
+    key_option!(ui, click_btn, Kp1);
+    key_option!(ui, click_btn, Kp2);
+    key_option!(ui, click_btn, Kp3);
+    key_option!(ui, click_btn, Kp4);
+    key_option!(ui, click_btn, Kp5);
+    key_option!(ui, click_btn, Kp6);
+    key_option!(ui, click_btn, Kp7);
+    key_option!(ui, click_btn, Kp8);
+    key_option!(ui, click_btn, Kp9);
+    key_option!(ui, click_btn, KpDelete);
 }
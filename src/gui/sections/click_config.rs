@@ -1,8 +1,16 @@
 use eframe::egui::{self, Context};
+use rusty_autoclicker::{
+    engine::{
+        pixel::Color,
+        window::{WindowInfo, WindowMatch},
+    },
+    types::{ClickPosition, ClickType, CoordMode},
+};
 
 use crate::{
     RustyAutoClickerApp,
-    types::{ClickPosition, ClickType},
+    fields::{Number, NumberField},
+    utils::format_coord,
 };
 
 impl RustyAutoClickerApp {
@@ -15,43 +23,36 @@ impl RustyAutoClickerApp {
                 if self.is_autoclicking || self.hotkey_window_open {
                     ui.disable();
                 };
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.ms_str)
-                        .desired_width(40.0f32)
-                        .hint_text("0"),
-                );
+                show_number_field(ui, &mut self.ms, 40.0f32, "0");
 
                 ui.label("sec");
                 if self.is_autoclicking || self.hotkey_window_open {
                     ui.disable();
                 };
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.sec_str)
-                        .desired_width(40.0f32)
-                        .hint_text("0"),
-                );
+                show_number_field(ui, &mut self.sec, 40.0f32, "0");
 
                 ui.label("min");
                 if self.is_autoclicking || self.hotkey_window_open {
                     ui.disable();
                 };
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.min_str)
-                        .desired_width(40.0f32)
-                        .hint_text("0"),
-                );
+                show_number_field(ui, &mut self.min, 40.0f32, "0");
 
                 ui.label("hr");
                 if self.is_autoclicking || self.hotkey_window_open {
                     ui.disable();
                 };
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.hr_str)
-                        .desired_width(40.0f32)
-                        .hint_text("0"),
-                );
+                show_number_field(ui, &mut self.hr, 40.0f32, "0");
             });
         });
+        show_field_errors(
+            ui,
+            &[
+                ("Hours", self.hr.error()),
+                ("Minutes", self.min.error()),
+                ("Seconds", self.sec.error()),
+                ("Milliseconds", self.ms.error()),
+            ],
+        );
     }
 
     pub fn show_click_type(&mut self, ui: &mut egui::Ui) {
@@ -80,11 +81,7 @@ impl RustyAutoClickerApp {
                 if self.is_autoclicking || self.hotkey_window_open {
                     ui.disable();
                 };
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.click_amount_str)
-                        .desired_width(40.0f32)
-                        .hint_text("0"),
-                );
+                show_number_field(ui, &mut self.click_amount, 40.0f32, "0");
                 if self.is_autoclicking && click_amount > 0u64 {
                     let remaining_clicks = click_amount.saturating_sub(self.click_counter);
                     let remaining_text = format!("Remaining {remaining_clicks:?}");
@@ -92,6 +89,7 @@ impl RustyAutoClickerApp {
                 }
             });
         });
+        show_field_errors(ui, &[("Click amount", self.click_amount.error())]);
     }
 
     pub fn show_click_position(&mut self, ui: &mut egui::Ui, ctx: &Context) {
@@ -106,25 +104,28 @@ impl RustyAutoClickerApp {
             {
                 Self::enter_coordinate_setting(self, ctx);
             };
+            if ui
+                .add_sized([80.0f32, 16.0f32], egui::widgets::Button::new("Set Points"))
+                .on_hover_text("Capture several points, clicked in turn")
+                .clicked()
+            {
+                Self::enter_point_capture(self, ctx);
+            };
             ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
                 if self.is_autoclicking || self.hotkey_window_open {
                     ui.disable();
                 };
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.click_y_str)
-                        .desired_width(50.0f32)
-                        .hint_text("0"),
-                );
-                ui.label("Y");
-                if self.is_autoclicking || self.hotkey_window_open {
-                    ui.disable();
-                };
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.click_x_str)
-                        .desired_width(50.0f32)
-                        .hint_text("0"),
-                );
-                ui.label("X");
+                if !self.points.is_empty() {
+                    self.show_points(ui);
+                } else {
+                    show_number_field(ui, &mut self.click_y, 50.0f32, "0");
+                    ui.label("Y");
+                    if self.is_autoclicking || self.hotkey_window_open {
+                        ui.disable();
+                    };
+                    show_number_field(ui, &mut self.click_x, 50.0f32, "0");
+                    ui.label("X");
+                }
 
                 if self.is_autoclicking || self.hotkey_window_open {
                     ui.disable();
@@ -144,5 +145,280 @@ impl RustyAutoClickerApp {
                 };
             });
         });
+        if self.points.is_empty() {
+            show_field_errors(
+                ui,
+                &[("X", self.click_x.error()), ("Y", self.click_y.error())],
+            );
+        }
+    }
+
+    /// The points of the target list, with a button to clear it, laid out right to left
+    fn show_points(&mut self, ui: &mut egui::Ui) {
+        if ui
+            .small_button("✖")
+            .on_hover_text("Clear the points, clicking at the coordinates")
+            .clicked()
+        {
+            self.points.clear();
+            return;
+        }
+        let list = self
+            .points
+            .iter()
+            .map(|point| {
+                format!(
+                    "{}: {}/{}",
+                    point.label,
+                    format_coord(point.x, self.coord_mode),
+                    format_coord(point.y, self.coord_mode)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        ui.label(format!("{} points", self.points.len()))
+            .on_hover_text(list);
+    }
+
+    /// The target image & how closely it must match, laid out right to left
+    fn show_image_target(&mut self, ui: &mut egui::Ui) {
+        let mut percent = self.image_target.confidence * 100.0f32;
+        ui.add(
+            egui::DragValue::new(&mut percent)
+                .range(1.0f32..=100.0f32)
+                .max_decimals(0)
+                .suffix("%"),
+        )
+        .on_hover_text("Lowest match score to consider the image found");
+        self.image_target.confidence = percent / 100.0f32;
+
+        let text = match self.image_target.path.file_name() {
+            Some(name) => format!("🖼 {}", name.to_string_lossy()),
+            None => "Choose…".to_owned(),
+        };
+        let hover_text = match (&self.image_search, self.image_center) {
+            (Some(_), _) => "Searching the screen…".to_owned(),
+            (None, Some((x, y))) => format!("Found at {x}/{y}"),
+            (None, None) => "Not found on the screen".to_owned(),
+        };
+        if ui
+            .add(egui::Button::new(text).truncate())
+            .on_hover_text(hover_text)
+            .clicked()
+        {
+            self.choose_target_image();
+        }
+    }
+
+    pub fn show_coord_mode(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Coordinates In");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                if self.is_autoclicking || self.hotkey_window_open {
+                    ui.disable();
+                };
+
+                let mut coord_mode = self.coord_mode;
+                if let CoordMode::MonitorPercent(index) = &mut coord_mode {
+                    // Allow any index, a missing monitor is reported when starting
+                    let max_index = self.monitors.len().saturating_sub(1).max(*index);
+                    ui.add(egui::DragValue::new(index).range(0..=max_index));
+                    ui.label("Monitor");
+                }
+                if coord_mode == CoordMode::Image {
+                    self.show_image_target(ui);
+                }
+                if coord_mode == CoordMode::Window {
+                    show_window_match(
+                        ui,
+                        &mut self.window_match,
+                        &self.windows,
+                        "window_match",
+                        "Pick with Set Coords",
+                    );
+                }
+                egui::ComboBox::from_id_salt("coord_mode")
+                    .selected_text(match coord_mode {
+                        CoordMode::Absolute => "Pixels",
+                        CoordMode::ScreenPercent => "% of screen",
+                        CoordMode::MonitorPercent(_) => "% of monitor",
+                        CoordMode::Window => "Window",
+                        CoordMode::Image => "Image",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut coord_mode, CoordMode::Absolute, "Pixels");
+                        ui.selectable_value(
+                            &mut coord_mode,
+                            CoordMode::ScreenPercent,
+                            "% of screen",
+                        );
+                        if ui
+                            .selectable_label(
+                                matches!(coord_mode, CoordMode::MonitorPercent(_)),
+                                "% of monitor",
+                            )
+                            .clicked()
+                            && !matches!(coord_mode, CoordMode::MonitorPercent(_))
+                        {
+                            coord_mode = CoordMode::MonitorPercent(0);
+                        }
+                        ui.selectable_value(&mut coord_mode, CoordMode::Window, "Window")
+                            .on_hover_text("Pixels from the top-left corner of a window (X11)");
+                        ui.selectable_value(&mut coord_mode, CoordMode::Image, "Image")
+                            .on_hover_text(
+                                "Pixels from the center of an image on the screen (X11)",
+                            );
+                    });
+                match (self.coord_mode, coord_mode) {
+                    // Same percentages on another monitor
+                    (CoordMode::MonitorPercent(_), CoordMode::MonitorPercent(_)) => {
+                        self.coord_mode = coord_mode
+                    }
+                    _ if coord_mode != self.coord_mode => self.change_coord_mode(coord_mode),
+                    _ => (),
+                }
+            });
+        });
+    }
+}
+
+/// How a window is found, laid out right to left
+///
+/// # Arguments
+///
+/// * `ui` - The ui to add the widgets to
+/// * `window_match` - The window match to edit
+/// * `windows` - The windows last read, to show the picked window and prefill the patterns
+/// * `id_salt` - Unique id of the combo box
+/// * `pick_hint` - How to pick a window, shown while none is picked
+pub fn show_window_match(
+    ui: &mut egui::Ui,
+    window_match: &mut WindowMatch,
+    windows: &[WindowInfo],
+    id_salt: &str,
+    pick_hint: &str,
+) {
+    let current_window = window_match.find(windows).ok().flatten().cloned();
+    match window_match {
+        WindowMatch::Id(_) => {
+            let text = match &current_window {
+                Some(window) => format!("⌖ {}", window.title),
+                None => pick_hint.to_owned(),
+            };
+            ui.add(egui::Label::new(text).truncate())
+                .on_hover_text(pick_hint);
+        }
+        WindowMatch::Title(pattern) | WindowMatch::Class(pattern) => {
+            ui.add(
+                egui::TextEdit::singleline(pattern)
+                    .desired_width(100.0f32)
+                    .hint_text("regex"),
+            );
+        }
     }
+
+    let mut kind = std::mem::discriminant(&*window_match);
+    let picked = std::mem::discriminant(&WindowMatch::Id(0));
+    let title = std::mem::discriminant(&WindowMatch::Title(String::new()));
+    let class = std::mem::discriminant(&WindowMatch::Class(String::new()));
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(match window_match {
+            WindowMatch::Id(_) => "Picked",
+            WindowMatch::Title(_) => "Title",
+            WindowMatch::Class(_) => "WM_CLASS",
+        })
+        .width(80.0f32)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut kind, picked, "Picked");
+            ui.selectable_value(&mut kind, title, "Title");
+            ui.selectable_value(&mut kind, class, "WM_CLASS");
+        });
+
+    // Start from the current window when switching to a pattern
+    if kind != std::mem::discriminant(&*window_match) {
+        *window_match = if kind == title {
+            WindowMatch::Title(
+                current_window.map_or_else(String::new, |window| regex::escape(&window.title)),
+            )
+        } else if kind == class {
+            WindowMatch::Class(
+                current_window.map_or_else(String::new, |window| regex::escape(&window.class)),
+            )
+        } else {
+            WindowMatch::Id(current_window.map_or(0, |window| window.id))
+        };
+    }
+}
+
+/// A text input for a number, with a red border & the reason on hover while it is invalid
+///
+/// # Arguments
+///
+/// * `ui` - The ui to add the input to
+/// * `field` - The number to edit
+/// * `width` - The width of the input
+/// * `hint` - The text shown while the input is empty
+pub fn show_number_field<T: Number>(
+    ui: &mut egui::Ui,
+    field: &mut NumberField<T>,
+    width: f32,
+    hint: &str,
+) -> egui::Response {
+    let mut text = field.text().to_owned();
+    let mut response = ui.add(
+        egui::TextEdit::singleline(&mut text)
+            .desired_width(width)
+            .hint_text(hint),
+    );
+    if response.changed() {
+        field.set_text(text);
+    }
+
+    if let Some(error) = field.error() {
+        ui.painter().rect_stroke(
+            response.rect,
+            ui.visuals().widgets.inactive.corner_radius,
+            egui::Stroke::new(1.0f32, ui.visuals().error_fg_color),
+            egui::StrokeKind::Inside,
+        );
+        response = response.on_hover_text(error);
+    }
+    response
+}
+
+/// The reasons the inputs of a section are invalid, one line each
+///
+/// # Arguments
+///
+/// * `ui` - The ui to add the reasons to
+/// * `errors` - The name of each input & why it is invalid, if it is
+pub fn show_field_errors(ui: &mut egui::Ui, errors: &[(&str, Option<&str>)]) {
+    for (name, error) in errors {
+        if let Some(error) = error {
+            ui.colored_label(ui.visuals().error_fg_color, format!("{name}: {error}"));
+        }
+    }
+}
+
+/// A small square filled with the color
+///
+/// # Arguments
+///
+/// * `ui` - The ui to add the square to
+/// * `color` - The color to show
+pub fn show_color_swatch(ui: &mut egui::Ui, color: Color) {
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(16.0f32, 16.0f32), egui::Sense::hover());
+    ui.painter().rect_filled(
+        rect,
+        2.0f32,
+        egui::Color32::from_rgb(color.r, color.g, color.b),
+    );
+    ui.painter().rect_stroke(
+        rect,
+        2.0f32,
+        ui.visuals().widgets.noninteractive.bg_stroke,
+        egui::StrokeKind::Inside,
+    );
+    response.on_hover_text(color.to_string());
 }
@@ -0,0 +1,78 @@
+use std::time::{Duration, Instant};
+
+use eframe::egui::{self};
+use rusty_autoclicker::engine::ClickErrorKind;
+
+use crate::RustyAutoClickerApp;
+
+impl RustyAutoClickerApp {
+    /// The bar replacing the window while autoclicking: status, clicks, run time, rate & stop
+    /// Dragging it moves the window
+    pub fn show_compact_bar(&mut self, ctx: &egui::Context) {
+        let summary = self.click_stats.summary(Instant::now());
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                // A pill filling the transparent window
+                let rect = ui.max_rect();
+                ui.painter()
+                    .rect_filled(rect, rect.height() / 2.0f32, ui.visuals().panel_fill);
+                if ui
+                    .interact(rect, ui.id().with("compact_drag"), egui::Sense::drag())
+                    .drag_started()
+                {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                }
+
+                let inner_rect = rect.shrink2(egui::vec2(rect.height() / 2.0f32, 0.0f32));
+                ui.scope_builder(
+                    egui::UiBuilder::new()
+                        .max_rect(inner_rect)
+                        .layout(egui::Layout::left_to_right(egui::Align::Center)),
+                    |ui| {
+                        let (status, color) = match self.click_paused() {
+                            Some(e) if e.kind == ClickErrorKind::Skipped => {
+                                ("⏭", ui.visuals().warn_fg_color)
+                            }
+                            Some(_) => ("⏸", ui.visuals().warn_fg_color),
+                            None if self.dry_run => ("●", ui.visuals().text_color()),
+                            None => ("●", egui::Color32::GREEN),
+                        };
+                        ui.colored_label(color, status).on_hover_text(
+                            match (self.click_paused(), self.dry_run) {
+                                (Some(e), _) => e.error.to_string(),
+                                (None, true) => "Dry run".to_owned(),
+                                (None, false) => "Autoclicking".to_owned(),
+                            },
+                        );
+                        ui.label(format!("{} clicks", summary.click_counter));
+                        ui.separator();
+                        ui.label(format_run_time(summary.run_time));
+                        ui.separator();
+                        ui.label(format!("{:.1}/s", summary.clicks_per_second));
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let text = match self.key_autoclick {
+                                Some(hotkey) => format!("⏹ STOP ({hotkey})"),
+                                None => "⏹ STOP".to_owned(),
+                            };
+                            if ui.button(text).clicked() {
+                                self.is_autoclicking = false;
+                            };
+                        });
+                    },
+                );
+            });
+    }
+}
+
+/// Format a run time as hours, minutes & seconds
+///
+/// # Arguments
+///
+/// * `run_time` - The run time
+fn format_run_time(run_time: Duration) -> String {
+    let secs = run_time.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
@@ -0,0 +1,185 @@
+use std::time::Instant;
+
+use eframe::egui::{self};
+use rusty_autoclicker::types::{ClickPosition, ClickType};
+
+use super::{
+    buttons::{show_key_options, show_mouse_options},
+    click_config::show_number_field,
+};
+use crate::{
+    RustyAutoClickerApp,
+    defines::*,
+    jobs::{JobSpec, JobState},
+};
+
+impl RustyAutoClickerApp {
+    pub fn show_jobs_panel(&mut self, ctx: &egui::Context) {
+        self.sync_job_states();
+        egui::TopBottomPanel::bottom("jobs_panel")
+            .resizable(self.jobs_panel_open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let running = self
+                        .job_states
+                        .iter()
+                        .filter(|state| state.runner.is_some())
+                        .count();
+                    let text = match running {
+                        0 => "🗂 Jobs".to_owned(),
+                        running => format!("🗂 Jobs ({running} running)"),
+                    };
+                    ui.toggle_value(&mut self.jobs_panel_open, text)
+                        .on_hover_text("Extra jobs clicking alongside the main one");
+                    if !self.jobs_panel_open {
+                        return;
+                    }
+
+                    if ui.button("➕ Add").clicked() {
+                        self.add_job();
+                    };
+                    if self.is_running_jobs() && ui.button("⏹ Stop all").clicked() {
+                        self.stop_all_jobs();
+                    };
+                });
+
+                if !self.jobs_panel_open {
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .max_height(160.0f32)
+                    .show(ui, |ui| {
+                        let mut toggled = None;
+                        let mut removed = None;
+                        for index in 0..self.jobs.len() {
+                            let state = &self.job_states[index];
+                            let is_running = state.runner.is_some();
+                            let is_paused = state
+                                .runner
+                                .as_ref()
+                                .is_some_and(|runner| runner.is_paused());
+                            let click_counter = state.click_counter();
+                            let clicks_per_second =
+                                state.stats.summary(Instant::now()).clicks_per_second;
+                            let is_setting_key = self.setting_job_key == Some(index);
+
+                            ui.group(|ui| {
+                                ui.horizontal(|ui| {
+                                    let (text, hover_text) = if is_running {
+                                        ("⏹", "Stop the job")
+                                    } else {
+                                        ("▶", "Start the job")
+                                    };
+                                    if ui.button(text).on_hover_text(hover_text).clicked() {
+                                        toggled = Some(index);
+                                    }
+                                    let job = &mut self.jobs[index];
+                                    ui.add_enabled(
+                                        !is_running,
+                                        egui::TextEdit::singleline(&mut job.name)
+                                            .desired_width(90.0f32),
+                                    );
+                                    ui.label(format!("{click_counter} clicks"))
+                                        .on_hover_text(format!("{clicks_per_second:.1}/s"));
+                                    if is_paused {
+                                        ui.label("⏸");
+                                    }
+
+                                    ui.with_layout(
+                                        egui::Layout::right_to_left(egui::Align::Center),
+                                        |ui| {
+                                            if ui
+                                                .small_button("✖")
+                                                .on_hover_text("Remove the job")
+                                                .clicked()
+                                            {
+                                                removed = Some(index);
+                                            }
+                                            let text = match (&job.hotkey, is_setting_key) {
+                                                (_, true) => "Press a key…".to_owned(),
+                                                (Some(hotkey), false) => format!("⌨ {hotkey}"),
+                                                (None, false) => "⌨ Hotkey".to_owned(),
+                                            };
+                                            if ui
+                                                .button(text)
+                                                .on_hover_text("Start & stop the job with a key")
+                                                .clicked()
+                                            {
+                                                self.setting_job_key =
+                                                    (!is_setting_key).then_some(index);
+                                            }
+                                        },
+                                    );
+                                });
+                                ui.add_enabled_ui(!is_running, |ui| {
+                                    show_job_settings(
+                                        ui,
+                                        &mut self.jobs[index],
+                                        &mut self.job_states[index],
+                                        index,
+                                    );
+                                });
+                            });
+                        }
+
+                        if let Some(index) = toggled {
+                            self.toggle_job(index);
+                        }
+                        if let Some(index) = removed {
+                            self.remove_job(index);
+                        }
+                    });
+            });
+    }
+}
+
+/// The interval, button, click type, position & amount of a job
+///
+/// # Arguments
+///
+/// * `ui` - The ui to add the settings to
+/// * `job` - The job to edit
+/// * `state` - The numbers of the job as typed
+/// * `index` - The index of the job, to give its widgets unique ids
+fn show_job_settings(ui: &mut egui::Ui, job: &mut JobSpec, state: &mut JobState, index: usize) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Every");
+        ui.add(
+            egui::DragValue::new(&mut job.interval_ms)
+                .range(JOB_INTERVAL_MS_RANGE)
+                .suffix(" ms"),
+        );
+
+        egui::ComboBox::from_id_salt(("job_button", index))
+            .selected_text(job.click_btn.to_string())
+            .show_ui(ui, |ui| {
+                show_mouse_options(ui, &mut job.click_btn);
+                ui.separator();
+                show_key_options(ui, &mut job.click_btn);
+            });
+        egui::ComboBox::from_id_salt(("job_click_type", index))
+            .selected_text(format!("{:?}", job.click_type))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut job.click_type, ClickType::Single, "Single");
+                ui.selectable_value(&mut job.click_type, ClickType::Double, "Double");
+            });
+
+        ui.selectable_value(&mut job.click_position, ClickPosition::Mouse, "Mouse");
+        ui.selectable_value(&mut job.click_position, ClickPosition::Coord, "Coords");
+        if job.click_position == ClickPosition::Coord {
+            ui.label("X");
+            show_number_field(ui, &mut state.click_x, 50.0f32, "0");
+            ui.label("Y");
+            show_number_field(ui, &mut state.click_y, 50.0f32, "0");
+        }
+
+        ui.label("Amount");
+        show_number_field(ui, &mut state.click_amount, 60.0f32, "0")
+            .on_hover_text("Stop after this amount of clicks, 0 = forever");
+    });
+    job.click_x = state.click_x.value();
+    job.click_y = state.click_y.value();
+    job.click_amount = state.click_amount.value();
+}
@@ -0,0 +1,70 @@
+use eframe::egui::{self, Color32};
+use tracing::{Level, level_filters::LevelFilter};
+
+use crate::RustyAutoClickerApp;
+
+/// Levels selectable in the log panel, most severe first
+const LEVEL_FILTERS: [LevelFilter; 5] = [
+    LevelFilter::ERROR,
+    LevelFilter::WARN,
+    LevelFilter::INFO,
+    LevelFilter::DEBUG,
+    LevelFilter::TRACE,
+];
+
+impl RustyAutoClickerApp {
+    pub fn show_log_panel(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::bottom("log_panel")
+            .resizable(self.log_panel_open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.toggle_value(&mut self.log_panel_open, "📜 Log");
+                    if !self.log_panel_open {
+                        return;
+                    }
+
+                    egui::ComboBox::from_id_salt("log_level")
+                        .selected_text(self.log_level.to_string())
+                        .show_ui(ui, |ui| {
+                            for level in LEVEL_FILTERS {
+                                ui.selectable_value(&mut self.log_level, level, level.to_string());
+                            }
+                        });
+                    if ui.button("Clear").clicked() {
+                        self.log_buffer.clear();
+                    };
+                });
+
+                if !self.log_panel_open {
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .max_height(120.0f32)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        let error_color = ui.visuals().error_fg_color;
+                        let warn_color = ui.visuals().warn_fg_color;
+                        for entry in self.log_buffer.entries(self.log_level) {
+                            let color = match entry.level {
+                                Level::ERROR => error_color,
+                                Level::WARN => warn_color,
+                                Level::INFO => ui.visuals().text_color(),
+                                _ => Color32::GRAY,
+                            };
+                            ui.colored_label(
+                                color,
+                                format!(
+                                    "{:>9.3}s {:<5} {}: {}",
+                                    entry.elapsed.as_secs_f64(),
+                                    entry.level,
+                                    entry.target,
+                                    entry.message
+                                ),
+                            );
+                        }
+                    });
+            });
+    }
+}
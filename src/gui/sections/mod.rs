@@ -6,6 +6,14 @@ use crate::RustyAutoClickerApp;
 mod bars;
 mod buttons;
 mod click_config;
+mod compact;
+mod jobs;
+mod logs;
+mod profiles;
+
+pub use buttons::{show_key_options, show_mouse_options};
+pub use click_config::{show_color_swatch, show_window_match};
+use click_config::{show_field_errors, show_number_field};
 
 impl RustyAutoClickerApp {
     pub fn show_movement_delay(&mut self, ui: &mut egui::Ui) {
@@ -17,23 +25,22 @@ impl RustyAutoClickerApp {
                 if self.is_autoclicking || self.hotkey_window_open {
                     ui.disable();
                 };
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.movement_ms_str)
-                        .desired_width(40.0f32)
-                        .hint_text("20"),
-                );
+                show_number_field(ui, &mut self.movement_ms, 40.0f32, "20");
 
                 ui.label("sec");
                 if self.is_autoclicking || self.hotkey_window_open {
                     ui.disable();
                 };
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.movement_sec_str)
-                        .desired_width(40.0f32)
-                        .hint_text("0"),
-                );
+                show_number_field(ui, &mut self.movement_sec, 40.0f32, "0");
             });
         });
+        show_field_errors(
+            ui,
+            &[
+                ("Seconds", self.movement_sec.error()),
+                ("Milliseconds", self.movement_ms.error()),
+            ],
+        );
     }
 
     pub fn show_infos(&self, ui: &mut egui::Ui, mouse: &MouseState, keys: &[Keycode]) {
@@ -80,7 +87,7 @@ impl RustyAutoClickerApp {
                     .clicked()
                     .then(|| {
                         // Start autoclick, first click is delayed
-                        Self::start_autoclick(self, 0u64);
+                        Self::start_autoclick(self, true);
                         self.is_autoclicking = true
                     });
             }
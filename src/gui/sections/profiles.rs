@@ -0,0 +1,65 @@
+use eframe::egui::{self};
+
+use crate::{RustyAutoClickerApp, profiles::ProfileAction};
+
+impl RustyAutoClickerApp {
+    pub fn show_profiles(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Profile: ");
+            if self.is_autoclicking || self.hotkey_window_open || self.profile_window_open {
+                ui.disable();
+            };
+
+            let mut selected_profile: Option<String> = None;
+            egui::ComboBox::from_id_salt("profile")
+                .selected_text(self.active_profile.as_deref().unwrap_or("None"))
+                .width(120.0f32)
+                .show_ui(ui, |ui| {
+                    for name in &self.profile_names {
+                        if ui
+                            .selectable_label(self.active_profile.as_ref() == Some(name), name)
+                            .clicked()
+                        {
+                            selected_profile = Some(name.clone());
+                        }
+                    }
+                });
+            if let Some(name) = selected_profile {
+                self.load_profile(&name);
+            }
+
+            let has_active_profile = self.active_profile.is_some();
+            if ui
+                .button("💾 Save")
+                .on_hover_text("Save to the selected profile")
+                .clicked()
+            {
+                match self.active_profile.clone() {
+                    Some(name) => self.save_profile(&name),
+                    None => self.open_profile_window(ProfileAction::SaveAs),
+                }
+            };
+            if ui.button("Save As").clicked() {
+                self.open_profile_window(ProfileAction::SaveAs);
+            };
+            if ui
+                .add_enabled(has_active_profile, egui::Button::new("Duplicate"))
+                .on_hover_text("Copy the saved profile to a new name")
+                .clicked()
+            {
+                self.open_profile_window(ProfileAction::Duplicate);
+            };
+            if ui
+                .add_enabled(has_active_profile, egui::Button::new("🗑 Delete"))
+                .clicked()
+            {
+                self.delete_active_profile();
+            };
+
+            if let Some(error) = &self.profile_error {
+                ui.colored_label(ui.visuals().error_fg_color, "⚠")
+                    .on_hover_text(error);
+            }
+        });
+    }
+}
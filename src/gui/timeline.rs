@@ -0,0 +1,484 @@
+use std::time::Instant;
+
+use eframe::egui::{self, Color32, Context, FontId, Pos2, Rect, Sense, Stroke, Vec2};
+use rdev::{Button, Key};
+use rusty_autoclicker::{
+    engine::sequence::{Action, Step},
+    types::{ClickButton, ClickType},
+};
+
+use super::sections::{show_key_options, show_mouse_options};
+use crate::{RustyAutoClickerApp, defines::*, timeline::ScriptSource};
+
+/// A change to the steps of the timeline, applied once every block was drawn
+enum StepEdit {
+    Select(usize),
+    Move { from: usize, to: usize },
+    Resize(usize, u64),
+    Duplicate(usize),
+    Delete(usize),
+}
+
+/// A new step of each kind, as added from the timeline menu
+fn new_steps() -> [Step; 6] {
+    [
+        Action::Click {
+            button: Button::Left,
+            click_type: ClickType::Single,
+            coord: None,
+        },
+        Action::Key { key: Key::Space },
+        Action::Move { x: 0.0, y: 0.0 },
+        Action::Wait,
+        Action::Text {
+            text: String::new(),
+        },
+        Action::Wheel {
+            delta_x: 0,
+            delta_y: -1,
+        },
+    ]
+    .map(|action| Step::new(action, DEFAULT_STEP_MS))
+}
+
+/// The color of the blocks of a kind of action
+///
+/// # Arguments
+///
+/// * `action` - The action of the block
+fn block_color(action: &Action) -> Color32 {
+    match action {
+        Action::Click { .. } => Color32::from_rgb(52, 101, 164),
+        Action::Key { .. } => Color32::from_rgb(117, 80, 123),
+        Action::Move { .. } => Color32::from_rgb(6, 152, 154),
+        Action::Wait => Color32::from_rgb(85, 87, 83),
+        Action::Text { .. } => Color32::from_rgb(193, 125, 17),
+        Action::Wheel { .. } => Color32::from_rgb(78, 154, 6),
+    }
+}
+
+/// The width of the block of a step, proportional to its duration
+///
+/// # Arguments
+///
+/// * `step` - The step of the block
+fn block_width(step: &Step) -> f32 {
+    (step.duration_ms as f32 / 1000f32 * TIMELINE_PIXELS_PER_SECOND).max(TIMELINE_MIN_BLOCK_WIDTH)
+}
+
+impl RustyAutoClickerApp {
+    pub fn show_timeline_window(&mut self, ctx: &Context) {
+        let mut window_open = self.timeline_window_open;
+        egui::Window::new("Timeline")
+            .default_width(480f32)
+            .collapsible(true)
+            .open(&mut window_open)
+            .show(ctx, |ui| {
+                let is_playing = self.is_playing_sequence();
+                ui.horizontal(|ui| {
+                    if is_playing {
+                        if ui.button("⏹ Stop").clicked() {
+                            self.stop_sequence();
+                        }
+                    } else if ui
+                        .add_enabled(
+                            !self.sequence.steps.is_empty() && !self.is_autoclicking,
+                            egui::Button::new("▶ Play"),
+                        )
+                        .clicked()
+                    {
+                        self.play_sequence();
+                    }
+
+                    ui.add_enabled_ui(!is_playing, |ui| {
+                        ui.label("Repeat");
+                        ui.add(egui::DragValue::new(&mut self.sequence.repeat))
+                            .on_hover_text("Times the timeline is played, 0 = forever");
+                        ui.menu_button("➕ Add", |ui| {
+                            for step in new_steps() {
+                                if ui.button(step.action.name()).clicked() {
+                                    self.sequence.steps.push(step);
+                                    self.selected_step = Some(self.sequence.steps.len() - 1);
+                                    ui.close();
+                                }
+                            }
+                        });
+                    });
+
+                    let mut export = None;
+                    ui.menu_button("xdotool", |ui| {
+                        if ui
+                            .add_enabled(!is_playing, egui::Button::new("Import script…"))
+                            .on_hover_text("Replace the timeline with an xdotool script")
+                            .clicked()
+                        {
+                            self.import_xdotool();
+                            ui.close();
+                        }
+                        ui.separator();
+                        for (source, text) in [
+                            (ScriptSource::Timeline, "Export timeline…"),
+                            (ScriptSource::MainJob, "Export main job…"),
+                            (ScriptSource::ClickHistory, "Export click history…"),
+                        ] {
+                            if ui.button(text).clicked() {
+                                export = Some(source);
+                                ui.close();
+                            }
+                        }
+                    });
+                    if let Some(source) = export {
+                        self.export_xdotool(source);
+                    }
+
+                    let total = self.sequence.total_duration().as_secs_f64();
+                    match &self.sequence_player {
+                        Some(player) => ui.label(format!(
+                            "{:.1}/{total:.1} s, loop {}",
+                            player.position(Instant::now()).as_secs_f64(),
+                            player.loop_counter() + 1
+                        )),
+                        None => ui.label(format!("{total:.1} s")),
+                    };
+                });
+
+                if let Some(status) = &self.timeline_status {
+                    ui.label(status);
+                }
+
+                egui::ScrollArea::horizontal()
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| self.show_timeline(ui, is_playing));
+
+                ui.separator();
+                let selected = self
+                    .selected_step
+                    .filter(|index| *index < self.sequence.steps.len());
+                match selected {
+                    Some(index) => {
+                        ui.add_enabled_ui(!is_playing, |ui| {
+                            if let Some(edit) = self.show_step_editor(ui, index) {
+                                self.apply_step_edit(edit);
+                            }
+                        });
+                    }
+                    None => {
+                        ui.weak("Add a step or select one to edit it");
+                    }
+                }
+            });
+        self.timeline_window_open = window_open;
+    }
+
+    /// The blocks of the steps, with the playhead while playing
+    /// Blocks are dragged to reorder them, and their right edge to change their duration
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - The ui to draw the timeline in
+    /// * `is_playing` - Whether the timeline is being played, its steps cannot change meanwhile
+    fn show_timeline(&mut self, ui: &mut egui::Ui, is_playing: bool) {
+        let steps = &self.sequence.steps;
+        let widths: Vec<f32> = steps.iter().map(block_width).collect();
+        let total_width = widths.iter().sum::<f32>().max(ui.available_width());
+        let (area, _) =
+            ui.allocate_exact_size(Vec2::new(total_width, TIMELINE_HEIGHT), Sense::hover());
+        let painter = ui.painter_at(area);
+        painter.rect_filled(area, 4f32, ui.visuals().extreme_bg_color);
+
+        let mut blocks = Vec::with_capacity(steps.len());
+        let mut left = area.left();
+        for width in &widths {
+            blocks.push(Rect::from_min_size(
+                Pos2::new(left, area.top()),
+                Vec2::new(*width, TIMELINE_HEIGHT),
+            ));
+            left += width;
+        }
+
+        let pointer = ui.ctx().pointer_interact_pos();
+        let (sense, handle_sense) = if is_playing {
+            (Sense::hover(), Sense::hover())
+        } else {
+            (Sense::click_and_drag(), Sense::drag())
+        };
+        let mut edit = None;
+
+        for (index, (step, block)) in steps.iter().zip(&blocks).enumerate() {
+            let id = ui.id().with(("timeline_step", index));
+            let response = ui
+                .interact(block.shrink(1f32), id, sense)
+                .on_hover_text(format!("{}, {} ms", step.action, step.duration_ms));
+            let handle = Rect::from_min_max(
+                Pos2::new(block.right() - 5f32, block.top()),
+                block.right_bottom(),
+            );
+            let handle_response = ui
+                .interact(handle, id.with("handle"), handle_sense)
+                .on_hover_cursor(egui::CursorIcon::ResizeHorizontal);
+
+            if response.clicked() || response.drag_started() {
+                edit = Some(StepEdit::Select(index));
+            }
+            response.context_menu(|ui| {
+                if ui.button("⧉ Duplicate").clicked() {
+                    edit = Some(StepEdit::Duplicate(index));
+                    ui.close();
+                }
+                if ui.button("🗑 Delete").clicked() {
+                    edit = Some(StepEdit::Delete(index));
+                    ui.close();
+                }
+            });
+
+            // The duration follows the right edge, in steps of 10 ms
+            if handle_response.dragged()
+                && let Some(pointer) = pointer
+            {
+                let duration_ms =
+                    ((pointer.x - block.left()).max(0f32) / TIMELINE_PIXELS_PER_SECOND * 100f32)
+                        .round() as u64
+                        * 10;
+                edit = Some(StepEdit::Resize(index, duration_ms));
+            }
+
+            // A dragged block follows the pointer & lands among the blocks whose center it passed
+            let mut rect = *block;
+            if response.dragged()
+                && let Some(pointer) = pointer
+            {
+                rect = rect.translate(Vec2::new(pointer.x - block.center().x, 0f32));
+            }
+            if response.drag_stopped()
+                && let Some(pointer) = pointer
+            {
+                let to = blocks
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, other_block)| {
+                        *other != index && other_block.center().x < pointer.x
+                    })
+                    .count();
+                if to != index {
+                    edit = Some(StepEdit::Move { from: index, to });
+                }
+            }
+
+            let is_selected = self.selected_step == Some(index);
+            let is_current = self
+                .sequence_player
+                .as_ref()
+                .is_some_and(|player| player.current_step() == Some(index));
+            let rect = rect.shrink(1f32);
+            let color = if is_current {
+                block_color(&step.action).gamma_multiply(1.4)
+            } else {
+                block_color(&step.action)
+            };
+            painter.rect_filled(rect, 3f32, color);
+            if is_selected {
+                painter.rect_stroke(
+                    rect,
+                    3f32,
+                    Stroke::new(2f32, ui.visuals().selection.stroke.color),
+                    egui::StrokeKind::Inside,
+                );
+            }
+            let text_painter = painter.with_clip_rect(rect.shrink(3f32));
+            text_painter.text(
+                rect.left_top() + Vec2::new(4f32, 4f32),
+                egui::Align2::LEFT_TOP,
+                step.action.name(),
+                FontId::proportional(12f32),
+                Color32::WHITE,
+            );
+            text_painter.text(
+                rect.left_bottom() + Vec2::new(4f32, -4f32),
+                egui::Align2::LEFT_BOTTOM,
+                format!("{} ms", step.duration_ms),
+                FontId::proportional(10f32),
+                Color32::from_white_alpha(180),
+            );
+            if handle_response.hovered() || handle_response.dragged() {
+                painter.rect_filled(handle, 0f32, Color32::from_white_alpha(120));
+            }
+        }
+
+        // The playhead, at the same fraction of the block as of the step being played
+        if let Some(player) = &self.sequence_player
+            && let Some(index) = player.current_step()
+            && let Some(block) = blocks.get(index)
+        {
+            let step = &player.sequence().steps[index];
+            let into_step = player.position(Instant::now()) - player.sequence().start_of(index);
+            let fraction = match step.duration_ms {
+                0 => 1f32,
+                _ => into_step.as_secs_f32() / step.duration().as_secs_f32(),
+            };
+            painter.vline(
+                block.left() + fraction * block.width(),
+                area.y_range(),
+                Stroke::new(2f32, Color32::RED),
+            );
+        }
+
+        if let Some(edit) = edit {
+            self.apply_step_edit(edit);
+        }
+    }
+
+    /// The settings of a step, with buttons to move, duplicate & delete it
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - The ui to add the settings to
+    /// * `index` - The index of the step
+    fn show_step_editor(&mut self, ui: &mut egui::Ui, index: usize) -> Option<StepEdit> {
+        let step_count = self.sequence.steps.len();
+        let step = &mut self.sequence.steps[index];
+        let mut edit = None;
+
+        ui.horizontal(|ui| {
+            ui.label(format!("Step {}", index + 1));
+            egui::ComboBox::from_id_salt("timeline_action")
+                .selected_text(step.action.name())
+                .show_ui(ui, |ui| {
+                    for new_step in new_steps() {
+                        let name = new_step.action.name();
+                        if ui
+                            .selectable_label(step.action.name() == name, name)
+                            .clicked()
+                            && step.action.name() != name
+                        {
+                            step.action = new_step.action;
+                        }
+                    }
+                });
+            ui.add(
+                egui::DragValue::new(&mut step.duration_ms)
+                    .range(0u64..=86_400_000u64)
+                    .speed(10f32)
+                    .suffix(" ms"),
+            )
+            .on_hover_text("Time until the next step starts");
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("🗑").on_hover_text("Delete").clicked() {
+                    edit = Some(StepEdit::Delete(index));
+                }
+                if ui.button("⧉").on_hover_text("Duplicate").clicked() {
+                    edit = Some(StepEdit::Duplicate(index));
+                }
+                if ui
+                    .add_enabled(index + 1 < step_count, egui::Button::new("▶"))
+                    .on_hover_text("Move later")
+                    .clicked()
+                {
+                    edit = Some(StepEdit::Move {
+                        from: index,
+                        to: index + 1,
+                    });
+                }
+                if ui
+                    .add_enabled(index > 0, egui::Button::new("◀"))
+                    .on_hover_text("Move earlier")
+                    .clicked()
+                {
+                    edit = Some(StepEdit::Move {
+                        from: index,
+                        to: index - 1,
+                    });
+                }
+            });
+        });
+
+        ui.horizontal_wrapped(|ui| match &mut step.action {
+            Action::Click {
+                button,
+                click_type,
+                coord,
+            } => {
+                let mut click_btn = ClickButton::Mouse(*button);
+                egui::ComboBox::from_id_salt("timeline_button")
+                    .selected_text(click_btn.to_string())
+                    .show_ui(ui, |ui| show_mouse_options(ui, &mut click_btn));
+                if let ClickButton::Mouse(new_button) = click_btn {
+                    *button = new_button;
+                }
+                ui.selectable_value(click_type, ClickType::Single, "Single");
+                ui.selectable_value(click_type, ClickType::Double, "Double");
+
+                let mut at_coord = coord.is_some();
+                if ui.checkbox(&mut at_coord, "At").changed() {
+                    *coord = at_coord.then_some((0f64, 0f64));
+                }
+                if let Some((x, y)) = coord {
+                    ui.label("X");
+                    ui.add(egui::DragValue::new(x));
+                    ui.label("Y");
+                    ui.add(egui::DragValue::new(y));
+                }
+            }
+            Action::Key { key } => {
+                let mut click_btn = ClickButton::Key(*key);
+                egui::ComboBox::from_id_salt("timeline_key")
+                    .selected_text(click_btn.to_string())
+                    .show_ui(ui, |ui| show_key_options(ui, &mut click_btn));
+                if let ClickButton::Key(new_key) = click_btn {
+                    *key = new_key;
+                }
+            }
+            Action::Move { x, y } => {
+                ui.label("X");
+                ui.add(egui::DragValue::new(x));
+                ui.label("Y");
+                ui.add(egui::DragValue::new(y));
+            }
+            Action::Wait => {
+                ui.weak("Waits for the duration of the step");
+            }
+            Action::Text { text } => {
+                ui.add(
+                    egui::TextEdit::singleline(text)
+                        .desired_width(240f32)
+                        .hint_text("Text to type"),
+                );
+            }
+            Action::Wheel { delta_x, delta_y } => {
+                ui.label("Horizontal");
+                ui.add(egui::DragValue::new(delta_x));
+                ui.label("Vertical");
+                ui.add(egui::DragValue::new(delta_y))
+                    .on_hover_text("Positive scrolls up");
+            }
+        });
+
+        edit
+    }
+
+    /// Apply a change to the steps, keeping the changed step selected
+    ///
+    /// # Arguments
+    ///
+    /// * `edit` - The change
+    fn apply_step_edit(&mut self, edit: StepEdit) {
+        let steps = &mut self.sequence.steps;
+        match edit {
+            StepEdit::Select(index) => self.selected_step = Some(index),
+            StepEdit::Move { from, to } => {
+                let step = steps.remove(from);
+                steps.insert(to, step);
+                self.selected_step = Some(to);
+            }
+            StepEdit::Resize(index, duration_ms) => steps[index].duration_ms = duration_ms,
+            StepEdit::Duplicate(index) => {
+                steps.insert(index + 1, steps[index].clone());
+                self.selected_step = Some(index + 1);
+            }
+            StepEdit::Delete(index) => {
+                steps.remove(index);
+                self.selected_step = None;
+            }
+        }
+    }
+}
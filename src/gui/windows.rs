@@ -1,6 +1,22 @@
+use std::time::{Duration, Instant};
+
 use eframe::egui::{self, Context};
+use egui_plot::{HLine, Line, Plot, PlotPoints};
+use rusty_autoclicker::engine::{
+    backend::BackendKind,
+    history::HistoryFormat,
+    pixel::{Color, PixelTrigger},
+    screen::Region,
+    template::MissingAction,
+    window::FocusAction,
+};
 
-use crate::RustyAutoClickerApp;
+use super::sections::show_window_match;
+use crate::{
+    RustyAutoClickerApp,
+    defines::FOCUS_PICK_DELAY,
+    profiles::{self, ProfileAction},
+};
 
 impl RustyAutoClickerApp {
     pub fn show_hotkeys_window(&mut self, ctx: &Context) {
@@ -69,4 +85,350 @@ impl RustyAutoClickerApp {
                 });
             });
     }
+
+    pub fn show_profile_window(&mut self, ctx: &Context) {
+        let title = match self.profile_action {
+            ProfileAction::SaveAs => "Save Profile As",
+            ProfileAction::Duplicate => "Duplicate Profile",
+        };
+        let mut window_open = self.profile_window_open;
+        egui::Window::new(title)
+            .fixed_size(egui::vec2(220f32, 60f32))
+            .anchor(egui::Align2::CENTER_CENTER, [0f32, 0f32])
+            .collapsible(false)
+            .open(&mut window_open)
+            .show(ctx, |ui| {
+                let name_is_valid = profiles::is_valid_name(&self.profile_name_str);
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.profile_name_str)
+                            .desired_width(150.0f32)
+                            .hint_text("Profile name"),
+                    );
+                    response.request_focus();
+                    if name_is_valid
+                        && response.lost_focus()
+                        && ui.input(|input_state| input_state.key_pressed(egui::Key::Enter))
+                    {
+                        self.confirm_profile_window();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(name_is_valid, egui::Button::new("Confirm"))
+                        .on_disabled_hover_text("Letters, digits, spaces, '-' and '_' only")
+                        .clicked()
+                    {
+                        self.confirm_profile_window();
+                    }
+                    if self.profile_names.contains(&self.profile_name_str) {
+                        ui.label("Overwrites existing profile");
+                    }
+                });
+            });
+        self.profile_window_open &= window_open;
+    }
+
+    /// Run the pending profile action with the entered name and close the window
+    fn confirm_profile_window(&mut self) {
+        let name = self.profile_name_str.clone();
+        match self.profile_action {
+            ProfileAction::SaveAs => self.save_profile(&name),
+            ProfileAction::Duplicate => self.duplicate_profile(&name),
+        }
+        self.profile_window_open = false;
+    }
+
+    pub fn show_options_window(&mut self, ctx: &Context) {
+        let mut pick_pixel = false;
+        egui::Window::new("Options")
+            .fixed_size(egui::vec2(260f32, 100f32))
+            .anchor(egui::Align2::CENTER_CENTER, [0f32, 0f32])
+            .collapsible(false)
+            .open(&mut self.options_window_open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Input backend");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        if self.is_autoclicking {
+                            ui.disable();
+                        };
+                        egui::ComboBox::from_id_salt("input_backend")
+                            .selected_text(self.backend_kind.to_string())
+                            .show_ui(ui, |ui| {
+                                for kind in BackendKind::available() {
+                                    ui.selectable_value(
+                                        &mut self.backend_kind,
+                                        *kind,
+                                        kind.to_string(),
+                                    );
+                                }
+                            });
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Stop after failed clicks")
+                        .on_hover_text("Consecutive clicks that could not be sent, 0 = never");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.max_consecutive_failures)
+                                .range(0u32..=1000u32),
+                        );
+                    });
+                });
+                ui.horizontal(|ui| {
+                    let mut fixed_seed = self.humanlike_seed.is_some();
+                    ui.checkbox(&mut fixed_seed, "Fixed humanlike seed")
+                        .on_hover_text("Repeat the same humanlike timings on every run");
+                    match (fixed_seed, &mut self.humanlike_seed) {
+                        (true, Some(seed)) => {
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                                // TOML integers are signed
+                                ui.add(egui::DragValue::new(seed).range(0..=i64::MAX as u64));
+                            });
+                        }
+                        (true, None) => self.humanlike_seed = Some(0u64),
+                        (false, _) => self.humanlike_seed = None,
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if self.is_autoclicking {
+                        ui.disable();
+                    };
+                    ui.checkbox(&mut self.focus_guard_enabled, "Only click in window")
+                        .on_hover_text(
+                            "Skip or pause the clicks while another window is focused (X11)",
+                        );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        ui.radio_value(&mut self.focus_guard.action, FocusAction::Pause, "Pause");
+                        ui.radio_value(&mut self.focus_guard.action, FocusAction::Skip, "Skip");
+                    });
+                });
+                ui.horizontal(|ui| {
+                    if self.is_autoclicking || !self.focus_guard_enabled {
+                        ui.disable();
+                    };
+                    match self.focus_pick_deadline {
+                        Some(deadline) => {
+                            let remaining = deadline.saturating_duration_since(Instant::now());
+                            ui.label(format!("Focus it within {}s", remaining.as_secs() + 1));
+                        }
+                        None => {
+                            if ui
+                                .button("Pick")
+                                .on_hover_text("Pick the window focused a few seconds later")
+                                .clicked()
+                            {
+                                self.focus_pick_deadline = Some(Instant::now() + FOCUS_PICK_DELAY);
+                            }
+                        }
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        show_window_match(
+                            ui,
+                            &mut self.focus_guard.window,
+                            &self.windows,
+                            "focus_window_match",
+                            "Pick a window",
+                        );
+                    });
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if self.is_autoclicking {
+                        ui.disable();
+                    };
+                    let mut has_region = self.image_target.region.is_some();
+                    ui.checkbox(&mut has_region, "Image search region")
+                        .on_hover_text(
+                            "Search the target image there only, faster than the whole screen",
+                        );
+                    match (has_region, &mut self.image_target.region) {
+                        (true, Some(_)) | (false, None) => (),
+                        (true, None) => {
+                            self.image_target.region = Some(Region {
+                                width: 640u32,
+                                height: 480u32,
+                                ..Region::default()
+                            })
+                        }
+                        (false, Some(_)) => self.image_target.region = None,
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        ui.radio_value(
+                            &mut self.image_target.when_missing,
+                            MissingAction::Stop,
+                            "Stop",
+                        );
+                        ui.radio_value(
+                            &mut self.image_target.when_missing,
+                            MissingAction::Pause,
+                            "Pause",
+                        )
+                        .on_hover_text("What to do while the image is not found");
+                    });
+                });
+                if let Some(region) = &mut self.image_target.region {
+                    ui.horizontal(|ui| {
+                        if self.is_autoclicking {
+                            ui.disable();
+                        };
+                        ui.add(egui::DragValue::new(&mut region.x).prefix("X "));
+                        ui.add(egui::DragValue::new(&mut region.y).prefix("Y "));
+                        ui.add(
+                            egui::DragValue::new(&mut region.width)
+                                .range(1u32..=u32::from(u16::MAX))
+                                .prefix("W "),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut region.height)
+                                .range(1u32..=u32::from(u16::MAX))
+                                .prefix("H "),
+                        );
+                    });
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if self.is_autoclicking {
+                        ui.disable();
+                    };
+                    ui.checkbox(&mut self.pixel_condition_enabled, "Pixel condition")
+                        .on_hover_text("Click depending on the color of a screen pixel (X11)");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        egui::ComboBox::from_id_salt("pixel_trigger")
+                            .selected_text(self.pixel_condition.trigger.to_string())
+                            .show_ui(ui, |ui| {
+                                for trigger in [
+                                    PixelTrigger::WaitUntil,
+                                    PixelTrigger::While,
+                                    PixelTrigger::StopOnChange,
+                                ] {
+                                    ui.selectable_value(
+                                        &mut self.pixel_condition.trigger,
+                                        trigger,
+                                        trigger.to_string(),
+                                    );
+                                }
+                            });
+                    });
+                });
+                ui.horizontal(|ui| {
+                    if self.is_autoclicking || !self.pixel_condition_enabled {
+                        ui.disable();
+                    };
+                    if ui
+                        .button("Pick")
+                        .on_hover_text("Pick the pixel & its color under the mouse")
+                        .clicked()
+                    {
+                        pick_pixel = true;
+                    }
+                    ui.add(egui::DragValue::new(&mut self.pixel_condition.x).prefix("X "));
+                    ui.add(egui::DragValue::new(&mut self.pixel_condition.y).prefix("Y "));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.pixel_condition.tolerance).prefix("± "),
+                        )
+                        .on_hover_text("Largest difference per color channel");
+                        let color = &mut self.pixel_condition.color;
+                        let mut rgb = [color.r, color.g, color.b];
+                        ui.color_edit_button_srgb(&mut rgb);
+                        *color = Color::new(rgb[0], rgb[1], rgb[2]);
+                    });
+                });
+            });
+
+        if pick_pixel {
+            self.enter_pixel_picking(ctx);
+        }
+    }
+
+    pub fn show_stats_window(&mut self, ctx: &Context) {
+        let summary = self.click_stats.summary(Instant::now());
+        let as_ms = |duration: Duration| duration.as_secs_f64() * 1000f64;
+        let mut export_format: Option<HistoryFormat> = None;
+
+        egui::Window::new("Statistics")
+            .default_width(260f32)
+            .collapsible(true)
+            .open(&mut self.stats_window_open)
+            .show(ctx, |ui| {
+                egui::Grid::new("stats_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Clicks");
+                        ui.label(summary.click_counter.to_string());
+                        ui.end_row();
+                        ui.label("Run time");
+                        ui.label(format!("{:.1?}", summary.run_time));
+                        ui.end_row();
+                        ui.label("Clicks/sec");
+                        ui.label(format!("{:.2}", summary.clicks_per_second));
+                        ui.end_row();
+                        ui.label("Target interval");
+                        ui.label(format!("{:.1} ms", as_ms(self.click_stats.target_interval)));
+                        ui.end_row();
+                        ui.label("Mean deviation");
+                        ui.label(format!("{:.2} ms", as_ms(summary.mean_deviation)));
+                        ui.end_row();
+                        ui.label("p50/p95/p99 deviation");
+                        ui.label(format!(
+                            "{:.2}/{:.2}/{:.2} ms",
+                            as_ms(summary.p50_deviation),
+                            as_ms(summary.p95_deviation),
+                            as_ms(summary.p99_deviation)
+                        ));
+                        ui.end_row();
+                    });
+
+                // Sparkline of the recent intervals against the target
+                let points: PlotPoints<'_> = self
+                    .click_stats
+                    .intervals()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, interval)| [index as f64, as_ms(*interval)])
+                    .collect();
+                Plot::new("stats_intervals")
+                    .height(80f32)
+                    .show_axes([false, true])
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .include_y(0f64)
+                    .show(ui, |plot_ui| {
+                        plot_ui.hline(HLine::new(
+                            "Target (ms)",
+                            as_ms(self.click_stats.target_interval),
+                        ));
+                        plot_ui.line(Line::new("Interval (ms)", points));
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "History: {} events",
+                        self.click_history.entries().len()
+                    ));
+                    if self.is_autoclicking || self.click_history.is_empty() {
+                        ui.disable();
+                    };
+                    if ui.button("Export CSV").clicked() {
+                        export_format = Some(HistoryFormat::Csv);
+                    };
+                    if ui.button("Export JSONL").clicked() {
+                        export_format = Some(HistoryFormat::JsonLines);
+                    };
+                });
+                if let Some(status) = &self.history_export_status {
+                    ui.label(status);
+                }
+            });
+
+        if let Some(format) = export_format {
+            self.export_history(format);
+        }
+    }
 }
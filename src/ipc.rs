@@ -0,0 +1,330 @@
+use rusty_autoclicker::types::StopReason;
+use serde::{Deserialize, Serialize};
+
+/// Command sent by a client, one JSON object per line, e.g. `{"cmd":"set_interval","ms":250}`
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum IpcCommand {
+    Start,
+    Stop,
+    Status,
+    SetInterval { ms: u64 },
+    LoadProfile { name: String },
+}
+
+/// Message sent to clients, one JSON object per line
+/// `Ok`, `Error` and `Status` answer a command, the others are streamed to every client
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IpcEvent {
+    Ok,
+    Error {
+        message: String,
+    },
+    Status {
+        is_autoclicking: bool,
+        click_counter: u64,
+        interval_ms: u64,
+        active_profile: Option<String>,
+    },
+    Started,
+    Clicks {
+        count: u64,
+    },
+    Stopped {
+        reason: StopReason,
+        count: u64,
+    },
+}
+
+impl IpcEvent {
+    /// Whether the event answers a command rather than being streamed
+    pub fn is_reply(&self) -> bool {
+        matches!(
+            self,
+            IpcEvent::Ok | IpcEvent::Error { .. } | IpcEvent::Status { .. }
+        )
+    }
+}
+
+#[cfg(unix)]
+pub use unix::*;
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        fs,
+        io::{self, BufRead, BufReader, Write},
+        net::Shutdown,
+        os::unix::{
+            fs::{DirBuilderExt, PermissionsExt},
+            net::{UnixListener, UnixStream},
+        },
+        path::{Path, PathBuf},
+        sync::{
+            Arc, Mutex,
+            mpsc::{self, Receiver, Sender, SyncSender},
+        },
+        thread,
+    };
+
+    use super::{IpcCommand, IpcEvent};
+    use crate::defines::{CONFIG_DIR_NAME, IPC_CLIENT_BACKLOG, IPC_SOCKET_NAME, IPC_WRITE_TIMEOUT};
+
+    /// Identifier of a connected client
+    pub type ClientId = u64;
+
+    /// Connected clients, with the queue of lines their writer thread sends
+    type Clients = Arc<Mutex<Vec<(ClientId, SyncSender<String>)>>>;
+
+    /// Directory of the control socket: the runtime directory if there is one, private to the
+    /// user, or else a directory of the user in the shared temp directory
+    fn socket_dir() -> PathBuf {
+        dirs::runtime_dir().unwrap_or_else(|| {
+            let user = std::env::var("USER")
+                .or_else(|_| std::env::var("LOGNAME"))
+                .unwrap_or_default();
+            std::env::temp_dir().join(format!("{CONFIG_DIR_NAME}-{user}"))
+        })
+    }
+
+    /// Path of the control socket
+    pub fn socket_path() -> PathBuf {
+        socket_dir().join(IPC_SOCKET_NAME)
+    }
+
+    /// Create the directory of the control socket, only accessible by the user
+    /// Fails if it exists & other users can access it, or it is not a directory, e.g. a symlink
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory
+    fn create_private_dir(dir: &Path) -> io::Result<()> {
+        match fs::DirBuilder::new().mode(0o700).create(dir) {
+            Ok(()) => return Ok(()),
+            Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+            Err(_) => (),
+        }
+        let metadata = fs::symlink_metadata(dir)?;
+        if !metadata.is_dir() || metadata.permissions().mode() & 0o077 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is not a private directory", dir.display()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Local control server listening on a Unix domain socket
+    pub struct IpcServer {
+        path: PathBuf,
+        commands: Receiver<(ClientId, IpcCommand)>,
+        clients: Clients,
+    }
+
+    impl IpcServer {
+        /// Listen on the control socket, failing if another instance is already listening
+        pub fn start() -> io::Result<Self> {
+            let path = socket_path();
+            if dirs::runtime_dir().is_none() {
+                create_private_dir(&socket_dir())?;
+            }
+            if UnixStream::connect(&path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("{} is used by another instance", path.display()),
+                ));
+            }
+            // Remove the socket left behind by a previous instance
+            let _ = fs::remove_file(&path);
+
+            let listener = UnixListener::bind(&path)?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+            let (sender, commands) = mpsc::channel();
+            let clients: Clients = Arc::default();
+            let listener_clients = clients.clone();
+            thread::spawn(move || accept_clients(listener, sender, listener_clients));
+
+            Ok(Self {
+                path,
+                commands,
+                clients,
+            })
+        }
+
+        /// Take the next pending command, if any
+        pub fn try_recv(&self) -> Option<(ClientId, IpcCommand)> {
+            self.commands.try_recv().ok()
+        }
+
+        /// Send an event to a single client
+        ///
+        /// # Arguments
+        ///
+        /// * `client_id` - The client to send to
+        /// * `event` - The event to send
+        pub fn reply(&self, client_id: ClientId, event: &IpcEvent) {
+            self.send(event, |id| id == client_id);
+        }
+
+        /// Send an event to every client
+        ///
+        /// # Arguments
+        ///
+        /// * `event` - The event to send
+        pub fn broadcast(&self, event: &IpcEvent) {
+            self.send(event, |_| true);
+        }
+
+        /// Queue an event for the matching clients without blocking
+        /// Clients that disconnected or do not read their events fast enough are dropped
+        ///
+        /// # Arguments
+        ///
+        /// * `event` - The event to send
+        /// * `filter` - Which clients to send to
+        fn send(&self, event: &IpcEvent, filter: impl Fn(ClientId) -> bool) {
+            let Ok(line) = serde_json::to_string(event) else {
+                return;
+            };
+            let mut clients = self.clients.lock().unwrap();
+            clients.retain(|(id, writer)| !filter(*id) || writer.try_send(line.clone()).is_ok());
+        }
+    }
+
+    impl Drop for IpcServer {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    /// Accept clients and spawn a reader thread for each
+    ///
+    /// # Arguments
+    ///
+    /// * `listener` - The listening socket
+    /// * `sender` - Where to forward the parsed commands
+    /// * `clients` - The connected clients
+    fn accept_clients(
+        listener: UnixListener,
+        sender: Sender<(ClientId, IpcCommand)>,
+        clients: Clients,
+    ) {
+        for (client_id, stream) in (0..).zip(listener.incoming()) {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let Ok(writer) = stream.try_clone() else {
+                continue;
+            };
+            let (line_sender, lines) = mpsc::sync_channel(IPC_CLIENT_BACKLOG);
+            clients.lock().unwrap().push((client_id, line_sender));
+            thread::spawn(move || write_lines(writer, lines));
+
+            let sender = sender.clone();
+            let clients = clients.clone();
+            thread::spawn(move || read_commands(client_id, stream, sender, clients));
+        }
+    }
+
+    /// Parse newline-delimited JSON commands until the client disconnects
+    ///
+    /// # Arguments
+    ///
+    /// * `client_id` - The client reading from
+    /// * `stream` - The client socket
+    /// * `sender` - Where to forward the parsed commands
+    /// * `clients` - The connected clients
+    fn read_commands(
+        client_id: ClientId,
+        stream: UnixStream,
+        sender: Sender<(ClientId, IpcCommand)>,
+        clients: Clients,
+    ) {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<IpcCommand>(&line) {
+                Ok(command) => {
+                    if sender.send((client_id, command)).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let event = IpcEvent::Error {
+                        message: format!("Invalid command: {e}"),
+                    };
+                    let line = serde_json::to_string(&event).unwrap_or_default();
+                    let mut clients = clients.lock().unwrap();
+                    clients.retain(|(id, writer)| {
+                        *id != client_id || writer.try_send(line.clone()).is_ok()
+                    });
+                }
+            }
+        }
+
+        clients.lock().unwrap().retain(|(id, _)| *id != client_id);
+    }
+
+    /// Write the queued lines to a client until it is dropped or a write fails
+    /// The socket is then shut down, which also ends the reader thread of the client
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The client socket
+    /// * `lines` - The lines to write
+    fn write_lines(mut stream: UnixStream, lines: Receiver<String>) {
+        // A client that stops reading is given up on rather than blocking this thread forever
+        if stream.set_write_timeout(Some(IPC_WRITE_TIMEOUT)).is_err() {
+            return;
+        }
+        for line in lines {
+            if writeln!(stream, "{line}").is_err() {
+                break;
+            }
+        }
+        let _ = stream.shutdown(Shutdown::Both);
+    }
+
+    /// Send a command to the running instance and print its answer
+    /// Without a command, print the streamed events until the instance exits
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to send, if any
+    pub fn run_client(command: Option<&IpcCommand>) -> io::Result<()> {
+        let path = socket_path();
+        let mut stream = UnixStream::connect(&path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("could not connect to {}: {e}", path.display()),
+            )
+        })?;
+
+        if let Some(command) = command {
+            let line = serde_json::to_string(command).map_err(io::Error::other)?;
+            writeln!(stream, "{line}")?;
+        }
+
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            println!("{line}");
+
+            let event: IpcEvent = serde_json::from_str(&line).map_err(io::Error::other)?;
+            if command.is_some() && event.is_reply() {
+                return match event {
+                    IpcEvent::Error { message } => Err(io::Error::other(message)),
+                    _ => Ok(()),
+                };
+            }
+        }
+
+        Ok(())
+    }
+}
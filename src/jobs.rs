@@ -0,0 +1,463 @@
+//! Extra click jobs running alongside the main one, each with its own settings & hotkey
+
+use std::time::{Duration, Instant};
+
+use device_query::Keycode;
+use rdev::Button;
+use rusty_autoclicker::{
+    engine::{
+        ClickJob, JobEvent,
+        backend::{InputBackend, NullBackend},
+        clock::SystemClock,
+        history::RecordingBackend,
+        runner::{JobRunner, step_due_runners},
+        stats::ClickStats,
+    },
+    types::{ClickButton, ClickInfo, ClickPosition, ClickType, CoordMode, StopReason},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::{
+    RustyAutoClickerApp,
+    defines::*,
+    fields::NumberField,
+    profiles::{Profile, parse_keycode, range_error},
+};
+
+/// An extra click job, saved with the profile
+/// The app mode, movement delay & input backend are shared with the main job
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobSpec {
+    pub name: String,
+    pub interval_ms: u64,
+    pub click_btn: ClickButton,
+    pub click_type: ClickType,
+    pub click_position: ClickPosition,
+    /// The click coordinates in pixels, with [`ClickPosition::Coord`]
+    pub click_x: i64,
+    pub click_y: i64,
+    /// Stop after this amount of clicks, 0 = never
+    pub click_amount: u64,
+    /// Starts & stops the job, stored by name as `device_query::Keycode` is not serializable
+    pub hotkey: Option<String>,
+}
+
+impl Default for JobSpec {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            interval_ms: DEFAULT_JOB_INTERVAL_MS,
+            click_btn: ClickButton::Mouse(Button::Left),
+            click_type: ClickType::Single,
+            click_position: ClickPosition::Mouse,
+            click_x: 0,
+            click_y: 0,
+            click_amount: 0,
+            hotkey: None,
+        }
+    }
+}
+
+impl JobSpec {
+    /// Why the job cannot be started, if it cannot, e.g. a number out of range in an edited
+    /// profile
+    pub fn invalid_input(&self) -> Option<String> {
+        let uses_coord = self.click_position == ClickPosition::Coord;
+        [
+            (
+                "Interval",
+                range_error(self.interval_ms, JOB_INTERVAL_MS_RANGE),
+            ),
+            ("Amount", range_error(self.click_amount, CLICK_AMOUNT_RANGE)),
+            (
+                "X",
+                range_error(self.click_x, JOB_COORD_RANGE).filter(|_| uses_coord),
+            ),
+            (
+                "Y",
+                range_error(self.click_y, JOB_COORD_RANGE).filter(|_| uses_coord),
+            ),
+        ]
+        .into_iter()
+        .find_map(|(name, error)| Some(format!("{name}: {}", error?)))
+    }
+
+    /// Build the click job, with the default shared settings
+    pub fn click_job(&self) -> ClickJob {
+        ClickJob {
+            click_info: ClickInfo {
+                click_btn: self.click_btn,
+                click_coord: (self.click_x as f64, self.click_y as f64),
+                click_position: self.click_position,
+                click_type: self.click_type,
+                coord_mode: CoordMode::Absolute,
+            },
+            interval: Duration::from_millis(self.interval_ms),
+            click_amount: self.click_amount,
+            ..ClickJob::default()
+        }
+    }
+}
+
+/// The state of an extra job while the app runs
+pub struct JobState {
+    /// The running job, `None` when stopped
+    pub runner: Option<JobRunner>,
+    /// The clicks of the last run, kept once it stopped
+    pub click_counter: u64,
+    pub stop_reason: Option<StopReason>,
+    /// The statistics of the current or last run
+    pub stats: ClickStats,
+    /// The numbers of the job as typed, copied to the job while they are valid
+    pub click_amount: NumberField<u64>,
+    pub click_x: NumberField<i64>,
+    pub click_y: NumberField<i64>,
+}
+
+impl JobState {
+    /// The state of a stopped job, showing its numbers
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - The job
+    pub fn new(spec: &JobSpec) -> Self {
+        Self {
+            runner: None,
+            click_counter: 0,
+            stop_reason: None,
+            stats: ClickStats::default(),
+            click_amount: NumberField::new(spec.click_amount, CLICK_AMOUNT_RANGE),
+            click_x: NumberField::new(spec.click_x, JOB_COORD_RANGE),
+            click_y: NumberField::new(spec.click_y, JOB_COORD_RANGE),
+        }
+    }
+
+    /// Why the numbers typed for the job are invalid, if they are
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - The job
+    pub fn invalid_input(&self, spec: &JobSpec) -> Option<String> {
+        let uses_coord = spec.click_position == ClickPosition::Coord;
+        [
+            ("Amount", self.click_amount.error()),
+            ("X", self.click_x.error().filter(|_| uses_coord)),
+            ("Y", self.click_y.error().filter(|_| uses_coord)),
+        ]
+        .into_iter()
+        .find_map(|(name, error)| Some(format!("{name}: {}", error?)))
+    }
+
+    /// The clicks of the current or last run
+    pub fn click_counter(&self) -> u64 {
+        self.runner
+            .as_ref()
+            .map_or(self.click_counter, |runner| runner.click_counter())
+    }
+}
+
+impl RustyAutoClickerApp {
+    /// Add a job with the default settings
+    pub fn add_job(&mut self) {
+        self.sync_job_states();
+        let spec = JobSpec {
+            name: format!("Job {}", self.jobs.len() + 1),
+            ..JobSpec::default()
+        };
+        self.job_states.push(JobState::new(&spec));
+        self.jobs.push(spec);
+    }
+
+    /// Add the states of the jobs that have none, e.g. after loading a profile
+    pub fn sync_job_states(&mut self) {
+        self.job_states.truncate(self.jobs.len());
+        for spec in &self.jobs[self.job_states.len()..] {
+            self.job_states.push(JobState::new(spec));
+        }
+    }
+
+    /// Stop & remove a job
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the job
+    pub fn remove_job(&mut self, index: usize) {
+        self.jobs.remove(index);
+        if index < self.job_states.len() {
+            self.job_states.remove(index);
+        }
+        if self.setting_job_key == Some(index) {
+            self.setting_job_key = None;
+        }
+    }
+
+    /// Start a stopped job or stop a running one
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the job
+    pub fn toggle_job(&mut self, index: usize) {
+        self.sync_job_states();
+        if self.job_states[index].runner.is_some() {
+            self.stop_job(index, StopReason::User);
+            return;
+        }
+
+        let spec = &self.jobs[index];
+        // Do not start clicking with a number that is not the one shown
+        if let Some(e) = self.job_states[index]
+            .invalid_input(spec)
+            .or_else(|| spec.invalid_input())
+        {
+            error!("Not starting {}: {e}", spec.name);
+            self.input_error = Some(format!("{}: {e}", spec.name));
+            return;
+        }
+        let main_job = Profile::from(&*self).click_job();
+        let job = ClickJob {
+            app_mode: main_job.app_mode,
+            movement_delay: main_job.movement_delay,
+            backend: self.backend_kind,
+            seed: self.humanlike_seed,
+            max_failures: self.max_consecutive_failures,
+            ..spec.click_job()
+        };
+        // Do not start clicking off screen, e.g. on a monitor that was unplugged
+        if let Err(e) = job.check_target() {
+            error!("Not starting {}: {e}", spec.name);
+            self.input_error = Some(format!("{}: {e}", spec.name));
+            return;
+        }
+
+        info!("Starting {}", spec.name);
+        let now = Instant::now();
+        let state = &mut self.job_states[index];
+        state.stats.start(job.interval, now);
+        state.runner = Some(JobRunner::new(job, &NullBackend, now));
+        state.stop_reason = None;
+    }
+
+    /// Stop a running job, keeping its click counter
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the job
+    /// * `stop_reason` - Why the job stopped
+    fn stop_job(&mut self, index: usize, stop_reason: StopReason) {
+        let state = &mut self.job_states[index];
+        if let Some(runner) = state.runner.take() {
+            info!(
+                clicks = runner.click_counter(),
+                reason = ?stop_reason,
+                "Stopped {}",
+                self.jobs[index].name
+            );
+            state.click_counter = runner.click_counter();
+            state.stop_reason = Some(stop_reason);
+            state.stats.stop(Instant::now());
+        }
+    }
+
+    /// Toggle the jobs whose hotkey was pressed, then send the clicks that are due, earliest first
+    /// Each job sends at most one click per frame
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys currently pressed
+    pub fn update_jobs(&mut self, keys: &[Keycode]) {
+        self.sync_job_states();
+
+        // Set the hotkey of a job once a key is released, the jobs being toggled by hotkey again
+        // once it is set
+        if let Some(index) = self.setting_job_key {
+            if let Some(key) = self
+                .keys_pressed
+                .iter()
+                .flatten()
+                .find(|key| !keys.contains(key))
+                .copied()
+            {
+                match self.hotkey_use(key, index) {
+                    Some(used_by) => {
+                        self.input_error = Some(format!("{key} already {used_by}"));
+                    }
+                    None => {
+                        self.jobs[index].hotkey = Some(key.to_string());
+                        self.setting_job_key = None;
+                    }
+                }
+            }
+        } else {
+            for index in 0..self.jobs.len() {
+                if let Some(key) = parse_keycode(&self.jobs[index].hotkey)
+                    && self.key_just_pressed(keys, key)
+                {
+                    self.toggle_job(index);
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let is_due = |state: &JobState| state.runner.as_ref().is_some_and(|r| r.due() <= now);
+        if !self.job_states.iter().any(is_due) {
+            return;
+        }
+
+        // Dry runs send the events nowhere
+        let mut null_backend = NullBackend;
+        let backend: &mut dyn InputBackend = if self.dry_run {
+            &mut null_backend
+        } else if self.ensure_input_backend() {
+            match self.input_backend.as_mut() {
+                Some((_, backend)) => backend.as_mut(),
+                None => return,
+            }
+        } else {
+            return;
+        };
+
+        // Record the events in the click history, like the ones of the main job
+        let mut recorder = RecordingBackend::new(backend, &mut self.click_history, self.app_mode);
+        let runners = self
+            .job_states
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, state)| Some((index, state.runner.as_mut()?)));
+        let mut clicks = Vec::new();
+        let stopped = step_due_runners(runners, &mut recorder, &mut SystemClock, |index, event| {
+            if let JobEvent::Clicked(_) = event {
+                clicks.push((index, Instant::now()));
+            }
+        });
+        for (index, now) in clicks {
+            self.job_states[index].stats.record(now);
+        }
+        for (index, stop_reason) in stopped {
+            self.stop_job(index, stop_reason);
+        }
+    }
+
+    /// What a key is already the hotkey of, if anything but the job
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key
+    /// * `index` - The index of the job getting the key
+    fn hotkey_use(&self, key: Keycode, index: usize) -> Option<String> {
+        if self.key_autoclick == Some(key) {
+            return Some("starts autoclicking".to_owned());
+        }
+        if self.key_set_coord == Some(key) {
+            return Some("sets the coordinates".to_owned());
+        }
+        self.jobs
+            .iter()
+            .enumerate()
+            .find(|(other, job)| *other != index && parse_keycode(&job.hotkey) == Some(key))
+            .map(|(_, job)| format!("toggles {}", job.name))
+    }
+
+    /// Stop every running job
+    pub fn stop_all_jobs(&mut self) {
+        for index in 0..self.job_states.len() {
+            self.stop_job(index, StopReason::User);
+        }
+    }
+
+    /// Whether any extra job is running
+    pub fn is_running_jobs(&self) -> bool {
+        self.job_states.iter().any(|state| state.runner.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_out_of_range_are_invalid() {
+        let mut spec = JobSpec {
+            click_amount: u64::MAX,
+            ..JobSpec::default()
+        };
+        assert_eq!(
+            spec.invalid_input().as_deref(),
+            Some("Amount: Must be between 0 and 1000000000000")
+        );
+
+        // The coordinates only matter when clicking at them
+        spec.click_amount = 0;
+        spec.click_x = i64::MAX;
+        assert_eq!(spec.invalid_input(), None);
+        spec.click_position = ClickPosition::Coord;
+        assert_eq!(
+            spec.invalid_input().as_deref(),
+            Some("X: Must be between -100000 and 100000")
+        );
+    }
+
+    #[test]
+    fn jobs_keep_clicking_while_a_hotkey_is_set() {
+        let mut app = RustyAutoClickerApp {
+            dry_run: true,
+            ..RustyAutoClickerApp::default()
+        };
+        app.add_job();
+        app.add_job();
+        app.toggle_job(0);
+        app.setting_job_key = Some(1);
+
+        app.update_jobs(&[]);
+
+        assert_eq!(app.job_states[0].click_counter(), 1);
+        assert_eq!(app.setting_job_key, Some(1));
+    }
+
+    #[test]
+    fn hotkeys_in_use_are_rejected() {
+        let mut app = RustyAutoClickerApp::default();
+        app.add_job();
+        app.add_job();
+        app.jobs[0].hotkey = Some(Keycode::F7.to_string());
+
+        // Released keys, already used by the main job then by the first job
+        for key in [HOTKEY_AUTOCLICK.unwrap(), Keycode::F7] {
+            app.setting_job_key = Some(1);
+            app.keys_pressed = Some(vec![key]);
+
+            app.update_jobs(&[]);
+
+            assert_eq!(app.jobs[1].hotkey, None);
+            assert_eq!(app.setting_job_key, Some(1));
+            assert!(app.input_error.take().is_some());
+        }
+
+        // A job may keep its own hotkey
+        app.setting_job_key = Some(0);
+        app.keys_pressed = Some(vec![Keycode::F7]);
+        app.update_jobs(&[]);
+        assert_eq!(app.setting_job_key, None);
+        assert_eq!(app.input_error, None);
+    }
+
+    #[test]
+    fn typed_numbers_are_checked_before_starting() {
+        let spec = JobSpec {
+            click_position: ClickPosition::Coord,
+            ..JobSpec::default()
+        };
+        let mut state = JobState::new(&spec);
+        assert_eq!(state.invalid_input(&spec), None);
+
+        state.click_y.set_text("1.5");
+
+        assert_eq!(
+            state.invalid_input(&spec).as_deref(),
+            Some("Y: Not a whole number")
+        );
+        // The last valid number is kept
+        assert_eq!(state.click_y.value(), 0);
+    }
+}
@@ -0,0 +1,10 @@
+//! Rusty AutoClicker engine, shared by the graphical interface and the command line
+//!
+//! See [`engine`] to embed the autoclicker in your own program.
+
+#![forbid(unsafe_code)]
+#![cfg_attr(not(debug_assertions), deny(warnings))] // Forbid warnings in release builds
+#![warn(clippy::all, rust_2018_idioms)]
+
+pub mod engine;
+pub mod types;
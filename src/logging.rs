@@ -0,0 +1,196 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write as _},
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use tracing::{
+    Event, Level, Subscriber,
+    field::{Field, Visit},
+    level_filters::LevelFilter,
+};
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{EnvFilter, Layer, layer::Context, prelude::*};
+
+use crate::defines::*;
+
+/// A log event kept in memory for the log panel
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct LogEntry {
+    /// Time since logging started
+    pub elapsed: Duration,
+    pub level: Level,
+    /// Module the event was logged from
+    pub target: String,
+    /// The message followed by the other fields as `key=value`
+    pub message: String,
+}
+
+/// The last `LOG_BUFFER_CAPACITY` log events, shared with the GUI
+#[derive(Clone, Debug, Default)]
+pub struct LogBuffer(Arc<Mutex<VecDeque<LogEntry>>>);
+
+impl LogBuffer {
+    /// Lock the buffer, the entries being oldest first
+    /// The entries are always left whole, so they stay usable after a thread panicked holding it
+    fn lock(&self) -> MutexGuard<'_, VecDeque<LogEntry>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Copy the entries at least as severe as a level, oldest first, without holding the lock
+    /// while they are shown
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The least severe level to copy
+    pub fn entries(&self, level: LevelFilter) -> Vec<LogEntry> {
+        self.lock()
+            .iter()
+            .filter(|entry| entry.level <= level)
+            .cloned()
+            .collect()
+    }
+
+    /// Remove all entries
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Add an entry, dropping the oldest one if full
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The entry to add
+    fn push(&self, entry: LogEntry) {
+        let mut entries = self.lock();
+        if entries.len() >= LOG_BUFFER_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
+/// Layer copying every event into a `LogBuffer`
+struct BufferLayer {
+    buffer: LogBuffer,
+    start: Instant,
+}
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        self.buffer.push(LogEntry {
+            elapsed: self.start.elapsed(),
+            level: *metadata.level(),
+            target: metadata.target().to_owned(),
+            message: visitor.message,
+        });
+    }
+}
+
+/// Format the fields of an event as `message key=value ...`
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.message.is_empty() {
+            self.message.push(' ');
+        }
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.message, "{}={value:?}", field.name());
+        }
+    }
+}
+
+/// Directory holding the log files, e.g. `~/.local/share/rusty-autoclicker/logs` on Linux
+pub fn logs_dir() -> io::Result<PathBuf> {
+    dirs::data_dir()
+        .map(|dir| dir.join(CONFIG_DIR_NAME).join(LOGS_DIR_NAME))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No data directory found"))
+}
+
+/// Install the global subscriber, writing to a daily rotated file and to the returned buffer
+/// The filter defaults to `DEFAULT_LOG_FILTER` and can be overridden with `RUST_LOG`
+/// Keep the guard alive until exit, dropping it flushes the file
+pub fn init() -> (LogBuffer, Option<WorkerGuard>) {
+    let buffer = LogBuffer::default();
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let buffer_layer = BufferLayer {
+        buffer: buffer.clone(),
+        start: Instant::now(),
+    };
+
+    let file_appender = logs_dir().and_then(|dir| {
+        fs::create_dir_all(&dir)?;
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix(LOG_FILE_SUFFIX)
+            .max_log_files(LOG_MAX_FILES)
+            .build(dir)
+            .map_err(io::Error::other)
+    });
+    let (file_layer, guard, file_error) = match file_appender {
+        Ok(file_appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(file_appender);
+            let file_layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(writer);
+            (Some(file_layer), Some(guard), None)
+        }
+        Err(e) => (None, None, Some(e)),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(buffer_layer)
+        .with(file_layer)
+        .init();
+
+    if let Some(e) = file_error {
+        tracing::warn!("Could not open the log file: {e}");
+    }
+
+    (buffer, guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_is_usable_after_a_panic() {
+        let buffer = LogBuffer::default();
+        let poisoner = buffer.clone();
+        std::thread::spawn(move || {
+            let _entries = poisoner.lock();
+            panic!("Poison the buffer");
+        })
+        .join()
+        .unwrap_err();
+
+        buffer.push(LogEntry {
+            elapsed: Duration::ZERO,
+            level: Level::INFO,
+            target: "test".to_owned(),
+            message: "Still logging".to_owned(),
+        });
+
+        assert_eq!(buffer.entries(LevelFilter::INFO).len(), 1);
+    }
+}
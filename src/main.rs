@@ -1,4 +1,4 @@
-#![forbid(unsafe_code)]
+#![deny(unsafe_code)] // Only to attach to the console on Windows
 #![cfg_attr(not(debug_assertions), deny(warnings))] // Forbid warnings in release builds
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] //Hide console window in release builds on Windows, this blocks stdout.
@@ -6,9 +6,16 @@
 use eframe::egui;
 
 mod app;
+mod cli;
 mod defines;
+mod fields;
 mod gui;
-mod types;
+mod ipc;
+mod jobs;
+mod logging;
+mod profiles;
+mod settings;
+mod timeline;
 mod utils;
 
 use crate::{
@@ -19,9 +26,24 @@ use crate::{
 
 // When compiling natively
 #[cfg(not(target_arch = "wasm32"))]
-fn main() {
+fn main() -> std::process::ExitCode {
     use eframe::egui::ViewportBuilder;
 
+    // Print the output of commands to the console they were run from
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    #[cfg(all(windows, not(debug_assertions)))]
+    if !args.is_empty() {
+        attach_parent_console();
+    }
+
+    // Keep the guard until exit so that the log file is flushed
+    let (log_buffer, _log_guard) = logging::init();
+
+    // Run headless if a command was given
+    if let Some(exit_code) = cli::main(&args) {
+        return exit_code;
+    }
+
     let native_options = eframe::NativeOptions {
         renderer: eframe::Renderer::Wgpu,
         viewport: ViewportBuilder::default()
@@ -39,7 +61,7 @@ fn main() {
         native_options,
         Box::new(|cc| {
             cc.egui_ctx.set_visuals(egui::Visuals::dark());
-            Ok(Box::new(RustyAutoClickerApp::new(cc)))
+            Ok(Box::new(RustyAutoClickerApp::new(cc, log_buffer)))
         }),
     ) {
         native_dialog::DialogBuilder::message()
@@ -54,5 +76,22 @@ fn main() {
             .alert()
             .show()
             .unwrap();
+        return std::process::ExitCode::FAILURE;
     };
+
+    std::process::ExitCode::SUCCESS
+}
+
+/// Attach to the console of the parent process, e.g. a terminal, as release builds on Windows
+/// have no console of their own
+/// Nothing happens when started without a console, e.g. from the Explorer
+#[cfg(all(windows, not(debug_assertions)))]
+#[allow(unsafe_code)]
+fn attach_parent_console() {
+    use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+
+    // SAFETY: AttachConsole takes no pointer & only fails when there is no console to attach to
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
 }
@@ -0,0 +1,345 @@
+use std::{
+    fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use device_query::Keycode;
+use rusty_autoclicker::{
+    engine::{
+        ClickJob, TargetPoint,
+        pixel::PixelCondition,
+        sequence::Sequence,
+        template::ImageTarget,
+        window::{FocusGuard, WindowMatch},
+    },
+    types::{AppMode, ClickButton, ClickInfo, ClickPosition, ClickType, CoordMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    RustyAutoClickerApp,
+    defines::*,
+    fields::{Number, NumberField},
+    jobs::JobSpec,
+    settings,
+    utils::{coord_range, duration_of, format_coord},
+};
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ProfileAction {
+    SaveAs,
+    Duplicate,
+}
+
+/// Snapshot of every user-facing setting that can be saved under a name
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)] // if we add new fields, give them default values when loading old profiles
+pub struct Profile {
+    // Click interval
+    pub hr: u64,
+    pub min: u64,
+    pub sec: f64,
+    pub ms: f64,
+
+    // Movement delay
+    pub movement_sec: f64,
+    pub movement_ms: f64,
+
+    // Click amount & coordinates
+    pub click_amount: u64,
+    pub click_x: f64,
+    pub click_y: f64,
+    pub points: Vec<TargetPoint>,
+
+    // Hotkeys, stored by name as `device_query::Keycode` is not serializable
+    pub key_autoclick: Option<String>,
+    pub key_set_coord: Option<String>,
+
+    // Enums
+    pub app_mode: AppMode,
+    pub click_btn: ClickButton,
+    pub click_type: ClickType,
+    pub click_position: ClickPosition,
+    pub coord_mode: CoordMode,
+    pub window_match: WindowMatch,
+    pub image_target: ImageTarget,
+
+    // Only click while a window is focused
+    pub focus_guard_enabled: bool,
+    pub focus_guard: FocusGuard,
+
+    // Wait for, click while or stop when a pixel has a color
+    pub pixel_condition_enabled: bool,
+    pub pixel_condition: PixelCondition,
+
+    // Extra jobs running alongside the main one
+    pub jobs: Vec<JobSpec>,
+
+    // Timeline of actions
+    pub sequence: Sequence,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::from(&RustyAutoClickerApp::default())
+    }
+}
+
+impl From<&RustyAutoClickerApp> for Profile {
+    fn from(app: &RustyAutoClickerApp) -> Self {
+        Self {
+            hr: app.hr.value(),
+            min: app.min.value(),
+            sec: app.sec.value(),
+            ms: app.ms.value(),
+            movement_sec: app.movement_sec.value(),
+            movement_ms: app.movement_ms.value(),
+            click_amount: app.click_amount.value(),
+            click_x: app.click_x.value(),
+            click_y: app.click_y.value(),
+            points: app.points.clone(),
+            key_autoclick: app.key_autoclick.map(|key| key.to_string()),
+            key_set_coord: app.key_set_coord.map(|key| key.to_string()),
+            app_mode: app.app_mode,
+            click_btn: app.click_btn,
+            click_type: app.click_type,
+            click_position: app.click_position,
+            coord_mode: app.coord_mode,
+            window_match: app.window_match.clone(),
+            image_target: app.image_target.clone(),
+            focus_guard_enabled: app.focus_guard_enabled,
+            focus_guard: app.focus_guard.clone(),
+            pixel_condition_enabled: app.pixel_condition_enabled,
+            pixel_condition: app.pixel_condition,
+            jobs: app.jobs.clone(),
+            sequence: app.sequence.clone(),
+        }
+    }
+}
+
+impl Profile {
+    /// Copy the profile settings onto the app
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - The app to apply the settings to
+    pub fn apply(&self, app: &mut RustyAutoClickerApp) {
+        // Out of range numbers, e.g. edited by hand, are shown as invalid inputs
+        app.hr.set(self.hr);
+        app.min.set(self.min);
+        app.sec.set(self.sec);
+        app.ms.set(self.ms);
+        app.movement_sec.set(self.movement_sec);
+        app.movement_ms.set(self.movement_ms);
+        app.click_amount.set(self.click_amount);
+        app.click_x.set_range(coord_range(self.coord_mode));
+        app.click_y.set_range(coord_range(self.coord_mode));
+        app.click_x
+            .set_text(format_coord(self.click_x, self.coord_mode));
+        app.click_y
+            .set_text(format_coord(self.click_y, self.coord_mode));
+        app.points = self.points.clone();
+        // Unknown key names fall back to the default hotkeys
+        app.key_autoclick = parse_keycode(&self.key_autoclick).or(HOTKEY_AUTOCLICK);
+        app.key_set_coord = parse_keycode(&self.key_set_coord).or(HOTKEY_SET_COORD);
+        app.app_mode = self.app_mode;
+        app.click_btn = self.click_btn;
+        app.click_type = self.click_type;
+        app.click_position = self.click_position;
+        app.coord_mode = self.coord_mode;
+        app.window_match = self.window_match.clone();
+        app.image_target = self.image_target.clone();
+        app.focus_guard_enabled = self.focus_guard_enabled;
+        app.focus_guard = self.focus_guard.clone();
+        app.pixel_condition_enabled = self.pixel_condition_enabled;
+        app.pixel_condition = self.pixel_condition;
+        // The jobs of the previous profile stop
+        app.jobs = self.jobs.clone();
+        app.job_states.clear();
+        app.setting_job_key = None;
+        app.sequence = self.sequence.clone();
+        app.sequence_player = None;
+        app.selected_step = None;
+    }
+
+    /// The first number out of its range, with the reason, if any, e.g. in a profile edited by hand
+    /// The same checks as the number inputs of the app
+    pub fn invalid_input(&self) -> Option<String> {
+        let uses_coord = self.click_position == ClickPosition::Coord && self.points.is_empty();
+        let coord_range = coord_range(self.coord_mode);
+        [
+            ("Hours", range_error(self.hr, HR_RANGE)),
+            ("Minutes", range_error(self.min, MIN_RANGE)),
+            ("Seconds", range_error(self.sec, SEC_RANGE)),
+            ("Milliseconds", range_error(self.ms, MS_RANGE)),
+            (
+                "Movement seconds",
+                range_error(self.movement_sec, MOVEMENT_SEC_RANGE),
+            ),
+            (
+                "Movement milliseconds",
+                range_error(self.movement_ms, MOVEMENT_MS_RANGE),
+            ),
+            (
+                "Click amount",
+                range_error(self.click_amount, CLICK_AMOUNT_RANGE),
+            ),
+            (
+                "X",
+                range_error(self.click_x, coord_range.clone()).filter(|_| uses_coord),
+            ),
+            (
+                "Y",
+                range_error(self.click_y, coord_range).filter(|_| uses_coord),
+            ),
+        ]
+        .into_iter()
+        .find_map(|(name, error)| Some(format!("{name}: {}", error?)))
+    }
+
+    /// Build the click job described by the profile
+    pub fn click_job(&self) -> ClickJob {
+        ClickJob {
+            app_mode: self.app_mode,
+            click_info: ClickInfo {
+                click_btn: self.click_btn,
+                click_coord: (self.click_x, self.click_y),
+                click_position: self.click_position,
+                click_type: self.click_type,
+                coord_mode: self.coord_mode,
+            },
+            interval: duration_of(self.hr, self.min, self.sec, self.ms),
+            click_amount: self.click_amount,
+            movement_delay: duration_of(0u64, 0u64, self.movement_sec, self.movement_ms),
+            window: (self.coord_mode == CoordMode::Window).then(|| self.window_match.clone()),
+            image: (self.coord_mode == CoordMode::Image).then(|| self.image_target.clone()),
+            focus_guard: self.focus_guard_enabled.then(|| self.focus_guard.clone()),
+            pixel_condition: self.pixel_condition_enabled.then_some(self.pixel_condition),
+            points: self.points.clone(),
+            ..ClickJob::default()
+        }
+    }
+}
+
+/// Why a number is not allowed in a number input, if it is not
+///
+/// # Arguments
+///
+/// * `value` - The number
+/// * `range` - The numbers allowed
+pub fn range_error<T: Number>(value: T, range: RangeInclusive<T>) -> Option<String> {
+    NumberField::new(value, range).error().map(str::to_owned)
+}
+
+/// Parse a hotkey saved by name
+///
+/// # Arguments
+///
+/// * `name` - The `Keycode` name, if any
+pub fn parse_keycode(name: &Option<String>) -> Option<Keycode> {
+    name.as_deref()
+        .and_then(|name| Keycode::from_str(name).ok())
+}
+
+/// Directory holding the profile files, e.g. `~/.config/rusty-autoclicker/profiles` on Linux
+pub fn profiles_dir() -> io::Result<PathBuf> {
+    Ok(settings::config_dir()?.join(PROFILES_DIR_NAME))
+}
+
+/// Path of the file for the named profile
+///
+/// # Arguments
+///
+/// * `name` - The profile name
+fn profile_path(name: &str) -> io::Result<PathBuf> {
+    if !is_valid_name(name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid profile name \"{name}\""),
+        ));
+    }
+    Ok(profiles_dir()?.join(format!("{name}.{PROFILE_FILE_EXTENSION}")))
+}
+
+/// Check that a profile name can safely be used as a file name
+///
+/// # Arguments
+///
+/// * `name` - The profile name
+pub fn is_valid_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && name.trim() == name
+        && name.len() <= PROFILE_NAME_MAX_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+}
+
+/// List the names of all saved profiles, sorted alphabetically
+pub fn list() -> Vec<String> {
+    let Ok(entries) = profiles_dir().and_then(fs::read_dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == PROFILE_FILE_EXTENSION)
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_owned()))
+        .filter(|name| is_valid_name(name))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+/// Load the named profile
+///
+/// # Arguments
+///
+/// * `name` - The profile name
+pub fn load(name: &str) -> io::Result<Profile> {
+    read_from(&profile_path(name)?)
+}
+
+/// Save a profile under the given name, overwriting any existing one
+///
+/// # Arguments
+///
+/// * `name` - The profile name
+/// * `profile` - The profile to save
+pub fn save(name: &str, profile: &Profile) -> io::Result<()> {
+    write_to(&profile_path(name)?, profile)
+}
+
+/// Delete the named profile
+///
+/// # Arguments
+///
+/// * `name` - The profile name
+pub fn delete(name: &str) -> io::Result<()> {
+    fs::remove_file(profile_path(name)?)
+}
+
+/// Deserialize a profile from a TOML file, migrating older versions
+///
+/// # Arguments
+///
+/// * `path` - The file to read
+fn read_from(path: &Path) -> io::Result<Profile> {
+    settings::from_toml(&fs::read_to_string(path)?)
+}
+
+/// Serialize a profile to a TOML file, creating parent directories as needed
+///
+/// # Arguments
+///
+/// * `path` - The file to write
+/// * `profile` - The profile to write
+fn write_to(path: &Path, profile: &Profile) -> io::Result<()> {
+    settings::write_atomically(path, &settings::to_toml(profile)?)
+}
@@ -0,0 +1,337 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use rusty_autoclicker::engine::{backend::BackendKind, defines::DEFAULT_MAX_FAILURES};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use toml::{Table, Value};
+
+use crate::{RustyAutoClickerApp, defines::*, profiles::Profile};
+
+/// Current schema version of the settings and profile files
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// Migrations upgrading a settings table by one version, `MIGRATIONS[n]` upgrades version `n` to `n + 1`
+const MIGRATIONS: [fn(&mut Table); SETTINGS_VERSION as usize] =
+    [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Settings restored on startup, saved whenever they change
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)] // if we add new fields, give them default values when loading old settings
+pub struct Settings {
+    /// Profile selected in the top bar, if any
+    pub active_profile: Option<String>,
+
+    /// Input backend sending the clicks
+    pub input_backend: BackendKind,
+
+    /// Consecutive failed clicks after which autoclicking stops, 0 = never
+    pub max_consecutive_failures: u32,
+
+    /// Seed of the random humanlike timings for reproducible runs, random if not set
+    pub humanlike_seed: Option<u64>,
+
+    /// Mark the targets & flash the clicks on screen while autoclicking
+    pub show_targets: bool,
+
+    /// Shrink the window to a compact always-on-top bar while autoclicking
+    pub compact_mode: bool,
+
+    /// Current settings, including unsaved changes to the active profile
+    #[serde(flatten)]
+    pub profile: Profile,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            active_profile: None,
+            input_backend: BackendKind::default(),
+            max_consecutive_failures: DEFAULT_MAX_FAILURES,
+            humanlike_seed: None,
+            show_targets: false,
+            compact_mode: true,
+            profile: Profile::default(),
+        }
+    }
+}
+
+impl From<&RustyAutoClickerApp> for Settings {
+    fn from(app: &RustyAutoClickerApp) -> Self {
+        Self {
+            active_profile: app.active_profile.clone(),
+            input_backend: app.backend_kind,
+            max_consecutive_failures: app.max_consecutive_failures,
+            humanlike_seed: app.humanlike_seed,
+            show_targets: app.show_targets,
+            compact_mode: app.compact_mode,
+            profile: Profile::from(app),
+        }
+    }
+}
+
+impl Settings {
+    /// Copy the settings onto the app
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - The app to apply the settings to
+    pub fn apply(&self, app: &mut RustyAutoClickerApp) {
+        self.profile.apply(app);
+        app.active_profile = self.active_profile.clone();
+        app.backend_kind = self.input_backend;
+        app.max_consecutive_failures = self.max_consecutive_failures;
+        app.humanlike_seed = self.humanlike_seed;
+        app.show_targets = self.show_targets;
+        app.compact_mode = self.compact_mode;
+    }
+}
+
+/// Directory holding the settings & profiles, e.g. `~/.config/rusty-autoclicker` on Linux
+pub fn config_dir() -> io::Result<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join(CONFIG_DIR_NAME))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No config directory found"))
+}
+
+/// Path of the settings file
+fn settings_path() -> io::Result<PathBuf> {
+    Ok(config_dir()?.join(SETTINGS_FILE_NAME))
+}
+
+/// Load the settings saved by the last session, `None` if there are none yet
+pub fn load() -> io::Result<Option<Settings>> {
+    match fs::read_to_string(settings_path()?) {
+        Ok(content) => from_toml(&content).map(Some),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Save the settings, overwriting the previous ones
+///
+/// # Arguments
+///
+/// * `settings` - The settings to save
+pub fn save(settings: &Settings) -> io::Result<()> {
+    write_atomically(&settings_path()?, &to_toml(settings)?)
+}
+
+/// Replace a file at once, creating parent directories as needed
+/// The content is written to a temporary file next to it first, so that a crash or a full disk
+/// never leaves the file half written
+///
+/// # Arguments
+///
+/// * `path` - The file to write
+/// * `content` - The content of the file
+pub fn write_atomically(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension("toml.tmp");
+    let written = File::create(&temp_path).and_then(|mut file| {
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    });
+    let renamed = written.and_then(|()| fs::rename(&temp_path, path));
+    if renamed.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    renamed
+}
+
+/// Deserialize a versioned TOML document, migrating it to the current version first
+///
+/// # Arguments
+///
+/// * `content` - The TOML document
+pub fn from_toml<T: DeserializeOwned>(content: &str) -> io::Result<T> {
+    let mut table: Table = toml::from_str(content).map_err(invalid_data)?;
+    migrate(&mut table)?;
+    table.remove(VERSION_KEY);
+    Value::Table(table).try_into().map_err(invalid_data)
+}
+
+/// Serialize to a TOML document tagged with the current version
+///
+/// # Arguments
+///
+/// * `value` - The value to serialize
+pub fn to_toml<T: Serialize>(value: &T) -> io::Result<String> {
+    let Value::Table(mut table) = Value::try_from(value).map_err(invalid_data)? else {
+        return Err(invalid_data("Expected a table"));
+    };
+    table.insert(VERSION_KEY.to_owned(), Value::from(SETTINGS_VERSION));
+    toml::to_string_pretty(&table).map_err(invalid_data)
+}
+
+/// Upgrade a settings table to the current version, untagged tables are version 0
+///
+/// # Arguments
+///
+/// * `table` - The table to upgrade
+fn migrate(table: &mut Table) -> io::Result<()> {
+    let version = match table.get(VERSION_KEY) {
+        None => 0,
+        Some(value) => value
+            .as_integer()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| invalid_data(format!("Invalid {VERSION_KEY} {value}")))?,
+    };
+    if version > SETTINGS_VERSION {
+        return Err(invalid_data(format!(
+            "Settings version {version} is newer than the supported version {SETTINGS_VERSION}"
+        )));
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(table);
+    }
+    table.insert(VERSION_KEY.to_owned(), Value::from(SETTINGS_VERSION));
+    Ok(())
+}
+
+/// Version 0 profiles only lacked the version key
+fn migrate_v0_to_v1(_table: &mut Table) {}
+
+/// Version 1 stored the numbers as the strings typed in the inputs, e.g. `hr_str = "1"`
+/// Empty strings were 0, other strings that are not numbers fall back to the defaults
+///
+/// # Arguments
+///
+/// * `table` - The table to upgrade
+fn migrate_v1_to_v2(table: &mut Table) {
+    const INTEGER_KEYS: [&str; 3] = ["hr", "min", "click_amount"];
+    const FLOAT_KEYS: [&str; 6] = [
+        "sec",
+        "ms",
+        "movement_sec",
+        "movement_ms",
+        "click_x",
+        "click_y",
+    ];
+
+    for key in INTEGER_KEYS.into_iter().chain(FLOAT_KEYS) {
+        let Some(Value::String(string)) = table.remove(&format!("{key}_str")) else {
+            continue;
+        };
+        let string = match string.trim() {
+            "" => "0",
+            string => string,
+        };
+        let value = if INTEGER_KEYS.contains(&key) {
+            string
+                .parse::<u64>()
+                .ok()
+                .and_then(|value| i64::try_from(value).ok())
+                .map(Value::from)
+        } else {
+            string.parse::<f64>().ok().map(Value::from)
+        };
+        if let Some(value) = value {
+            table.insert(key.to_owned(), value);
+        }
+    }
+}
+
+/// Wrap a (de)serialization error into an `io::Error`
+fn invalid_data<E>(e: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip_through_toml() {
+        let settings = Settings {
+            active_profile: Some("farm".to_owned()),
+            humanlike_seed: Some(42),
+            compact_mode: false,
+            ..Settings::default()
+        };
+
+        let content = to_toml(&settings).unwrap();
+
+        assert!(content.contains(&format!("{VERSION_KEY} = {SETTINGS_VERSION}")));
+        assert_eq!(from_toml::<Settings>(&content).unwrap(), settings);
+    }
+
+    #[test]
+    fn seeds_too_large_for_toml_are_not_serialized() {
+        let mut settings = Settings {
+            humanlike_seed: Some(i64::MAX as u64),
+            ..Settings::default()
+        };
+        assert!(to_toml(&settings).is_ok());
+
+        settings.humanlike_seed = Some(i64::MAX as u64 + 1);
+        assert!(to_toml(&settings).is_err());
+    }
+
+    #[test]
+    fn untagged_profiles_are_migrated_to_the_current_version() {
+        let content = "hr_str = \"1\"\nsec_str = \"1.5\"\nclick_amount_str = \"\"";
+
+        let profile: Profile = from_toml(content).unwrap();
+
+        assert_eq!((profile.hr, profile.sec, profile.click_amount), (1, 1.5, 0));
+        // Saved again, the profile is tagged & read back the same
+        let content = to_toml(&profile).unwrap();
+        assert!(content.contains(&format!("{VERSION_KEY} = {SETTINGS_VERSION}")));
+        assert_eq!(from_toml::<Profile>(&content).unwrap(), profile);
+    }
+
+    #[test]
+    fn files_are_replaced_at_once() {
+        let dir = std::env::temp_dir().join(format!("rusty-autoclicker-{}", std::process::id()));
+        let path = dir.join("nested").join("settings.toml");
+
+        write_atomically(&path, "old").unwrap();
+        write_atomically(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        // Only the file is left, not the temporary one
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let content = format!("{VERSION_KEY} = {}", SETTINGS_VERSION + 1);
+
+        let error = from_toml::<Settings>(&content).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn v1_strings_become_numbers() {
+        let mut table: Table =
+            toml::from_str("hr_str = \"2\"\nsec_str = \"\"\nms_str = \" 12.5 \"\nmin_str = \"x\"")
+                .unwrap();
+
+        migrate_v1_to_v2(&mut table);
+
+        // A blank input was 0, one that was not a number falls back to the default
+        let expected: Table = toml::from_str("hr = 2\nsec = 0.0\nms = 12.5").unwrap();
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn v1_integers_too_large_for_toml_are_dropped() {
+        let mut table: Table =
+            toml::from_str("click_amount_str = \"18446744073709551615\"\nhr_str = \"-1\"").unwrap();
+
+        migrate_v1_to_v2(&mut table);
+
+        assert!(table.is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+//! Playing the timeline of actions, one step per frame when it is due, & exchanging it with
+//! xdotool scripts
+
+use std::{fs, time::Instant};
+
+use rusty_autoclicker::engine::{
+    backend::{InputBackend, InputError, NullBackend},
+    clock::SystemClock,
+    history::RecordingBackend,
+    sequence::{Sequence, SequencePlayer},
+    xdotool,
+};
+use tracing::{error, info, warn};
+
+use crate::{RustyAutoClickerApp, profiles::Profile};
+
+/// What to export as an xdotool script
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ScriptSource {
+    Timeline,
+    MainJob,
+    ClickHistory,
+}
+
+impl ScriptSource {
+    /// The default name of the exported file
+    fn file_name(self) -> &'static str {
+        match self {
+            ScriptSource::Timeline => "timeline.sh",
+            ScriptSource::MainJob => "click-job.sh",
+            ScriptSource::ClickHistory => "click-history.sh",
+        }
+    }
+}
+
+impl RustyAutoClickerApp {
+    /// Start playing the timeline from its first step
+    /// The actions are sent in the app mode, with the movement delay & backend of the main job
+    pub fn play_sequence(&mut self) {
+        if let Err(e) = self.sequence.check() {
+            error!("Not playing the timeline: {e}");
+            self.input_error = Some(format!("Timeline: {e}"));
+            return;
+        }
+
+        let job = Profile::from(&*self).click_job().seed(self.humanlike_seed);
+        info!(steps = self.sequence.steps.len(), "Playing the timeline");
+        self.sequence_player = Some(SequencePlayer::new(
+            self.sequence.clone(),
+            &job,
+            &NullBackend,
+            Instant::now(),
+        ));
+    }
+
+    /// Stop playing the timeline
+    pub fn stop_sequence(&mut self) {
+        if let Some(player) = self.sequence_player.take() {
+            info!(loops = player.loop_counter(), "Stopped the timeline");
+        }
+    }
+
+    /// Play the step of the timeline that is due, stopping once it is over or an action fails
+    pub fn update_sequence(&mut self) {
+        let Some(player) = &self.sequence_player else {
+            return;
+        };
+        if Instant::now() < player.due() {
+            return;
+        }
+        if player.is_over() {
+            self.stop_sequence();
+            return;
+        }
+
+        // Dry runs send the events nowhere
+        let mut null_backend = NullBackend;
+        let backend: &mut dyn InputBackend = if self.dry_run {
+            &mut null_backend
+        } else if self.ensure_input_backend() {
+            match self.input_backend.as_mut() {
+                Some((_, backend)) => backend.as_mut(),
+                None => return,
+            }
+        } else {
+            self.stop_sequence();
+            return;
+        };
+
+        let Some(player) = self.sequence_player.as_mut() else {
+            return;
+        };
+        // Record the events in the click history, like the ones of the main job
+        let mut recorder = RecordingBackend::new(backend, &mut self.click_history, self.app_mode);
+        if let Err(e) = player.step(&mut recorder, &mut SystemClock) {
+            warn!("{e}");
+            self.input_error = Some(format!("Timeline: {e}"));
+            self.stop_sequence();
+        }
+    }
+
+    /// Whether the timeline is being played
+    pub fn is_playing_sequence(&self) -> bool {
+        self.sequence_player.is_some()
+    }
+
+    /// Ask for an xdotool script and replace the timeline with it
+    pub fn import_xdotool(&mut self) {
+        let path = native_dialog::DialogBuilder::file()
+            .add_filter("Shell script", ["sh"])
+            .open_single_file()
+            .show();
+
+        self.timeline_status = match path {
+            Ok(Some(path)) => {
+                let sequence = fs::read_to_string(&path)
+                    .map_err(|e| InputError::new(e.to_string()))
+                    .and_then(|script| xdotool::parse_script(&script));
+                match sequence {
+                    Ok(sequence) => {
+                        info!(steps = sequence.steps.len(), "Imported {}", path.display());
+                        self.sequence = sequence;
+                        self.selected_step = None;
+                        Some(format!("Imported {}", path.display()))
+                    }
+                    Err(e) => {
+                        error!("Could not import {}: {e}", path.display());
+                        Some(format!("Could not import: {e}"))
+                    }
+                }
+            }
+            Ok(None) => None,
+            Err(e) => Some(format!("Could not open the file dialog: {e}")),
+        };
+    }
+
+    /// Ask where to save an xdotool script and export the timeline, the main job or the history
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - What to export
+    pub fn export_xdotool(&mut self, source: ScriptSource) {
+        let script = match source {
+            ScriptSource::Timeline => Ok(self.sequence.clone()),
+            ScriptSource::MainJob => Profile::from(&*self).click_job().to_sequence(),
+            ScriptSource::ClickHistory => Ok(self.click_history.to_sequence()),
+        }
+        .and_then(|sequence: Sequence| xdotool::write_script(&sequence));
+        let script = match script {
+            Ok(script) => script,
+            Err(e) => {
+                self.timeline_status = Some(format!("Could not export: {e}"));
+                return;
+            }
+        };
+
+        let path = native_dialog::DialogBuilder::file()
+            .set_filename(source.file_name())
+            .add_filter("Shell script", ["sh"])
+            .save_single_file()
+            .show();
+        self.timeline_status = match path {
+            Ok(Some(path)) => match write_script(&path, &script) {
+                Ok(()) => {
+                    info!("Exported the xdotool script to {}", path.display());
+                    Some(format!("Exported to {}", path.display()))
+                }
+                Err(e) => {
+                    error!("Could not export the xdotool script: {e}");
+                    Some(format!("Could not export: {e}"))
+                }
+            },
+            Ok(None) => None,
+            Err(e) => Some(format!("Could not open the file dialog: {e}")),
+        };
+    }
+}
+
+/// Write a shell script, executable on Unix
+///
+/// # Arguments
+///
+/// * `path` - The file to write
+/// * `script` - The script
+fn write_script(path: &std::path::Path, script: &str) -> std::io::Result<()> {
+    fs::write(path, script)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
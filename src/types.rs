@@ -1,34 +1,53 @@
 use std::fmt;
 
 use rdev::{Button, Key};
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Copy, Clone)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum AppMode {
     Bot,
     Humanlike,
 }
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Debug)]
 pub struct ClickInfo {
     pub click_btn: ClickButton,
     pub click_coord: (f64, f64),
     pub click_position: ClickPosition,
     pub click_type: ClickType,
+    pub coord_mode: CoordMode,
 }
 
-#[derive(PartialEq, Eq, Copy, Clone)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum ClickPosition {
     Mouse,
     Coord,
 }
 
-#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+/// How the click coordinates are expressed, converted to pixels when clicking
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordMode {
+    /// Pixels of the virtual screen
+    #[default]
+    Absolute,
+    /// Percentage of the whole virtual screen, spanning all monitors
+    ScreenPercent,
+    /// Percentage of the monitor with the given index, the primary one being 0
+    MonitorPercent(usize),
+    /// Pixels from the top-left corner of the target window, wherever it is
+    Window,
+    /// Pixels from the center of the target image, wherever it is found on the screen
+    Image,
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum ClickType {
     Single,
     Double,
 }
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum ClickButton {
     Mouse(Button),
     Key(Key),
@@ -42,3 +61,17 @@ impl fmt::Display for ClickButton {
         }
     }
 }
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    Completed,
+    User,
+    Ipc,
+    /// Too many consecutive clicks could not be sent
+    Failed,
+    /// The watched pixel no longer has its color
+    PixelChanged,
+    /// The target image was not found on the screen
+    ImageMissing,
+}
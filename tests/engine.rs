@@ -0,0 +1,1968 @@
+//! Exact event sequences & timings of the click engine, run on a virtual clock
+
+use std::{
+    sync::atomic::AtomicBool,
+    time::{Duration, Instant},
+};
+
+use image::{DynamicImage, RgbImage, imageops};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use rdev::{Button, EventType, Key};
+use rusty_autoclicker::{
+    engine::{
+        ClickError, ClickErrorKind, ClickJob, JobEvent, Target, TargetPoint,
+        backend::{InputBackend, InputError, MockBackend},
+        clock::{Clock, VirtualClock},
+        defines::*,
+        history::{ClickHistory, HistoryEntry, RecordingBackend},
+        pixel::{Color, PixelCondition, PixelTrigger},
+        runner::{JobRunner, step_due_runners},
+        screen::{self, Monitor, Region},
+        sequence::{Action, Sequence, SequencePlayer, Step},
+        stats::{ClickStats, StatsSummary},
+        template::{ImageTarget, MissingAction, Template},
+        window::{FocusAction, FocusGuard, WindowInfo, WindowMatch},
+        xdotool,
+    },
+    types::{AppMode, ClickButton, ClickType, CoordMode, StopReason},
+};
+
+const SEED: u64 = 42;
+const MOUSE: (i32, i32) = (0, 0);
+const TARGET: (f64, f64) = (25.0, 10.0);
+const MOVEMENT_DELAY: Duration = Duration::from_millis(20);
+
+const PRESS: EventType = EventType::ButtonPress(Button::Left);
+const RELEASE: EventType = EventType::ButtonRelease(Button::Left);
+const MOVE_TO_TARGET: EventType = EventType::MouseMove {
+    x: TARGET.0,
+    y: TARGET.1,
+};
+
+/// Steps of the humanlike movement from `MOUSE` to `TARGET`, one every `MOVEMENT_DELAY`
+const HUMANLIKE_PATH: [EventType; 3] = [
+    EventType::MouseMove { x: 10.0, y: 10.0 },
+    EventType::MouseMove { x: 20.0, y: 10.0 },
+    EventType::MouseMove { x: 25.0, y: 10.0 },
+];
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+fn job(app_mode: AppMode, click_type: ClickType, target: Target) -> ClickJob {
+    ClickJob::new()
+        .mode(app_mode)
+        .click_type(click_type)
+        .target(target)
+        .movement_delay(MOVEMENT_DELAY)
+        .seed(Some(SEED))
+}
+
+/// Send a single click of the job and return the timed events
+fn click(job: &ClickJob) -> Vec<(Duration, EventType)> {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+    job.click(
+        &mut backend.clone(),
+        &mut clock.clone(),
+        &mut job.new_rng(),
+        MOUSE,
+    )
+    .unwrap();
+    backend.timed_events()
+}
+
+/// The random durations drawn by a humanlike click, in order
+struct HumanlikeDurations(StdRng);
+
+impl HumanlikeDurations {
+    fn new() -> Self {
+        Self(StdRng::seed_from_u64(SEED))
+    }
+
+    fn press(&mut self) -> Duration {
+        ms(self.0.random_range(DURATION_CLICK_MIN..DURATION_CLICK_MAX))
+    }
+
+    fn between_clicks(&mut self) -> Duration {
+        ms(self
+            .0
+            .random_range(DURATION_DOUBLE_CLICK_MIN..DURATION_DOUBLE_CLICK_MAX))
+    }
+}
+
+/// Timestamp the humanlike path, starting at `start`
+fn humanlike_path(start: Duration) -> Vec<(Duration, EventType)> {
+    HUMANLIKE_PATH
+        .iter()
+        .enumerate()
+        .map(|(step, event)| (start + MOVEMENT_DELAY * step as u32, *event))
+        .collect()
+}
+
+#[test]
+fn bot_single_at_mouse() {
+    let events = click(&job(AppMode::Bot, ClickType::Single, Target::Mouse));
+    assert_eq!(events, vec![(ms(0), PRESS), (ms(0), RELEASE)]);
+}
+
+#[test]
+fn bot_single_at_coord() {
+    let events = click(&job(AppMode::Bot, ClickType::Single, TARGET.into()));
+    assert_eq!(
+        events,
+        vec![(ms(0), MOVE_TO_TARGET), (ms(0), PRESS), (ms(0), RELEASE)]
+    );
+}
+
+#[test]
+fn bot_double_at_mouse() {
+    let events = click(&job(AppMode::Bot, ClickType::Double, Target::Mouse));
+    assert_eq!(
+        events,
+        vec![
+            (ms(0), PRESS),
+            (ms(0), RELEASE),
+            (ms(0), PRESS),
+            (ms(0), RELEASE)
+        ]
+    );
+}
+
+#[test]
+fn bot_double_at_coord() {
+    let events = click(&job(AppMode::Bot, ClickType::Double, TARGET.into()));
+    assert_eq!(
+        events,
+        vec![
+            (ms(0), MOVE_TO_TARGET),
+            (ms(0), PRESS),
+            (ms(0), RELEASE),
+            (ms(0), MOVE_TO_TARGET),
+            (ms(0), PRESS),
+            (ms(0), RELEASE)
+        ]
+    );
+}
+
+#[test]
+fn bot_key() {
+    let job =
+        job(AppMode::Bot, ClickType::Single, Target::Mouse).button(ClickButton::Key(Key::Space));
+    assert_eq!(
+        click(&job),
+        vec![
+            (ms(0), EventType::KeyPress(Key::Space)),
+            (ms(0), EventType::KeyRelease(Key::Space))
+        ]
+    );
+}
+
+#[test]
+fn humanlike_single_at_mouse() {
+    let mut durations = HumanlikeDurations::new();
+    let release = durations.press();
+
+    let events = click(&job(AppMode::Humanlike, ClickType::Single, Target::Mouse));
+    assert_eq!(events, vec![(ms(0), PRESS), (release, RELEASE)]);
+}
+
+#[test]
+fn humanlike_single_at_coord() {
+    let mut durations = HumanlikeDurations::new();
+    let press = MOVEMENT_DELAY * HUMANLIKE_PATH.len() as u32;
+    let release = press + durations.press();
+
+    let mut expected = humanlike_path(ms(0));
+    expected.extend([(press, PRESS), (release, RELEASE)]);
+
+    let events = click(&job(AppMode::Humanlike, ClickType::Single, TARGET.into()));
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn humanlike_double_at_mouse() {
+    let mut durations = HumanlikeDurations::new();
+    let first_release = durations.press();
+    let second_press = first_release + durations.between_clicks();
+    let second_release = second_press + durations.press();
+
+    let events = click(&job(AppMode::Humanlike, ClickType::Double, Target::Mouse));
+    assert_eq!(
+        events,
+        vec![
+            (ms(0), PRESS),
+            (first_release, RELEASE),
+            (second_press, PRESS),
+            (second_release, RELEASE)
+        ]
+    );
+}
+
+#[test]
+fn humanlike_double_at_coord() {
+    let mut durations = HumanlikeDurations::new();
+    let path_duration = MOVEMENT_DELAY * HUMANLIKE_PATH.len() as u32;
+    let first_press = path_duration;
+    let first_release = first_press + durations.press();
+    // The second click moves from the starting mouse position again
+    let second_path = first_release + durations.between_clicks();
+    let second_press = second_path + path_duration;
+    let second_release = second_press + durations.press();
+
+    let mut expected = humanlike_path(ms(0));
+    expected.extend([(first_press, PRESS), (first_release, RELEASE)]);
+    expected.extend(humanlike_path(second_path));
+    expected.extend([(second_press, PRESS), (second_release, RELEASE)]);
+
+    let events = click(&job(AppMode::Humanlike, ClickType::Double, TARGET.into()));
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn click_stops_after_a_failed_release() {
+    let backend = MockBackend::new().with_failing_release();
+    let job = job(AppMode::Humanlike, ClickType::Double, Target::Mouse);
+
+    let error = job
+        .click(
+            &mut backend.clone(),
+            &mut VirtualClock::new(),
+            &mut job.new_rng(),
+            MOUSE,
+        )
+        .unwrap_err();
+
+    assert_eq!(error.event_type, Some(RELEASE));
+    assert_eq!(backend.events(), [PRESS]);
+    assert_eq!(backend.failed_events(), [RELEASE]);
+}
+
+#[test]
+fn key_is_released_after_a_failed_press() {
+    let backend = MockBackend::new().with_failing_key(Key::Space);
+    for app_mode in [AppMode::Bot, AppMode::Humanlike] {
+        backend.clear();
+        let job =
+            job(app_mode, ClickType::Single, Target::Mouse).button(ClickButton::Key(Key::Space));
+
+        let error = job
+            .click(
+                &mut backend.clone(),
+                &mut VirtualClock::new(),
+                &mut job.new_rng(),
+                MOUSE,
+            )
+            .unwrap_err();
+
+        // The press error is the one reported
+        assert_eq!(error.event_type, Some(EventType::KeyPress(Key::Space)));
+        assert_eq!(backend.events(), [EventType::KeyRelease(Key::Space)]);
+    }
+}
+
+#[test]
+fn humanlike_seed_makes_runs_reproducible() {
+    let run = |seed: u64| {
+        let clock = VirtualClock::new();
+        let backend = MockBackend::new().with_clock(clock.clone());
+        job(AppMode::Humanlike, ClickType::Double, Target::Mouse)
+            .seed(Some(seed))
+            .amount(20)
+            .run_on(
+                &mut backend.clone(),
+                &mut clock.clone(),
+                &AtomicBool::new(false),
+                |_| (),
+            );
+        backend.timed_events()
+    };
+
+    assert_eq!(run(SEED), run(SEED));
+    assert_ne!(run(SEED), run(SEED + 1));
+}
+
+#[test]
+fn run_sends_clicks_at_the_interval() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+    let mut clicks = Vec::new();
+
+    let report = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .interval(ms(100))
+        .amount(3)
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |event| {
+                if let JobEvent::Clicked(click_counter) = event {
+                    clicks.push(click_counter)
+                }
+            },
+        );
+
+    assert_eq!(
+        backend.timed_events(),
+        vec![
+            (ms(0), PRESS),
+            (ms(0), RELEASE),
+            (ms(100), PRESS),
+            (ms(100), RELEASE),
+            (ms(200), PRESS),
+            (ms(200), RELEASE)
+        ]
+    );
+    assert_eq!(clicks, vec![1, 2, 3]);
+    assert_eq!(report.click_counter, 3);
+    assert_eq!(report.stop_reason, StopReason::Completed);
+    assert_eq!(report.elapsed, ms(200));
+}
+
+/// Step the due runners every `POLL_INTERVAL` like the app does on every frame, until they all
+/// stopped, returning when each one stopped
+fn run_frames(
+    jobs: &[ClickJob],
+    backend: &MockBackend,
+    clock: &VirtualClock,
+    mut on_event: impl FnMut(usize, JobEvent),
+) -> Vec<(StopReason, Duration)> {
+    let mut runners: Vec<Option<JobRunner>> = jobs
+        .iter()
+        .map(|job| Some(JobRunner::new(job.clone(), backend, clock.now())))
+        .collect();
+    let mut stopped = vec![None; jobs.len()];
+    while runners.iter().any(Option::is_some) {
+        let running = runners
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, runner)| Some((index, runner.as_mut()?)));
+        for (index, stop_reason) in step_due_runners(
+            running,
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &mut on_event,
+        ) {
+            runners[index] = None;
+            stopped[index] = Some((stop_reason, clock.elapsed()));
+        }
+        clock.clone().sleep(POLL_INTERVAL);
+    }
+    stopped.into_iter().flatten().collect()
+}
+
+#[test]
+fn due_runners_interleave_the_clicks_by_due_time() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+    let key_job = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .button(ClickButton::Key(Key::KeyF))
+        .interval(ms(250))
+        .amount(2);
+    let mut clicks = Vec::new();
+
+    let stopped = run_frames(
+        &[
+            job(AppMode::Bot, ClickType::Single, Target::Mouse)
+                .interval(ms(100))
+                .amount(3),
+            key_job,
+        ],
+        &backend,
+        &clock,
+        |index, event| {
+            if let JobEvent::Clicked(click_counter) = event {
+                clicks.push((index, click_counter))
+            }
+        },
+    );
+
+    assert_eq!(
+        backend.timed_events(),
+        vec![
+            (ms(0), PRESS),
+            (ms(0), RELEASE),
+            (ms(0), EventType::KeyPress(Key::KeyF)),
+            (ms(0), EventType::KeyRelease(Key::KeyF)),
+            (ms(100), PRESS),
+            (ms(100), RELEASE),
+            (ms(200), PRESS),
+            (ms(200), RELEASE),
+            (ms(250), EventType::KeyPress(Key::KeyF)),
+            (ms(250), EventType::KeyRelease(Key::KeyF))
+        ]
+    );
+    assert_eq!(clicks, vec![(0, 1), (1, 1), (0, 2), (0, 3), (1, 2)]);
+    assert_eq!(
+        stopped,
+        [
+            (StopReason::Completed, ms(200)),
+            (StopReason::Completed, ms(250))
+        ]
+    );
+}
+
+#[test]
+fn due_runners_send_each_click_whole() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+    let key_job = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .button(ClickButton::Key(Key::KeyF))
+        .amount(1);
+
+    run_frames(
+        &[
+            job(AppMode::Humanlike, ClickType::Single, TARGET.into()).amount(1),
+            key_job,
+        ],
+        &backend,
+        &clock,
+        |_, _| (),
+    );
+
+    // The key is due during the humanlike movement, it is pressed once the click is over
+    let mut expected = HUMANLIKE_PATH.to_vec();
+    expected.extend([
+        PRESS,
+        RELEASE,
+        EventType::KeyPress(Key::KeyF),
+        EventType::KeyRelease(Key::KeyF),
+    ]);
+    assert_eq!(backend.events(), expected);
+}
+
+#[test]
+fn only_due_runners_click() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+    let job = job(AppMode::Bot, ClickType::Single, Target::Mouse).interval(ms(100));
+    let mut first = JobRunner::new(job.clone(), &backend, clock.now());
+    let mut second =
+        JobRunner::new(job, &backend, clock.now()).first_click_at(clock.now() + ms(50));
+
+    let stopped = step_due_runners(
+        [(0, &mut first), (1, &mut second)],
+        &mut backend.clone(),
+        &mut clock.clone(),
+        |_, _| (),
+    );
+
+    assert!(stopped.is_empty());
+    assert_eq!((first.click_counter(), second.click_counter()), (1, 0));
+    assert_eq!(backend.events(), [PRESS, RELEASE]);
+}
+
+#[test]
+fn run_clicks_the_points_in_turn() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+    let move_to = |x: f64, y: f64| EventType::MouseMove { x, y };
+
+    job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .points(vec![
+            TargetPoint::new("Point 1", 10.0, 20.0),
+            TargetPoint::new("Point 2", 30.0, 40.0),
+        ])
+        .amount(3)
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |_| (),
+        );
+
+    assert_eq!(
+        backend.events(),
+        vec![
+            move_to(10.0, 20.0),
+            PRESS,
+            RELEASE,
+            move_to(30.0, 40.0),
+            PRESS,
+            RELEASE,
+            move_to(10.0, 20.0),
+            PRESS,
+            RELEASE
+        ]
+    );
+}
+
+#[test]
+fn run_starts_humanlike_movement_at_the_backend_mouse_position() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_mouse_position(20, 10);
+
+    job(AppMode::Humanlike, ClickType::Single, TARGET.into())
+        .amount(1)
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |_| (),
+        );
+
+    assert_eq!(backend.events()[0], MOVE_TO_TARGET);
+}
+
+#[test]
+fn run_stops_when_requested() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+
+    let report = job(AppMode::Bot, ClickType::Single, Target::Mouse).run_on(
+        &mut backend.clone(),
+        &mut clock.clone(),
+        &AtomicBool::new(true),
+        |_| (),
+    );
+
+    assert!(backend.events().is_empty());
+    assert_eq!(report.click_counter, 0);
+    assert_eq!(report.stop_reason, StopReason::User);
+}
+
+/// Backend failing to send any event
+struct FailingBackend;
+
+impl InputBackend for FailingBackend {
+    fn mouse_position(&self) -> Option<(i32, i32)> {
+        Some(MOUSE)
+    }
+
+    fn move_to(&mut self, _x: f64, _y: f64) -> Result<(), InputError> {
+        Err(InputError::new("move failed"))
+    }
+
+    fn press(&mut self, _button: Button) -> Result<(), InputError> {
+        Err(InputError::new("press failed"))
+    }
+
+    fn release(&mut self, _button: Button) -> Result<(), InputError> {
+        Err(InputError::new("release failed"))
+    }
+
+    fn wheel(&mut self, _delta_x: i64, _delta_y: i64) -> Result<(), InputError> {
+        Err(InputError::new("wheel failed"))
+    }
+
+    fn key_press(&mut self, _key: Key) -> Result<(), InputError> {
+        Err(InputError::new("key press failed"))
+    }
+
+    fn key_release(&mut self, _key: Key) -> Result<(), InputError> {
+        Err(InputError::new("key release failed"))
+    }
+}
+
+#[test]
+fn run_stops_after_consecutive_failures() {
+    let report = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .interval(ms(100))
+        .max_failures(3)
+        .run_on(
+            &mut FailingBackend,
+            &mut VirtualClock::new(),
+            &AtomicBool::new(false),
+            |_| (),
+        );
+
+    assert_eq!(report.click_counter, 0);
+    assert_eq!(report.failure_counter, 3);
+    assert_eq!(report.stop_reason, StopReason::Failed);
+    assert_eq!(report.elapsed, ms(200));
+    let last_error = report.last_error.unwrap();
+    assert_eq!(last_error.event_type, Some(PRESS));
+    assert_eq!(last_error.error, InputError::new("press failed"));
+}
+
+/// A 1080p primary monitor with a 4K monitor on its right
+const MONITORS: [Monitor; 2] = [
+    Monitor {
+        x: 0,
+        y: 0,
+        width: 1920,
+        height: 1080,
+    },
+    Monitor {
+        x: 1920,
+        y: 0,
+        width: 3840,
+        height: 2160,
+    },
+];
+
+/// The point a bot click at the coordinates moves to
+fn bot_click_position(coord_mode: CoordMode, coord: (f64, f64)) -> Result<EventType, String> {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_monitors(MONITORS.to_vec());
+    job(AppMode::Bot, ClickType::Single, coord.into())
+        .coord_mode(coord_mode)
+        .click(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &mut StdRng::seed_from_u64(SEED),
+            MOUSE,
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(backend.events()[0])
+}
+
+#[test]
+fn percent_of_monitor_is_converted_at_click_time() {
+    assert_eq!(
+        bot_click_position(CoordMode::MonitorPercent(0), (50.0, 100.0)),
+        Ok(EventType::MouseMove {
+            x: 960.0,
+            y: 1079.0
+        })
+    );
+    assert_eq!(
+        bot_click_position(CoordMode::MonitorPercent(1), (50.0, 100.0)),
+        Ok(EventType::MouseMove {
+            x: 1920.0 + 1920.0,
+            y: 2159.0
+        })
+    );
+}
+
+#[test]
+fn percent_of_screen_spans_all_monitors() {
+    assert_eq!(
+        bot_click_position(CoordMode::ScreenPercent, (0.0, 25.0)),
+        Ok(EventType::MouseMove { x: 0.0, y: 540.0 })
+    );
+}
+
+#[test]
+fn coordinates_off_screen_are_rejected() {
+    // Below the 1080p monitor, within the bounds of the screen
+    let error = bot_click_position(CoordMode::ScreenPercent, (10.0, 90.0)).unwrap_err();
+    assert!(error.contains("not on any screen"), "{error}");
+
+    assert!(screen::to_pixels(CoordMode::Absolute, (-1.0, 0.0), &MONITORS).is_err());
+
+    let error = bot_click_position(CoordMode::MonitorPercent(2), (50.0, 50.0)).unwrap_err();
+    assert!(error.contains("Monitor 2 does not exist"), "{error}");
+}
+
+#[test]
+fn pixels_convert_back_to_percentages() {
+    let percent = screen::from_pixels(
+        CoordMode::MonitorPercent(1),
+        (1920.0 + 959.75, 2159.0),
+        &MONITORS,
+    )
+    .unwrap();
+    assert_eq!(percent, (25.0, 100.0));
+    assert_eq!(
+        screen::to_pixels(CoordMode::MonitorPercent(1), percent, &MONITORS),
+        Ok((1920.0 + 960.0, 2159.0))
+    );
+}
+
+fn window(id: u32, title: &str, x: i32, y: i32) -> WindowInfo {
+    WindowInfo {
+        id,
+        title: title.to_owned(),
+        instance: "xterm".to_owned(),
+        class: "XTerm".to_owned(),
+        x,
+        y,
+        width: 800,
+        height: 600,
+    }
+}
+
+#[test]
+fn window_target_follows_the_window() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_windows(vec![window(1, "Editor", 100, 50), window(2, "Game", 0, 0)]);
+    let job = job(AppMode::Bot, ClickType::Single, (10.0, 20.0).into())
+        .window(WindowMatch::Title("^Edit".to_owned()));
+    let mut rng = job.new_rng();
+
+    job.click(&mut backend.clone(), &mut clock.clone(), &mut rng, MOUSE)
+        .unwrap();
+    backend.set_windows(vec![window(1, "Editor", 300, 200)]);
+    job.click(&mut backend.clone(), &mut clock.clone(), &mut rng, MOUSE)
+        .unwrap();
+
+    let moves: Vec<EventType> = backend
+        .events()
+        .into_iter()
+        .filter(|event| matches!(event, EventType::MouseMove { .. }))
+        .collect();
+    assert_eq!(
+        moves,
+        vec![
+            EventType::MouseMove { x: 110.0, y: 70.0 },
+            EventType::MouseMove { x: 310.0, y: 220.0 }
+        ]
+    );
+}
+
+#[test]
+fn window_match_picks_the_topmost_window() {
+    let windows = [
+        window(1, "Terminal 1", 0, 0),
+        window(2, "Terminal 2", 10, 10),
+    ];
+    let find = |window_match: WindowMatch| {
+        window_match
+            .find(&windows)
+            .map(|window| window.map(|window| window.id))
+    };
+
+    assert_eq!(find(WindowMatch::Title("Terminal".to_owned())), Ok(Some(2)));
+    assert_eq!(find(WindowMatch::Title("1$".to_owned())), Ok(Some(1)));
+    assert_eq!(find(WindowMatch::Class("^xterm$".to_owned())), Ok(Some(2)));
+    assert_eq!(find(WindowMatch::Id(1)), Ok(Some(1)));
+    assert_eq!(find(WindowMatch::Id(3)), Ok(None));
+    assert!(find(WindowMatch::Title("(".to_owned())).is_err());
+    assert!(find(WindowMatch::default()).is_err());
+}
+
+#[test]
+fn run_pauses_while_the_window_is_missing() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_windows(Vec::new());
+    let mut events = Vec::new();
+
+    let report = job(AppMode::Bot, ClickType::Single, (10.0, 20.0).into())
+        .window(WindowMatch::Class("XTerm".to_owned()))
+        .interval(ms(100))
+        .amount(2)
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |event| {
+                // The window opens once the job paused
+                if matches!(event, JobEvent::Paused(_)) {
+                    backend.set_windows(vec![window(1, "xterm", 0, 0)]);
+                }
+                events.push(event);
+            },
+        );
+
+    assert_eq!(
+        events,
+        vec![
+            JobEvent::Paused("window of class /XTerm/ not found".to_owned()),
+            JobEvent::Resumed,
+            JobEvent::Clicked(1),
+            JobEvent::Clicked(2)
+        ]
+    );
+    assert_eq!(report.failure_counter, 0);
+    assert_eq!(report.stop_reason, StopReason::Completed);
+    // Retried after the retry interval, capped by the click interval
+    assert_eq!(report.elapsed, ms(200));
+}
+
+fn focus_guard(action: FocusAction) -> FocusGuard {
+    FocusGuard {
+        window: WindowMatch::Title("^Game$".to_owned()),
+        action,
+    }
+}
+
+#[test]
+fn run_skips_clicks_while_another_window_is_focused() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_windows(vec![window(1, "Editor", 0, 0), window(2, "Game", 0, 0)])
+        .with_active_window(1);
+    let mut events = Vec::new();
+
+    let report = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .focus_guard(focus_guard(FocusAction::Skip))
+        .interval(ms(100))
+        .amount(2)
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |event| {
+                // The game gets the focus after two skipped clicks
+                if events.len() == 1 {
+                    backend.set_active_window(2);
+                }
+                events.push(event);
+            },
+        );
+
+    let skipped = JobEvent::Skipped("window titled /^Game$/ is not focused".to_owned());
+    assert_eq!(
+        events,
+        vec![
+            skipped.clone(),
+            skipped,
+            JobEvent::Resumed,
+            JobEvent::Clicked(1),
+            JobEvent::Clicked(2)
+        ]
+    );
+    assert_eq!(report.skipped_counter, 2);
+    assert_eq!(report.failure_counter, 0);
+    // Skipped clicks keep the schedule
+    assert_eq!(report.elapsed, ms(300));
+    assert_eq!(backend.events(), vec![PRESS, RELEASE, PRESS, RELEASE]);
+}
+
+#[test]
+fn run_pauses_while_another_window_is_focused() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_windows(vec![window(1, "Editor", 0, 0), window(2, "Game", 0, 0)])
+        .with_active_window(0);
+    let mut events = Vec::new();
+
+    let report = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .focus_guard(focus_guard(FocusAction::Pause))
+        .interval(ms(1000))
+        .amount(2)
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |event| {
+                if matches!(event, JobEvent::Paused(_)) {
+                    backend.set_active_window(2);
+                }
+                events.push(event);
+            },
+        );
+
+    assert_eq!(
+        events,
+        vec![
+            JobEvent::Paused("window titled /^Game$/ is not focused".to_owned()),
+            JobEvent::Resumed,
+            JobEvent::Clicked(1),
+            JobEvent::Clicked(2)
+        ]
+    );
+    assert_eq!(report.skipped_counter, 0);
+    // Looked for the focus again after the retry interval
+    assert_eq!(report.elapsed, WINDOW_RETRY_INTERVAL + ms(1000));
+}
+
+#[test]
+fn focus_guard_checks_its_pattern_before_running() {
+    let job = ClickJob::new().focus_guard(FocusGuard {
+        window: WindowMatch::Class("(".to_owned()),
+        action: FocusAction::Skip,
+    });
+    let backend = MockBackend::new()
+        .with_windows(Vec::new())
+        .with_active_window(0);
+
+    assert!(
+        job.check_focus(&backend)
+            .is_err_and(|e| e.kind == ClickErrorKind::Failed)
+    );
+}
+
+const RED: Color = Color::new(255, 0, 0);
+const BLACK: Color = Color::new(0, 0, 0);
+
+fn pixel_condition(trigger: PixelTrigger) -> PixelCondition {
+    PixelCondition {
+        x: 10,
+        y: 20,
+        color: RED,
+        tolerance: 10,
+        trigger,
+    }
+}
+
+#[test]
+fn color_is_written_in_hex() {
+    assert_eq!("#FF8000".parse(), Ok(Color::new(255, 128, 0)));
+    assert_eq!("00ff00".parse(), Ok(Color::new(0, 255, 0)));
+    assert!("#ff80".parse::<Color>().is_err());
+    assert!("#gg0000".parse::<Color>().is_err());
+    assert_eq!(Color::new(255, 128, 0).to_string(), "#ff8000");
+    assert!(RED.matches(&Color::new(245, 10, 0), 10));
+    assert!(!RED.matches(&Color::new(244, 0, 0), 10));
+}
+
+#[test]
+fn run_waits_for_the_pixel_then_stops_checking_it() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_pixel_color(BLACK);
+    let mut events = Vec::new();
+
+    let report = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .pixel_condition(pixel_condition(PixelTrigger::WaitUntil))
+        .interval(ms(1000))
+        .amount(3)
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |event| {
+                match event {
+                    JobEvent::Paused(_) => backend.set_pixel_color(RED),
+                    // Not checked anymore once clicking started
+                    JobEvent::Clicked(1) => backend.set_pixel_color(BLACK),
+                    _ => (),
+                }
+                events.push(event);
+            },
+        );
+
+    assert_eq!(
+        events,
+        vec![
+            JobEvent::Paused("pixel 10/20 of color #ff0000 ±10 is #000000".to_owned()),
+            JobEvent::Resumed,
+            JobEvent::Clicked(1),
+            JobEvent::Clicked(2),
+            JobEvent::Clicked(3)
+        ]
+    );
+    assert_eq!(report.stop_reason, StopReason::Completed);
+    // Polled again after the poll interval
+    assert_eq!(report.elapsed, PIXEL_POLL_INTERVAL + ms(2000));
+}
+
+#[test]
+fn run_retries_after_the_poll_interval_with_a_long_interval() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_pixel_color(BLACK);
+    let mut events = Vec::new();
+
+    // Longer than the time since the clock started, so it cannot be subtracted from it
+    let report = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .pixel_condition(pixel_condition(PixelTrigger::WaitUntil))
+        .interval(Duration::from_secs(100 * 365 * 24 * 3600))
+        .amount(1)
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |event| {
+                if matches!(event, JobEvent::Paused(_)) {
+                    backend.set_pixel_color(RED);
+                }
+                events.push(event);
+            },
+        );
+
+    assert_eq!(
+        events,
+        vec![
+            JobEvent::Paused("pixel 10/20 of color #ff0000 ±10 is #000000".to_owned()),
+            JobEvent::Resumed,
+            JobEvent::Clicked(1)
+        ]
+    );
+    assert_eq!(report.elapsed, PIXEL_POLL_INTERVAL);
+}
+
+#[test]
+fn run_only_clicks_while_the_pixel_matches() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_pixel_color(Color::new(250, 5, 5));
+    let mut events = Vec::new();
+
+    let report = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .pixel_condition(pixel_condition(PixelTrigger::While))
+        .interval(ms(100))
+        .amount(2)
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |event| {
+                match event {
+                    JobEvent::Clicked(1) => backend.set_pixel_color(BLACK),
+                    JobEvent::Skipped(_) => backend.set_pixel_color(RED),
+                    _ => (),
+                }
+                events.push(event);
+            },
+        );
+
+    assert_eq!(
+        events,
+        vec![
+            JobEvent::Clicked(1),
+            JobEvent::Skipped("pixel 10/20 of color #ff0000 ±10 is #000000".to_owned()),
+            JobEvent::Resumed,
+            JobEvent::Clicked(2)
+        ]
+    );
+    assert_eq!(report.skipped_counter, 1);
+    assert_eq!(report.elapsed, ms(200));
+}
+
+#[test]
+fn run_stops_when_the_pixel_changes() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_pixel_color(RED);
+
+    let report = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .pixel_condition(pixel_condition(PixelTrigger::StopOnChange))
+        .interval(ms(100))
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |event| {
+                if event == JobEvent::Clicked(2) {
+                    backend.set_pixel_color(BLACK);
+                }
+            },
+        );
+
+    assert_eq!(report.click_counter, 2);
+    assert_eq!(report.stop_reason, StopReason::PixelChanged);
+    assert_eq!(report.elapsed, ms(200));
+    assert_eq!(backend.events(), vec![PRESS, RELEASE, PRESS, RELEASE]);
+}
+
+/// A screen of random pixels, where any crop is found at a single place
+fn noise_screen(seed: u64) -> RgbImage {
+    let mut rng = StdRng::seed_from_u64(seed);
+    RgbImage::from_fn(320, 240, |_, _| image::Rgb(rng.random()))
+}
+
+/// Save a crop of the screen as the target image, in a file unique to the test
+fn save_target(screen: &RgbImage, name: &str, region: Region) -> ImageTarget {
+    let path = std::env::temp_dir().join(format!(
+        "rusty-autoclicker-{name}-{}.png",
+        std::process::id()
+    ));
+    imageops::crop_imm(
+        screen,
+        region.x as u32,
+        region.y as u32,
+        region.width,
+        region.height,
+    )
+    .to_image()
+    .save(&path)
+    .unwrap();
+    ImageTarget {
+        path,
+        ..ImageTarget::default()
+    }
+}
+
+#[test]
+fn region_is_written_like_an_x11_geometry() {
+    let region = Region {
+        x: -1920,
+        y: 0,
+        width: 800,
+        height: 600,
+    };
+    assert_eq!(region.to_string(), "800x600-1920+0");
+    assert_eq!("800x600-1920+0".parse(), Ok(region));
+    assert!("800x600".parse::<Region>().is_err());
+    assert!("0x600+0+0".parse::<Region>().is_err());
+}
+
+#[test]
+fn template_is_found_in_the_screen() {
+    let screen = noise_screen(SEED);
+    // Halved only, and downscaled further
+    for (width, height) in [(12, 10), (64, 48)] {
+        let crop = imageops::crop_imm(&screen, 200, 150, width, height).to_image();
+        let template = Template::from_image(&DynamicImage::ImageRgb8(crop)).unwrap();
+
+        let found = template.find(&screen, (1920, 0)).unwrap();
+        assert_eq!((found.x, found.y), (1920 + 200, 150));
+        assert!(found.score > 0.99, "score {}", found.score);
+    }
+
+    let flat = RgbImage::from_pixel(8, 8, image::Rgb([40, 40, 40]));
+    assert!(Template::from_image(&DynamicImage::ImageRgb8(flat)).is_err());
+}
+
+#[test]
+fn small_template_is_found_at_any_offset() {
+    let screen = noise_screen(SEED);
+    // Under twice the coarse size, at even & odd offsets of the downscaled screen
+    for (width, height) in [(12, 10), (6, 6), (15, 4)] {
+        for (x, y) in [(201, 151), (36, 90), (0, 0), (320 - width, 240 - height)] {
+            let crop = imageops::crop_imm(&screen, x, y, width, height).to_image();
+            let template = Template::from_image(&DynamicImage::ImageRgb8(crop)).unwrap();
+
+            let found = template.find(&screen, (0, 0)).unwrap();
+            assert_eq!((found.x, found.y), (x as i32, y as i32), "{width}x{height}");
+            assert!(found.score > 0.99, "score {}", found.score);
+        }
+    }
+}
+
+#[test]
+fn image_target_clicks_around_the_image() {
+    let screen = noise_screen(SEED);
+    let region = Region {
+        x: 100,
+        y: 60,
+        width: 40,
+        height: 30,
+    };
+    let image = save_target(&screen, "offset", region);
+    let backend = MockBackend::new().with_screen(screen);
+    let job = job(AppMode::Bot, ClickType::Single, (5.0, -3.0).into()).image(image.clone());
+
+    job.click(
+        &mut backend.clone(),
+        &mut VirtualClock::new(),
+        &mut job.new_rng(),
+        MOUSE,
+    )
+    .unwrap();
+
+    // 5/-3 pixels from the center of the image
+    assert_eq!(
+        backend.events(),
+        vec![EventType::MouseMove { x: 125.0, y: 72.0 }, PRESS, RELEASE]
+    );
+    std::fs::remove_file(image.path).unwrap();
+}
+
+#[test]
+fn run_stops_when_the_image_is_missing() {
+    let screen = noise_screen(SEED);
+    let image = save_target(
+        &screen,
+        "missing",
+        Region {
+            x: 10,
+            y: 10,
+            width: 40,
+            height: 30,
+        },
+    );
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new()
+        .with_clock(clock.clone())
+        .with_screen(screen);
+
+    let report = job(AppMode::Bot, ClickType::Single, (0.0, 0.0).into())
+        .image(ImageTarget {
+            when_missing: MissingAction::Stop,
+            ..image.clone()
+        })
+        .interval(ms(100))
+        .run_on(
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |event| {
+                // The image goes away after the first click
+                if event == JobEvent::Clicked(1) {
+                    backend.set_screen(noise_screen(SEED + 1));
+                }
+            },
+        );
+
+    assert_eq!(report.click_counter, 1);
+    assert_eq!(report.stop_reason, StopReason::ImageMissing);
+    assert_eq!(report.elapsed, ms(100));
+    std::fs::remove_file(image.path).unwrap();
+}
+
+#[test]
+fn runner_clicks_at_the_image_center_searched_elsewhere() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+    // Never searched by the runner, the file does not exist
+    let job = job(AppMode::Bot, ClickType::Single, (5.0, -5.0).into()).image(ImageTarget {
+        path: "missing.png".into(),
+        ..ImageTarget::default()
+    });
+    let mut runner = JobRunner::new(job, &backend, clock.now()).search_image_elsewhere();
+    let mut events = Vec::new();
+    let mut step = |runner: &mut JobRunner| {
+        runner.step(&mut backend.clone(), &mut clock.clone(), &mut |event| {
+            events.push(event)
+        })
+    };
+
+    // Nothing is sent until the center is known
+    assert!(runner.needs_image_center());
+    assert_eq!(step(&mut runner), None);
+    assert_eq!(runner.due(), clock.now() + POLL_INTERVAL);
+
+    runner.set_image_center(Ok((100.0, 50.0)));
+    assert_eq!(step(&mut runner), None);
+    // Each center is used by one click
+    assert!(runner.needs_image_center());
+
+    // A missing image stops the job like a search at click time
+    runner.set_image_center(Err(ClickError {
+        event_type: None,
+        error: InputError::new("Image not found"),
+        kind: ClickErrorKind::Stopped(StopReason::ImageMissing),
+    }));
+    assert_eq!(step(&mut runner), Some(StopReason::ImageMissing));
+
+    assert_eq!(events, [JobEvent::Clicked(1)]);
+    assert_eq!(
+        backend.events(),
+        [EventType::MouseMove { x: 105.0, y: 45.0 }, PRESS, RELEASE]
+    );
+}
+
+fn sequence(steps: Vec<Step>) -> Sequence {
+    Sequence { steps, repeat: 1 }
+}
+
+#[test]
+fn sequence_plays_the_steps_at_their_times() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+    let sequence = sequence(vec![
+        Step::new(Action::Move { x: 25.0, y: 10.0 }, 100),
+        Step::new(
+            Action::Click {
+                button: Button::Left,
+                click_type: ClickType::Single,
+                coord: None,
+            },
+            50,
+        ),
+        Step::new(Action::Wait, 200),
+        Step::new(Action::Key { key: Key::KeyA }, 0),
+        Step::new(
+            Action::Wheel {
+                delta_x: 0,
+                delta_y: -3,
+            },
+            10,
+        ),
+    ]);
+    let mut played = Vec::new();
+
+    let stop_reason = sequence
+        .run_on(
+            &job(AppMode::Bot, ClickType::Single, Target::Mouse),
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |index| played.push(index),
+        )
+        .unwrap();
+
+    assert_eq!(stop_reason, StopReason::Completed);
+    assert_eq!(played, [0, 1, 2, 3, 4]);
+    assert_eq!(
+        backend.timed_events(),
+        [
+            (ms(0), MOVE_TO_TARGET),
+            (ms(100), PRESS),
+            (ms(100), RELEASE),
+            (ms(350), EventType::KeyPress(Key::KeyA)),
+            (ms(350), EventType::KeyRelease(Key::KeyA)),
+            (
+                ms(350),
+                EventType::Wheel {
+                    delta_x: 0,
+                    delta_y: -3
+                }
+            ),
+        ]
+    );
+    // The sequence is over once the last step ran its duration
+    assert_eq!(clock.elapsed(), sequence.total_duration());
+}
+
+#[test]
+fn sequence_moves_like_a_humanlike_click() {
+    let clock = VirtualClock::new();
+    let backend = MockBackend::new().with_clock(clock.clone());
+    let sequence = sequence(vec![Step::new(
+        Action::Move {
+            x: TARGET.0,
+            y: TARGET.1,
+        },
+        0,
+    )]);
+
+    sequence
+        .run_on(
+            &job(AppMode::Humanlike, ClickType::Single, Target::Mouse),
+            &mut backend.clone(),
+            &mut clock.clone(),
+            &AtomicBool::new(false),
+            |_| (),
+        )
+        .unwrap();
+
+    assert_eq!(backend.events(), HUMANLIKE_PATH);
+}
+
+#[test]
+fn sequence_types_text_with_shift() {
+    let backend = MockBackend::new();
+    let sequence = sequence(vec![Step::new(
+        Action::Text {
+            text: "Hi!".to_owned(),
+        },
+        0,
+    )]);
+
+    sequence
+        .run_on(
+            &job(AppMode::Bot, ClickType::Single, Target::Mouse),
+            &mut backend.clone(),
+            &mut VirtualClock::new(),
+            &AtomicBool::new(false),
+            |_| (),
+        )
+        .unwrap();
+
+    assert_eq!(
+        backend.events(),
+        [
+            EventType::KeyPress(Key::ShiftLeft),
+            EventType::KeyPress(Key::KeyH),
+            EventType::KeyRelease(Key::KeyH),
+            EventType::KeyRelease(Key::ShiftLeft),
+            EventType::KeyPress(Key::KeyI),
+            EventType::KeyRelease(Key::KeyI),
+            EventType::KeyPress(Key::ShiftLeft),
+            EventType::KeyPress(Key::Num1),
+            EventType::KeyRelease(Key::Num1),
+            EventType::KeyRelease(Key::ShiftLeft),
+        ]
+    );
+}
+
+#[test]
+fn sequence_releases_shift_when_a_key_fails() {
+    let backend = MockBackend::new().with_failing_key(Key::KeyH);
+    let sequence = sequence(vec![Step::new(
+        Action::Text {
+            text: "Hi".to_owned(),
+        },
+        0,
+    )]);
+
+    let error = sequence
+        .run_on(
+            &job(AppMode::Bot, ClickType::Single, Target::Mouse),
+            &mut backend.clone(),
+            &mut VirtualClock::new(),
+            &AtomicBool::new(false),
+            |_| (),
+        )
+        .unwrap_err();
+
+    assert_eq!(error.kind, ClickErrorKind::Failed);
+    assert_eq!(
+        backend.events(),
+        [
+            EventType::KeyPress(Key::ShiftLeft),
+            EventType::KeyRelease(Key::KeyH),
+            EventType::KeyRelease(Key::ShiftLeft),
+        ]
+    );
+}
+
+#[test]
+fn sequence_rejects_text_it_cannot_type() {
+    let sequence = sequence(vec![
+        Step::new(Action::Wait, 10),
+        Step::new(
+            Action::Text {
+                text: "café".to_owned(),
+            },
+            0,
+        ),
+    ]);
+
+    let error = sequence.check().unwrap_err();
+
+    assert_eq!(error.message, "Step 2: Cannot type 'é'");
+}
+
+#[test]
+fn sequence_repeats_and_moves_the_playhead() {
+    let clock = VirtualClock::new();
+    let mut backend = MockBackend::new().with_clock(clock.clone());
+    let sequence = Sequence {
+        repeat: 2,
+        ..sequence(vec![
+            Step::new(Action::Key { key: Key::KeyA }, 100),
+            Step::new(Action::Wait, 300),
+        ])
+    };
+    let mut player = SequencePlayer::new(
+        sequence,
+        &job(AppMode::Bot, ClickType::Single, Target::Mouse),
+        &backend,
+        clock.now(),
+    );
+    let mut clock = clock;
+
+    player.step(&mut backend, &mut clock).unwrap();
+    clock.advance(ms(40));
+    assert_eq!(player.position(clock.now()), ms(40));
+    clock.advance(ms(60));
+    player.step(&mut backend, &mut clock).unwrap();
+    clock.advance(ms(500));
+    // The playhead waits at the end of the step until the next one is played
+    assert_eq!(player.position(clock.now()), ms(400));
+    assert!(!player.is_over());
+
+    player.step(&mut backend, &mut clock).unwrap();
+    player.step(&mut backend, &mut clock).unwrap();
+    assert!(player.is_over());
+    assert_eq!(player.loop_counter(), 2);
+    assert_eq!(backend.events().len(), 4);
+}
+
+#[test]
+fn sequence_round_trips_through_toml() {
+    let sequence = sequence(vec![
+        Step::new(
+            Action::Click {
+                button: Button::Right,
+                click_type: ClickType::Double,
+                coord: Some(TARGET),
+            },
+            100,
+        ),
+        Step::new(
+            Action::Click {
+                button: Button::Left,
+                click_type: ClickType::Single,
+                coord: None,
+            },
+            100,
+        ),
+        Step::new(Action::Key { key: Key::Return }, 50),
+        Step::new(
+            Action::Text {
+                text: "hello".to_owned(),
+            },
+            500,
+        ),
+        Step::new(Action::Wait, 1000),
+    ]);
+
+    let text = toml::to_string(&sequence).unwrap();
+
+    assert_eq!(toml::from_str::<Sequence>(&text).unwrap(), sequence);
+}
+
+#[test]
+fn xdotool_script_is_imported() {
+    let script = r#"#!/bin/sh
+# Open the search & look for something
+xdotool mousemove 640 360 click 1
+sleep 0.5
+xdotool key --delay 100 ctrl Return
+xdotool type --delay 10 "hello 'world'"; sleep 1s
+xdotool click --repeat 2 --delay 50 3 click --repeat 3 5
+"#;
+
+    let sequence = xdotool::parse_script(script).unwrap();
+
+    assert_eq!(sequence.repeat, 1);
+    assert_eq!(
+        sequence.steps,
+        [
+            Step::new(Action::Move { x: 640.0, y: 360.0 }, 0),
+            Step::new(
+                Action::Click {
+                    button: Button::Left,
+                    click_type: ClickType::Single,
+                    coord: None,
+                },
+                500,
+            ),
+            Step::new(
+                Action::Key {
+                    key: Key::ControlLeft
+                },
+                100
+            ),
+            Step::new(Action::Key { key: Key::Return }, 100),
+            Step::new(
+                Action::Text {
+                    text: "hello 'world'".to_owned()
+                },
+                1130
+            ),
+            Step::new(
+                Action::Click {
+                    button: Button::Right,
+                    click_type: ClickType::Single,
+                    coord: None,
+                },
+                50,
+            ),
+            Step::new(
+                Action::Click {
+                    button: Button::Right,
+                    click_type: ClickType::Single,
+                    coord: None,
+                },
+                0,
+            ),
+            Step::new(
+                Action::Wheel {
+                    delta_x: 0,
+                    delta_y: -3
+                },
+                200
+            ),
+        ]
+    );
+}
+
+#[test]
+fn xdotool_loop_becomes_the_repeat_count() {
+    let script = "for i in $(seq 5); do\n    xdotool key space\n    sleep 2\ndone\n";
+
+    let sequence = xdotool::parse_script(script).unwrap();
+
+    assert_eq!(sequence.repeat, 5);
+    assert_eq!(
+        sequence.steps,
+        [Step::new(Action::Key { key: Key::Space }, 2012)]
+    );
+    assert_eq!(
+        xdotool::parse_script("while true; do xdotool click 1; done")
+            .unwrap()
+            .repeat,
+        0
+    );
+}
+
+#[test]
+fn xdotool_unsupported_commands_are_rejected() {
+    for (script, message) in [
+        (
+            "xdotool key ctrl+c",
+            "Command 1: Key combinations like ctrl+c are not supported",
+        ),
+        (
+            "sleep 1\nxdotool windowactivate 42",
+            "Command 2: Unsupported xdotool command windowactivate",
+        ),
+        (
+            "xdotool mousemove --window 42 10 10",
+            "Command 1: Unsupported option --window of mousemove",
+        ),
+        (
+            "notify-send done",
+            "Command 1: Unsupported command notify-send",
+        ),
+        ("xdotool type 'oops", "Unterminated ' quote"),
+        (
+            "xdotool click 1\nfor i in $(seq 2); do",
+            "Command 2: Only a loop around the whole script is supported",
+        ),
+        ("sleep 1e300", "Command 1: Invalid duration \"1e300\""),
+        ("sleep 1e20h", "Command 1: Invalid duration \"1e20h\""),
+        ("sleep -1", "Command 1: Invalid duration \"-1\""),
+        ("sleep inf", "Command 1: Invalid duration \"inf\""),
+        (
+            "xdotool click --repeat 18446744073709551615 1",
+            "Command 1: --repeat must be at most 100000",
+        ),
+        (
+            "xdotool key --delay 18446744073709551615 space",
+            "Command 1: --delay must be at most 3600000",
+        ),
+        (
+            "xdotool key --repeat 100000 a b",
+            "Command 1: The script is longer than 100000 steps",
+        ),
+        (
+            "xdotool click --repeat 100000 1\nxdotool click 1",
+            "Command 2: The script is longer than 100000 steps",
+        ),
+    ] {
+        assert_eq!(xdotool::parse_script(script).unwrap_err().message, message);
+    }
+}
+
+#[test]
+fn xdotool_longest_repeats_and_delays_are_imported() {
+    let script = "xdotool click --repeat 100000 --delay 3600000 4\n\
+                  xdotool type --delay 3600000 hello";
+
+    let sequence = xdotool::parse_script(script).unwrap();
+    assert_eq!(sequence.steps.len(), 2);
+    assert_eq!(
+        sequence.steps[0].action,
+        Action::Wheel {
+            delta_x: 0,
+            delta_y: 100_000
+        }
+    );
+    assert_eq!(sequence.steps[0].duration_ms, 99_999 * 3_600_000);
+    assert_eq!(sequence.steps[1].duration_ms, 5 * 3_600_000);
+}
+
+#[test]
+fn xdotool_script_round_trips() {
+    let sequence = Sequence {
+        repeat: 3,
+        steps: vec![
+            Step::new(
+                Action::Click {
+                    button: Button::Left,
+                    click_type: ClickType::Single,
+                    coord: Some(TARGET),
+                },
+                250,
+            ),
+            Step::new(Action::Key { key: Key::F5 }, 0),
+            Step::new(
+                Action::Text {
+                    text: "it's done".to_owned(),
+                },
+                1500,
+            ),
+            Step::new(
+                Action::Wheel {
+                    delta_x: 0,
+                    delta_y: 4,
+                },
+                0,
+            ),
+            Step::new(Action::Move { x: 1.5, y: 2.0 }, 10),
+        ],
+    };
+
+    let script = xdotool::write_script(&sequence).unwrap();
+
+    assert_eq!(
+        script,
+        "#!/bin/sh
+# Exported from Rusty AutoClicker
+for _ in $(seq 3); do
+    xdotool mousemove 25 10 click 1
+    sleep 0.25
+    xdotool key --delay 0 F5
+    xdotool type --delay 0 'it'\\''s done'
+    sleep 1.5
+    xdotool click --repeat 4 --delay 0 4
+    xdotool mousemove 1.5 2
+    sleep 0.01
+done
+"
+    );
+    let imported = xdotool::parse_script(&script).unwrap();
+    // The click at coordinates comes back as a move & a click at the mouse
+    assert_eq!(imported.repeat, 3);
+    assert_eq!(
+        imported.steps[0],
+        Step::new(Action::Move { x: 25.0, y: 10.0 }, 0)
+    );
+    assert_eq!(imported.steps[2..], sequence.steps[1..]);
+}
+
+#[test]
+fn job_is_exported_as_a_sequence() {
+    let sequence = job(AppMode::Bot, ClickType::Double, Target::Mouse)
+        .interval(ms(200))
+        .points(vec![
+            TargetPoint::new("a", 10.0, 20.0),
+            TargetPoint::new("b", 30.0, 40.0),
+        ])
+        .amount(3)
+        .to_sequence()
+        .unwrap();
+    let click = |coord| {
+        Step::new(
+            Action::Click {
+                button: Button::Left,
+                click_type: ClickType::Double,
+                coord: Some(coord),
+            },
+            200,
+        )
+    };
+
+    // 3 clicks on 2 points do not make whole rounds
+    assert_eq!(sequence.repeat, 1);
+    assert_eq!(
+        sequence.steps,
+        [
+            click((10.0, 20.0)),
+            click((30.0, 40.0)),
+            click((10.0, 20.0))
+        ]
+    );
+
+    let key_sequence = job(AppMode::Bot, ClickType::Double, Target::Mouse)
+        .button(ClickButton::Key(Key::KeyF))
+        .interval(ms(100))
+        .to_sequence()
+        .unwrap();
+    assert_eq!(key_sequence.repeat, 0);
+    assert_eq!(
+        key_sequence.steps,
+        [
+            Step::new(Action::Key { key: Key::KeyF }, 0),
+            Step::new(Action::Key { key: Key::KeyF }, 100),
+        ]
+    );
+}
+
+#[test]
+fn job_is_exported_as_a_sequence_without_unrolling_whole_rounds() {
+    let points = vec![
+        TargetPoint::new("a", 10.0, 20.0),
+        TargetPoint::new("b", 30.0, 40.0),
+    ];
+
+    let sequence = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .points(points.clone())
+        .amount(1_000_000_000_000)
+        .to_sequence()
+        .unwrap();
+
+    assert_eq!(sequence.steps.len(), 2);
+    assert_eq!(sequence.repeat, 500_000_000_000);
+
+    // Too many clicks to unroll
+    let error = job(AppMode::Bot, ClickType::Single, Target::Mouse)
+        .points(points)
+        .amount(MAX_UNROLLED_CLICKS + 1)
+        .to_sequence()
+        .unwrap_err();
+    assert!(error.message.starts_with("100001 clicks on 2 points"));
+}
+
+#[test]
+fn history_is_exported_as_a_sequence() {
+    let mut history = ClickHistory::default();
+    for event_type in [MOVE_TO_TARGET, PRESS, RELEASE, PRESS] {
+        history.record(&event_type, AppMode::Bot);
+    }
+
+    let sequence = history.to_sequence();
+
+    // The press without its release is left out
+    let actions: Vec<Action> = sequence.steps.into_iter().map(|step| step.action).collect();
+    assert_eq!(
+        actions,
+        [
+            Action::Move {
+                x: TARGET.0,
+                y: TARGET.1
+            },
+            Action::Click {
+                button: Button::Left,
+                click_type: ClickType::Single,
+                coord: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn history_records_the_events_at_the_last_position() {
+    let mut history = ClickHistory::default();
+    for event_type in [
+        PRESS,
+        MOVE_TO_TARGET,
+        RELEASE,
+        EventType::KeyPress(Key::KeyA),
+        EventType::Wheel {
+            delta_x: 0,
+            delta_y: -3,
+        },
+    ] {
+        history.record(&event_type, AppMode::Humanlike);
+    }
+
+    let entries: Vec<_> = history
+        .entries()
+        .iter()
+        .map(|entry| {
+            (
+                entry.event.as_str(),
+                entry.button.as_deref(),
+                entry.key.as_deref(),
+                entry.x.zip(entry.y),
+                entry.delta_y,
+            )
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("press", Some("Left"), None, None, None),
+            ("move", None, None, Some(TARGET), None),
+            ("release", Some("Left"), None, Some(TARGET), None),
+            ("key_press", None, Some("KeyA"), Some(TARGET), None),
+            ("wheel", None, None, Some(TARGET), Some(-3)),
+        ]
+    );
+    assert!(
+        history
+            .entries()
+            .iter()
+            .all(|entry| entry.mode == AppMode::Humanlike)
+    );
+    assert_eq!(history.recorded_count(), 5);
+}
+
+#[test]
+fn history_drops_the_oldest_events_when_full() {
+    let mut history = ClickHistory::default();
+    history.record(&MOVE_TO_TARGET, AppMode::Bot);
+    for _ in 0..HISTORY_CAPACITY {
+        history.record(&PRESS, AppMode::Bot);
+    }
+
+    assert_eq!(history.entries().len(), HISTORY_CAPACITY);
+    assert_eq!(history.recorded_count(), HISTORY_CAPACITY as u64 + 1);
+    assert!(history.entries().iter().all(|entry| entry.event == "press"));
+}
+
+#[test]
+fn recording_backend_starts_at_the_mouse_position() {
+    let mut backend = MockBackend::new()
+        .with_mouse_position(5, 7)
+        .with_failing_key(Key::KeyA);
+    let mut history = ClickHistory::default();
+    let mut recorder = RecordingBackend::new(&mut backend, &mut history, AppMode::Bot);
+
+    recorder.press(Button::Left).unwrap();
+    recorder.key_press(Key::KeyA).unwrap_err();
+
+    // The event that failed is not recorded
+    assert_eq!(history.entries().len(), 1);
+    assert_eq!(history.entries()[0].x, Some(5.0));
+    assert_eq!(history.entries()[0].y, Some(7.0));
+}
+
+#[test]
+fn history_is_written_as_csv() {
+    let mut history = ClickHistory::default();
+    for event_type in [MOVE_TO_TARGET, PRESS, EventType::KeyRelease(Key::KeyA)] {
+        history.record(&event_type, AppMode::Bot);
+    }
+    let mut csv = Vec::new();
+
+    history.write_csv(&mut csv).unwrap();
+
+    // Leave out the timestamps, the time the events were recorded at
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("timestamp_ms,event,button,key,x,y,delta_x,delta_y,mode")
+    );
+    let rows: Vec<&str> = lines.map(|line| line.split_once(',').unwrap().1).collect();
+    assert_eq!(
+        rows,
+        [
+            "move,,,25,10,,,Bot",
+            "press,Left,,25,10,,,Bot",
+            "key_release,,KeyA,25,10,,,Bot",
+        ]
+    );
+}
+
+#[test]
+fn history_is_written_as_json_lines() {
+    let mut history = ClickHistory::default();
+    for event_type in [
+        MOVE_TO_TARGET,
+        EventType::Wheel {
+            delta_x: 1,
+            delta_y: -3,
+        },
+    ] {
+        history.record(&event_type, AppMode::Humanlike);
+    }
+    let mut json_lines = Vec::new();
+
+    history.write_json_lines(&mut json_lines).unwrap();
+
+    let entries: Vec<HistoryEntry> = String::from_utf8(json_lines)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries, Vec::from(history.entries().clone()));
+}
+
+/// The statistics of clicks sent the given times later than a 100 ms interval
+///
+/// # Arguments
+///
+/// * `deviations_ms` - How late each click after the first one was, in milliseconds
+fn stats_with_deviations(deviations_ms: &[u64]) -> StatsSummary {
+    let mut stats = ClickStats::default();
+    let mut now = Instant::now();
+    stats.start(ms(100), now);
+    stats.record(now);
+    for deviation in deviations_ms {
+        now += ms(100 + deviation);
+        stats.record(now);
+    }
+    stats.summary(now)
+}
+
+#[test]
+fn stats_percentiles_are_zero_without_intervals() {
+    let summary = stats_with_deviations(&[]);
+
+    assert_eq!(summary.click_counter, 1);
+    assert_eq!(summary.mean_deviation, Duration::ZERO);
+    assert_eq!(summary.p50_deviation, Duration::ZERO);
+    assert_eq!(summary.p99_deviation, Duration::ZERO);
+}
+
+#[test]
+fn stats_percentiles_of_one_interval_are_its_deviation() {
+    let summary = stats_with_deviations(&[10]);
+
+    assert_eq!(summary.mean_deviation, ms(10));
+    assert_eq!(summary.p50_deviation, ms(10));
+    assert_eq!(summary.p95_deviation, ms(10));
+    assert_eq!(summary.p99_deviation, ms(10));
+}
+
+#[test]
+fn stats_percentiles_use_the_nearest_rank() {
+    // 1 to 20 ms late, out of order
+    let deviations: Vec<u64> = (1..=20).map(|index| index * 7 % 20 + 1).collect();
+
+    let summary = stats_with_deviations(&deviations);
+
+    // The 10th, 19th & 20th smallest of 20 deviations
+    assert_eq!(summary.p50_deviation, ms(10));
+    assert_eq!(summary.p95_deviation, ms(19));
+    assert_eq!(summary.p99_deviation, ms(20));
+    assert_eq!(summary.mean_deviation, Duration::from_micros(10_500));
+}
@@ -0,0 +1,132 @@
+//! Window targets & screen capture against a real X server, e.g.
+//! `xvfb-run cargo test --test window`
+//! The tests pass without doing anything when `$DISPLAY` is not set
+
+#![cfg(target_os = "linux")]
+
+use rusty_autoclicker::engine::{
+    ClickErrorKind, ClickJob,
+    backend::NullBackend,
+    pixel::{self, Color},
+    window::{self, WindowMatch},
+};
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{
+        AtomEnum, ConfigureWindowAux, ConnectionExt, CreateWindowAux, PropMode, Window, WindowClass,
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+};
+
+/// Open a mapped top-level window filled with the background pixel, `None` without an X server
+/// Tests run in parallel, so each one gives its window another title & place
+fn open_window(title: &str, x: i16, y: i16, background: u32) -> Option<(RustConnection, Window)> {
+    std::env::var_os("DISPLAY")?;
+    let (connection, screen_num) = x11rb::connect(None).ok()?;
+    let screen = &connection.setup().roots[screen_num];
+    let window = connection.generate_id().unwrap();
+
+    connection
+        .create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            x,
+            y,
+            320,
+            240,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new().background_pixel(background),
+        )
+        .unwrap();
+    connection
+        .change_property8(
+            PropMode::REPLACE,
+            window,
+            AtomEnum::WM_NAME,
+            AtomEnum::STRING,
+            title.as_bytes(),
+        )
+        .unwrap();
+    connection
+        .change_property8(
+            PropMode::REPLACE,
+            window,
+            AtomEnum::WM_CLASS,
+            AtomEnum::STRING,
+            b"clicktest\0ClickTest\0",
+        )
+        .unwrap();
+    connection.map_window(window).unwrap();
+    connection.sync().unwrap();
+    Some((connection, window))
+}
+
+#[test]
+fn window_is_found_by_title_and_class() {
+    let Some((connection, window)) = open_window("rusty-autoclicker window test", 40, 30, 0) else {
+        return;
+    };
+
+    let windows = window::windows().unwrap();
+    let by_title = WindowMatch::Title("window test$".to_owned())
+        .find(&windows)
+        .unwrap()
+        .unwrap();
+    assert_eq!(by_title.id, window);
+    assert_eq!((by_title.x, by_title.y), (40, 30));
+    assert_eq!((by_title.width, by_title.height), (320, 240));
+    let by_class = WindowMatch::Class("^ClickTest$".to_owned())
+        .find(&windows)
+        .unwrap()
+        .unwrap();
+    // The window of the other test may match too
+    assert_eq!(
+        (by_class.instance.as_str(), by_class.class.as_str()),
+        ("clicktest", "ClickTest")
+    );
+
+    connection.destroy_window(window).unwrap();
+    connection.sync().unwrap();
+}
+
+#[test]
+fn window_target_follows_the_window_and_pauses_when_closed() {
+    let Some((connection, window)) = open_window("rusty-autoclicker moving window", 0, 0, 0) else {
+        return;
+    };
+    let job = ClickJob::new()
+        .target((10.0, 20.0))
+        .window(WindowMatch::Id(window));
+
+    assert_eq!(job.target_pixels(&NullBackend), Ok(Some((10.0, 20.0))));
+
+    connection
+        .configure_window(window, &ConfigureWindowAux::new().x(100).y(50))
+        .unwrap();
+    connection.sync().unwrap();
+    assert_eq!(job.target_pixels(&NullBackend), Ok(Some((110.0, 70.0))));
+
+    connection.destroy_window(window).unwrap();
+    connection.sync().unwrap();
+    assert!(job.target_pixels(&NullBackend).unwrap_err().kind == ClickErrorKind::Paused);
+    assert_eq!(job.check_target(), Ok(()));
+}
+
+#[test]
+fn pixel_is_captured_from_the_screen() {
+    // 24-bit TrueColor, the default visual of Xvfb
+    let Some((connection, window)) = open_window("rusty-autoclicker pixel", 600, 400, 0x336699)
+    else {
+        return;
+    };
+
+    assert_eq!(pixel::pixel_at(610, 410), Ok(Color::new(0x33, 0x66, 0x99)));
+    assert!(pixel::pixel_at(-1, 0).is_err());
+
+    connection.destroy_window(window).unwrap();
+    connection.sync().unwrap();
+}